@@ -3,21 +3,15 @@ mod list;
 mod script;
 
 use clap::App;
-use futures::TryStreamExt;
-use nibard::{Database, Executor};
+use nibard::migrate::{Migration, Migrator};
+use nibard::Database;
 use tokio::io::AsyncWriteExt;
 async fn create_schema(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
-    let mut ctx = db.begin().await?;
-
-    let scheme = include_str!("./schema.sql");
-
-    ctx.execute_many(scheme)
-        .await
-        .try_collect::<Vec<_>>()
+    Migrator::new()
+        .migration(Migration::new(1, "schema").up_sql(include_str!("./schema.sql")))
+        .run(db)
         .await?;
 
-    ctx.commit().await?;
-
     Ok(())
 }
 
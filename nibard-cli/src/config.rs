@@ -0,0 +1,143 @@
+use nibard::Database;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE: &str = "nibard.toml";
+const DEFAULT_PROFILE: &str = "default";
+const MIGRATIONS_DIR: &str = "migrations";
+
+/// Connection profiles, read from `nibard.toml`:
+///
+/// ```toml
+/// [profiles.default]
+/// url = "sqlite:./dev.sqlite"
+/// migrations = "db/migrations"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    profiles: HashMap<String, Profile>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Profile {
+    url: String,
+    migrations: Option<PathBuf>,
+}
+
+impl Config {
+    /// Reads `path`, or `nibard.toml` in the working directory if it exists.
+    pub fn load(path: Option<&str>) -> Result<Config, Box<dyn Error>> {
+        let path = match path {
+            Some(path) => Path::new(path),
+            None if Path::new(CONFIG_FILE).exists() => Path::new(CONFIG_FILE),
+            None => return Ok(Config::default()),
+        };
+        let content =
+            std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let config =
+            toml::from_str(&content).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Ok(config)
+    }
+
+    /// The connection URL for `database`, a profile name or a URL as taken
+    /// by `ConnectOptions::from_str`. Without one the default profile is used.
+    pub fn url<'a>(&'a self, database: Option<&'a str>) -> Result<&'a str, Box<dyn Error>> {
+        match database {
+            Some(database) => Ok(self
+                .profiles
+                .get(database)
+                .map_or(database, |profile| profile.url.as_str())),
+            None => self
+                .profiles
+                .get(DEFAULT_PROFILE)
+                .map(|profile| profile.url.as_str())
+                .ok_or_else(|| {
+                    "no database given, pass --database or add a default profile".into()
+                }),
+        }
+    }
+
+    pub fn migrations(&self, database: Option<&str>) -> PathBuf {
+        self.profiles
+            .get(database.unwrap_or(DEFAULT_PROFILE))
+            .and_then(|profile| profile.migrations.clone())
+            .unwrap_or_else(|| PathBuf::from(MIGRATIONS_DIR))
+    }
+
+    pub async fn open(&self, database: Option<&str>) -> Result<Database, Box<dyn Error>> {
+        let db = Database::open(self.url(database)?).await?;
+        Ok(db)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config(name: &str, toml: &str) -> Config {
+        let path =
+            std::env::temp_dir().join(format!("nibard-cli-{}-{}.toml", std::process::id(), name));
+        std::fs::write(&path, toml).unwrap();
+        let config = Config::load(path.to_str());
+        std::fs::remove_file(&path).unwrap();
+        config.unwrap()
+    }
+
+    #[test]
+    fn profiles() {
+        let config = config(
+            "profiles",
+            r#"
+            [profiles.default]
+            url = "sqlite:./dev.sqlite"
+
+            [profiles.prod]
+            url = "postgres://db/app"
+            migrations = "db/migrations"
+            "#,
+        );
+
+        assert_eq!(config.url(None).unwrap(), "sqlite:./dev.sqlite");
+        assert_eq!(config.url(Some("prod")).unwrap(), "postgres://db/app");
+        assert_eq!(
+            config.url(Some("mysql://localhost/test")).unwrap(),
+            "mysql://localhost/test"
+        );
+
+        assert_eq!(config.migrations(None), PathBuf::from("migrations"));
+        assert_eq!(
+            config.migrations(Some("prod")),
+            PathBuf::from("db/migrations")
+        );
+        assert_eq!(
+            config.migrations(Some("sqlite::memory:")),
+            PathBuf::from("migrations")
+        );
+    }
+
+    #[test]
+    fn missing_default() {
+        let config = config(
+            "missing-default",
+            "[profiles.prod]\nurl = \"postgres://db/app\"\n",
+        );
+        let err = config.url(None).unwrap_err();
+        assert!(err.to_string().contains("no database given"));
+        assert_eq!(config.url(Some("prod")).unwrap(), "postgres://db/app");
+    }
+
+    #[test]
+    fn load_errors() {
+        let err = Config::load(Some("does-not-exist.toml")).unwrap_err();
+        assert!(err.to_string().starts_with("does-not-exist.toml: "));
+
+        let path = std::env::temp_dir().join(format!("nibard-cli-{}-bad.toml", std::process::id()));
+        std::fs::write(&path, "[profiles.default]\n").unwrap();
+        let err = Config::load(path.to_str()).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.to_string().contains("url"));
+    }
+}
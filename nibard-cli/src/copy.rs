@@ -0,0 +1,26 @@
+use super::config::Config;
+use clap::{App, Arg, ArgMatches};
+use nibard::Database;
+
+pub fn make() -> App<'static> {
+    App::new("copy")
+        .about("Copies the tables and rows of the database to another database")
+        .arg(
+            Arg::new("to")
+                .takes_value(true)
+                .required(true)
+                .help("Connection URL or profile name of the target"),
+        )
+}
+
+pub async fn run(
+    db: &Database,
+    config: &Config,
+    args: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dst = config.open(args.value_of("to")).await?;
+    for (table, rows) in nibard::dump::copy_database(db, &dst).await? {
+        println!("{}: {} rows", table, rows);
+    }
+    Ok(())
+}
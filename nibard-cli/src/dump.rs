@@ -0,0 +1,49 @@
+use super::output;
+use clap::{App, Arg, ArgMatches};
+use futures::TryStreamExt;
+use nibard::prelude::*;
+use nibard::Database;
+use std::fs::File;
+use std::io::{self, BufWriter};
+
+pub fn make() -> App<'static> {
+    App::new("dump")
+        .about("Writes the tables, rows and indexes of the database as SQL")
+        .arg(output::dialect_arg())
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .takes_value(true)
+                .help("File to write, stdout by default"),
+        )
+}
+
+pub fn make_restore() -> App<'static> {
+    App::new("restore")
+        .about("Runs a SQL file, eg. a dump, in a transaction")
+        .arg(Arg::new("file").takes_value(true).required(true))
+}
+
+pub async fn run(db: &Database, args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let dialect = output::dialect(args.value_of("dialect"), db.dialect());
+    match args.value_of("output") {
+        Some(path) => nibard::dump::dump(db, BufWriter::new(File::create(path)?), dialect).await?,
+        None => nibard::dump::dump(db, BufWriter::new(io::stdout()), dialect).await?,
+    }
+    Ok(())
+}
+
+pub async fn restore(db: &Database, args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let path = args.value_of("file").unwrap_or_default();
+    let sql = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+
+    let mut tx = db.begin().await?;
+    (&mut tx)
+        .execute_many(sql.as_str())
+        .await
+        .try_collect::<Vec<_>>()
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+mod config;
+mod copy;
+mod dump;
+mod migrate;
+mod output;
+mod query;
+mod schema;
+
+use clap::{App, AppSettings, Arg};
+use config::Config;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let app = App::new("nibard")
+        .about("Queries, migrates, dumps and copies databases")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .short('c')
+                .takes_value(true)
+                .help("Config file with connection profiles, nibard.toml by default"),
+        )
+        .arg(
+            Arg::new("database")
+                .long("database")
+                .short('d')
+                .takes_value(true)
+                .help("Connection URL or profile name, the default profile if left out"),
+        )
+        .subcommand(query::make())
+        .subcommand(schema::make())
+        .subcommand(migrate::make())
+        .subcommand(dump::make())
+        .subcommand(dump::make_restore())
+        .subcommand(copy::make())
+        .get_matches();
+
+    let config = Config::load(app.value_of("config"))?;
+    let database = app.value_of("database");
+    let db = config.open(database).await?;
+
+    match app.subcommand() {
+        Some(("query", args)) => query::run(&db, args).await?,
+        Some(("schema", args)) => schema::run(&db, args).await?,
+        Some(("migrate", args)) => migrate::run(&db, &config.migrations(database), args).await?,
+        Some(("dump", args)) => dump::run(&db, args).await?,
+        Some(("restore", args)) => dump::restore(&db, args).await?,
+        Some(("copy", args)) => copy::run(&db, &config, args).await?,
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
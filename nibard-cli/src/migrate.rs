@@ -0,0 +1,79 @@
+use clap::{App, AppSettings, Arg, ArgMatches};
+use nibard::migrate::Migrator;
+use nibard::Database;
+use std::path::Path;
+
+pub fn make() -> App<'static> {
+    let dry_run = Arg::new("dry-run")
+        .long("dry-run")
+        .help("Prints the SQL instead of running it");
+
+    App::new("migrate")
+        .about("Applies, reverts and lists migrations")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::new("dir")
+                .long("dir")
+                .takes_value(true)
+                .help("Migration directory, the profile's or ./migrations by default"),
+        )
+        .subcommand(
+            App::new("up")
+                .about("Applies the pending migrations")
+                .arg(dry_run.clone()),
+        )
+        .subcommand(
+            App::new("down")
+                .about("Reverts the migrations above a version")
+                .arg(Arg::new("version").takes_value(true).required(true))
+                .arg(dry_run),
+        )
+        .subcommand(App::new("status").about("Lists applied and pending migrations"))
+}
+
+pub async fn run(
+    db: &Database,
+    dir: &Path,
+    args: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = args.value_of("dir").map_or(dir, Path::new);
+    let migrator = Migrator::from_dir(dir)?;
+
+    match args.subcommand() {
+        Some(("up", args)) if args.is_present("dry-run") => {
+            print!("{}", migrator.plan(db).await?);
+        }
+        Some(("up", _)) => {
+            for migration in migrator.run(db).await?.migrations {
+                println!("applied {} {}", migration.version, migration.name);
+            }
+        }
+        Some(("down", args)) => {
+            let version = args
+                .value_of("version")
+                .unwrap_or_default()
+                .parse::<i64>()
+                .map_err(|_| "version has to be a number")?;
+            if args.is_present("dry-run") {
+                print!("{}", migrator.plan_rollback(db, version).await?);
+            } else {
+                for migration in migrator.rollback(db, version).await?.migrations {
+                    println!("reverted {} {}", migration.version, migration.name);
+                }
+            }
+        }
+        Some(("status", _)) => {
+            let applied = migrator.applied(db).await?;
+            for migration in migrator.migrations() {
+                let status = if applied.iter().any(|a| a.version == migration.version) {
+                    "applied"
+                } else {
+                    "pending"
+                };
+                println!("{:>8} {} {}", status, migration.version, migration.name);
+            }
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
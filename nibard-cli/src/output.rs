@@ -0,0 +1,244 @@
+use clap::Arg;
+use futures::{pin_mut, Stream, TryStreamExt};
+use nibard::connection::Error;
+use nibard::csv::{self, CsvOptions};
+use nibard::prelude::*;
+use nibard::{DatabaseRow, Dialect, Value};
+use serde_json::{Map, Number, Value as Json};
+use std::io::{self, Write};
+
+pub fn format_arg() -> Arg<'static> {
+    Arg::new("format")
+        .long("format")
+        .takes_value(true)
+        .possible_values(["table", "json", "csv"])
+        .default_value("table")
+        .help("Output format")
+}
+
+pub fn dialect_arg() -> Arg<'static> {
+    Arg::new("dialect")
+        .long("dialect")
+        .takes_value(true)
+        .possible_values(["sqlite", "postgres", "mysql"])
+        .help("SQL dialect to write, the database's by default")
+}
+
+pub fn dialect(name: Option<&str>, default: Dialect) -> Dialect {
+    match name {
+        Some("sqlite") => Dialect::Sqlite,
+        Some("postgres") => Dialect::Pg,
+        Some("mysql") => Dialect::MySQL,
+        _ => default,
+    }
+}
+
+/// Prints `rows` to stdout in `format`, one of the values of `format_arg`.
+pub async fn print<S>(rows: S, format: &str) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: Stream<Item = Result<DatabaseRow, Error>>,
+{
+    write(rows, format, io::stdout()).await
+}
+
+async fn write<S, W>(rows: S, format: &str, mut out: W) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: Stream<Item = Result<DatabaseRow, Error>>,
+    W: Write,
+{
+    if format == "csv" {
+        csv::export(rows, out, &CsvOptions::new()).await?;
+        return Ok(());
+    }
+
+    pin_mut!(rows);
+    let mut columns = Vec::new();
+    let mut records = Vec::new();
+    while let Some(row) = rows.try_next().await? {
+        if columns.is_empty() {
+            columns = row
+                .columns()
+                .iter()
+                .map(|column| column.name.to_owned())
+                .collect::<Vec<_>>();
+        }
+        let mut record = Vec::with_capacity(columns.len());
+        for column in &columns {
+            record.push(row.try_get(column, None)?);
+        }
+        records.push(record);
+    }
+
+    if format == "json" {
+        let rows = records
+            .into_iter()
+            .map(|record| {
+                Json::Object(
+                    columns
+                        .iter()
+                        .cloned()
+                        .zip(record.into_iter().map(json))
+                        .collect::<Map<_, _>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_writer_pretty(&mut out, &rows)?;
+        writeln!(out)?;
+    } else {
+        print_table(&mut out, &columns, &records)?;
+    }
+    Ok(())
+}
+
+fn print_table<W: Write>(
+    out: &mut W,
+    columns: &[String],
+    records: &[Vec<Value>],
+) -> io::Result<()> {
+    let options = CsvOptions::new().null("NULL");
+    let cells = records
+        .iter()
+        .map(|record| {
+            record
+                .iter()
+                .map(|value| options.format(value).replace('\n', " "))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let widths = columns
+        .iter()
+        .enumerate()
+        .map(|(idx, column)| {
+            cells
+                .iter()
+                .map(|row| row[idx].chars().count())
+                .chain(Some(column.chars().count()))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    let line = |out: &mut W, fields: &mut dyn Iterator<Item = &str>| -> io::Result<()> {
+        let fields = fields
+            .zip(&widths)
+            .map(|(field, width)| format!("{:width$}", field, width = width))
+            .collect::<Vec<_>>();
+        writeln!(out, "{}", fields.join(" | ").trim_end())
+    };
+
+    line(out, &mut columns.iter().map(String::as_str))?;
+    let rule = widths
+        .iter()
+        .map(|width| "-".repeat(*width))
+        .collect::<Vec<_>>();
+    writeln!(out, "{}", rule.join("-+-"))?;
+    for row in &cells {
+        line(out, &mut row.iter().map(String::as_str))?;
+    }
+    writeln!(out, "({} rows)", cells.len())
+}
+
+fn json(value: Value) -> Json {
+    match value {
+        Value::Null => Json::Null,
+        Value::Text(text) => Json::String(text),
+        Value::SmallInt(i) => Json::from(i),
+        Value::Int(i) => Json::from(i),
+        Value::BigInt(i) => Json::from(i),
+        Value::Float(f) => Number::from_f64(f).map_or(Json::Null, Json::Number),
+        Value::Real(f) => Number::from_f64(f as f64).map_or(Json::Null, Json::Number),
+        Value::Bool(b) => Json::Bool(b),
+        Value::Json(json) => json,
+        value => Json::String(CsvOptions::new().format(&value).into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nibard::query::Query;
+    use nibard::Database;
+
+    const SQL: &str = "SELECT 1 AS id, 'lamp' AS name, 9.5 AS price, NULL AS note \
+                       UNION ALL SELECT 2, 'desk\nlamp', 0.25, 'a, b'";
+
+    async fn render(format: &str) -> String {
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        let mut out = Vec::new();
+        write(
+            Query::new(SQL.to_owned(), Vec::new()).fetch(&db),
+            format,
+            &mut out,
+        )
+        .await
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[tokio::test]
+    async fn table() {
+        assert_eq!(
+            render("table").await,
+            "id | name      | price | note\n\
+             ---+-----------+-------+-----\n\
+             1  | lamp      | 9.5   | NULL\n\
+             2  | desk lamp | 0.25  | a, b\n\
+             (2 rows)\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn json_rows() {
+        assert_eq!(
+            render("json").await,
+            r#"[
+  {
+    "id": 1,
+    "name": "lamp",
+    "price": 9.5,
+    "note": null
+  },
+  {
+    "id": 2,
+    "name": "desk\nlamp",
+    "price": 0.25,
+    "note": "a, b"
+  }
+]
+"#
+        );
+    }
+
+    #[tokio::test]
+    async fn csv_rows() {
+        assert_eq!(
+            render("csv").await,
+            "id,name,price,note\n1,lamp,9.5,\n2,\"desk\nlamp\",0.25,\"a, b\"\n"
+        );
+    }
+
+    #[test]
+    fn empty_table() {
+        let mut out = Vec::new();
+        print_table(&mut out, &["id".to_owned()], &[]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "id\n--\n(0 rows)\n");
+    }
+
+    #[test]
+    fn json_values() {
+        assert_eq!(json(Value::Null), Json::Null);
+        assert_eq!(json(Value::BigInt(-7)), Json::from(-7));
+        assert_eq!(json(Value::Real(0.5)), Json::from(0.5));
+        assert_eq!(json(Value::Float(f64::NAN)), Json::Null);
+        assert_eq!(json(Value::Bool(true)), Json::Bool(true));
+        assert_eq!(
+            json(Value::Binary(b"hi".to_vec())),
+            Json::String("aGk=".to_owned())
+        );
+        assert_eq!(
+            json(Value::Json(serde_json::json!({ "a": [1] }))),
+            serde_json::json!({ "a": [1] })
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use super::output;
+use clap::{App, Arg, ArgMatches};
+use nibard::dsl::ast::Stmt;
+use nibard::prelude::*;
+use nibard::query::Query;
+use nibard::Database;
+use std::path::Path;
+
+pub fn make() -> App<'static> {
+    App::new("query")
+        .about("Runs SQL, or a statement file in JSON or YAML, and prints the rows")
+        .arg(
+            Arg::new("sql")
+                .takes_value(true)
+                .required_unless_present("file")
+                .help("SQL to run"),
+        )
+        .arg(
+            Arg::new("file")
+                .long("file")
+                .takes_value(true)
+                .conflicts_with("sql")
+                .help("A .sql file, or a .json or .yaml statement file"),
+        )
+        .arg(output::format_arg())
+}
+
+pub async fn run(db: &Database, args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let format = args.value_of("format").unwrap_or("table");
+
+    let path = match args.value_of("file") {
+        Some(path) => Path::new(path),
+        None => {
+            let sql = args.value_of("sql").unwrap_or_default().to_owned();
+            return output::print(Query::new(sql, Vec::new()).fetch(db), format).await;
+        }
+    };
+
+    let content =
+        std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let stmt: Stmt = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str(&content)?,
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&content)?,
+        _ => return output::print(Query::new(content, Vec::new()).fetch(db), format).await,
+    };
+    output::print(db.query(stmt), format).await
+}
@@ -0,0 +1,79 @@
+use super::output;
+use clap::{App, Arg, ArgMatches};
+use nibard::dsl::create::Schema;
+use nibard::prelude::*;
+use nibard::Database;
+
+pub fn make() -> App<'static> {
+    App::new("schema")
+        .about("Prints the tables and indexes of the database as DDL")
+        .arg(output::dialect_arg())
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Prints a schema file instead, as read by Schema::load"),
+        )
+}
+
+pub async fn run(db: &Database, args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = db.introspect().await?;
+
+    if args.is_present("json") {
+        println!("{}", json(&schema)?);
+        return Ok(());
+    }
+
+    let dialect = output::dialect(args.value_of("dialect"), db.dialect());
+    for sql in schema.statements(dialect)? {
+        println!("{};", sql);
+    }
+    Ok(())
+}
+
+/// The schema file printed by `--json`.
+fn json(schema: &Schema<'_>) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(schema)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nibard::{Type, Value};
+
+    #[tokio::test]
+    async fn json_round_trip() {
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        db.execute(
+            "CREATE TABLE settings(id INTEGER PRIMARY KEY, name TEXT NOT NULL DEFAULT 'x', \
+             enabled BOOL DEFAULT 1, ratio REAL DEFAULT 0.5, weight DOUBLE DEFAULT 1.25, \
+             retries INT DEFAULT 3)",
+        )
+        .await
+        .unwrap();
+        let schema = db.introspect().await.unwrap();
+        let defaults = schema.tables[0]
+            .fields
+            .iter()
+            .map(|column| column.default.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            defaults,
+            [
+                None,
+                Some(Value::Text("x".into())),
+                Some(Value::Bool(true)),
+                Some(Value::Real(0.5)),
+                Some(Value::Float(1.25)),
+                Some(Value::Int(3)),
+            ]
+        );
+        assert_eq!(schema.tables[0].fields[3].ty, Type::Real);
+
+        let path =
+            std::env::temp_dir().join(format!("nibard-cli-{}-schema.json", std::process::id()));
+        std::fs::write(&path, json(&schema).unwrap()).unwrap();
+        let loaded = Schema::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), schema);
+    }
+}
@@ -0,0 +1,237 @@
+use super::{
+    error::*,
+    executor::{Execute, Executor, QueryResult},
+    row::DatabaseRow,
+    transaction::{DatabaseTransaction, TransactionKind},
+};
+use futures::{
+    future::{BoxFuture, FutureExt, TryFutureExt},
+    stream::{BoxStream, StreamExt, TryStreamExt},
+};
+use nibard_dsl::ast::SoftDelete;
+use nibard_shared::{Dialect, Value};
+use sqlx::Acquire;
+use std::sync::Arc;
+
+/// A connection checked out of the pool, returned to it when dropped.
+///
+/// Statements run through it share the connection state, so settings like
+/// Sqlite's `PRAGMA foreign_keys`, which has no effect inside a transaction,
+/// can be changed before `begin`.
+pub struct DatabaseConnection {
+    kind: ConnectionKind,
+    soft_delete: Arc<SoftDelete>,
+}
+
+pub(crate) enum ConnectionKind {
+    #[cfg(feature = "postgres")]
+    Pg(sqlx::pool::PoolConnection<sqlx::Postgres>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(sqlx::pool::PoolConnection<sqlx::Sqlite>),
+    #[cfg(feature = "mysql")]
+    MySQL(sqlx::pool::PoolConnection<sqlx::MySql>),
+}
+
+impl DatabaseConnection {
+    pub(crate) fn new(kind: ConnectionKind, soft_delete: Arc<SoftDelete>) -> Self {
+        DatabaseConnection { kind, soft_delete }
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        match &self.kind {
+            #[cfg(feature = "postgres")]
+            ConnectionKind::Pg(_) => Dialect::Pg,
+            #[cfg(feature = "sqlite")]
+            ConnectionKind::Sqlite(_) => Dialect::Sqlite,
+            #[cfg(feature = "mysql")]
+            ConnectionKind::MySQL(_) => Dialect::MySQL,
+        }
+    }
+
+    pub fn soft_delete(&self) -> &SoftDelete {
+        &self.soft_delete
+    }
+
+    /// Begins a transaction on this connection.
+    pub async fn begin(&mut self) -> Result<DatabaseTransaction<'_>, Error> {
+        let kind = match &mut self.kind {
+            #[cfg(feature = "postgres")]
+            ConnectionKind::Pg(pg) => pg.begin().await.map(TransactionKind::Pg)?,
+            #[cfg(feature = "sqlite")]
+            ConnectionKind::Sqlite(sqlite) => sqlite.begin().await.map(TransactionKind::Sqlite)?,
+            #[cfg(feature = "mysql")]
+            ConnectionKind::MySQL(mysql) => mysql.begin().await.map(TransactionKind::MySQL)?,
+        };
+        Ok(DatabaseTransaction::new(kind, self.soft_delete.clone()))
+    }
+}
+
+impl<'c> Executor<'c> for &'c mut DatabaseConnection {
+    fn dialect(&self) -> Dialect {
+        DatabaseConnection::dialect(self)
+    }
+
+    fn soft_delete(&self) -> &SoftDelete {
+        &self.soft_delete
+    }
+
+    fn fetch_one<'e, 'q, E>(self, execute: E) -> BoxFuture<'e, Result<DatabaseRow, Error>>
+    where
+        'q: 'e,
+        'c: 'e,
+        E: 'q + Execute<'q>,
+    {
+        let fut = async move {
+            let row = match &mut self.kind {
+                #[cfg(feature = "postgres")]
+                ConnectionKind::Pg(pg) => {
+                    let q = query_and_bind!(execute);
+                    q.fetch_one(&mut **pg).await.map(DatabaseRow::Pg)?
+                }
+                #[cfg(feature = "sqlite")]
+                ConnectionKind::Sqlite(sqlite) => {
+                    let q = query_and_bind!(execute);
+                    q.fetch_one(&mut **sqlite).await.map(DatabaseRow::Sqlite)?
+                }
+                #[cfg(feature = "mysql")]
+                ConnectionKind::MySQL(mysql) => {
+                    let q = query_and_bind!(execute);
+                    q.fetch_one(&mut **mysql).await.map(DatabaseRow::MySQL)?
+                }
+            };
+
+            Ok(row)
+        };
+
+        Box::pin(fut)
+    }
+
+    fn fetch<'e, 'q, E>(self, execute: E) -> BoxStream<'e, Result<DatabaseRow, Error>>
+    where
+        'q: 'e,
+        'c: 'e,
+        E: 'q + Execute<'q>,
+    {
+        match &mut self.kind {
+            #[cfg(feature = "postgres")]
+            ConnectionKind::Pg(pg) => {
+                let q = query_and_bind!(execute);
+                q.fetch(&mut **pg)
+                    .map_ok(DatabaseRow::Pg)
+                    .err_into()
+                    .boxed()
+            }
+            #[cfg(feature = "sqlite")]
+            ConnectionKind::Sqlite(sqlite) => {
+                let q = query_and_bind!(execute);
+                q.fetch(&mut **sqlite)
+                    .map_ok(DatabaseRow::Sqlite)
+                    .err_into()
+                    .boxed()
+            }
+            #[cfg(feature = "mysql")]
+            ConnectionKind::MySQL(mysql) => {
+                let q = query_and_bind!(execute);
+                q.fetch(&mut **mysql)
+                    .map_ok(DatabaseRow::MySQL)
+                    .err_into()
+                    .boxed()
+            }
+        }
+    }
+
+    fn execute<'e, 'q, E>(self, execute: E) -> BoxFuture<'e, Result<QueryResult, Error>>
+    where
+        'q: 'e,
+        'c: 'e,
+        E: 'q + Execute<'q>,
+    {
+        match &mut self.kind {
+            #[cfg(feature = "postgres")]
+            ConnectionKind::Pg(pg) => {
+                let q = query_and_bind!(execute);
+                q.execute(&mut **pg)
+                    .err_into()
+                    .map_ok(|ret| QueryResult {
+                        rows_affected: ret.rows_affected(),
+                        last_insert_id: None,
+                    })
+                    .boxed()
+            }
+            #[cfg(feature = "sqlite")]
+            ConnectionKind::Sqlite(sqlite) => {
+                let q = query_and_bind!(execute);
+                q.execute(&mut **sqlite)
+                    .map_ok(|ret| QueryResult {
+                        rows_affected: ret.rows_affected(),
+                        last_insert_id: Some(ret.last_insert_rowid()),
+                    })
+                    .err_into()
+                    .boxed()
+            }
+            #[cfg(feature = "mysql")]
+            ConnectionKind::MySQL(mysql) => {
+                let q = query_and_bind!(execute);
+                q.execute(&mut **mysql)
+                    .err_into()
+                    .map_ok(|ret| QueryResult {
+                        rows_affected: ret.rows_affected(),
+                        last_insert_id: Some(ret.last_insert_id() as i64),
+                    })
+                    .boxed()
+            }
+        }
+    }
+
+    fn execute_many<'e, 'q, E>(
+        self,
+        execute: E,
+    ) -> BoxFuture<'e, BoxStream<'e, Result<QueryResult, Error>>>
+    where
+        'q: 'e,
+        'c: 'e,
+        E: 'q + Execute<'q>,
+    {
+        async move {
+            match &mut self.kind {
+                #[cfg(feature = "postgres")]
+                ConnectionKind::Pg(pg) => {
+                    let q = query_and_bind!(execute);
+                    q.execute_many(&mut **pg)
+                        .await
+                        .map_ok(|ret| QueryResult {
+                            rows_affected: ret.rows_affected(),
+                            last_insert_id: None,
+                        })
+                        .err_into()
+                        .boxed()
+                }
+                #[cfg(feature = "sqlite")]
+                ConnectionKind::Sqlite(sqlite) => {
+                    let q = query_and_bind!(execute);
+                    q.execute_many(&mut **sqlite)
+                        .await
+                        .map_ok(|ret| QueryResult {
+                            rows_affected: ret.rows_affected(),
+                            last_insert_id: Some(ret.last_insert_rowid()),
+                        })
+                        .err_into()
+                        .boxed()
+                }
+                #[cfg(feature = "mysql")]
+                ConnectionKind::MySQL(mysql) => {
+                    let q = query_and_bind!(execute);
+                    q.execute_many(&mut **mysql)
+                        .await
+                        .map_ok(|ret| QueryResult {
+                            rows_affected: ret.rows_affected(),
+                            last_insert_id: Some(ret.last_insert_id() as i64),
+                        })
+                        .err_into()
+                        .boxed()
+                }
+            }
+        }
+        .boxed()
+    }
+}
@@ -1,9 +1,12 @@
+use super::connection::{ConnectionKind, DatabaseConnection};
 pub use super::{error::*, executor::*, row::*, transaction::*};
 use futures::{
     future::BoxFuture, stream::BoxStream, FutureExt, StreamExt, TryFutureExt, TryStreamExt,
 };
+use nibard_dsl::ast::SoftDelete;
 use nibard_shared::{Dialect, Value};
 use std::str::FromStr;
+use std::sync::Arc;
 
 pub enum ConnectOptions {
     #[cfg(feature = "postgres")]
@@ -46,7 +49,10 @@ impl ConnectOptions {
 
     pub async fn open(self) -> Result<Database, Error> {
         let kind = self.build().await?;
-        Ok(Database { kind })
+        Ok(Database {
+            kind,
+            soft_delete: Arc::default(),
+        })
     }
 }
 
@@ -102,18 +108,29 @@ pub enum DatabaseKind {
 }
 
 impl DatabaseKind {
-    pub async fn begin<'c>(&'c self) -> Result<DatabaseTransaction<'c>, Error> {
+    pub(crate) async fn begin<'c>(&'c self) -> Result<TransactionKind<'c>, Error> {
         match self {
             #[cfg(feature = "postgres")]
-            DatabaseKind::Pg(pg) => Ok(pg.begin().await.map(DatabaseTransaction::Pg)?),
+            DatabaseKind::Pg(pg) => Ok(pg.begin().await.map(TransactionKind::Pg)?),
             #[cfg(feature = "sqlite")]
             DatabaseKind::Sqlite(sqlite) => {
-                Ok(sqlite.begin().await.map(DatabaseTransaction::Sqlite)?)
+                Ok(sqlite.begin().await.map(TransactionKind::Sqlite)?)
             }
             #[cfg(feature = "mysql")]
-            DatabaseKind::MySQL(mysql) => {
-                Ok(mysql.begin().await.map(DatabaseTransaction::MySQL)?)
+            DatabaseKind::MySQL(mysql) => Ok(mysql.begin().await.map(TransactionKind::MySQL)?),
+        }
+    }
+
+    pub(crate) async fn acquire(&self) -> Result<ConnectionKind, Error> {
+        match self {
+            #[cfg(feature = "postgres")]
+            DatabaseKind::Pg(pg) => Ok(pg.acquire().await.map(ConnectionKind::Pg)?),
+            #[cfg(feature = "sqlite")]
+            DatabaseKind::Sqlite(sqlite) => {
+                Ok(sqlite.acquire().await.map(ConnectionKind::Sqlite)?)
             }
+            #[cfg(feature = "mysql")]
+            DatabaseKind::MySQL(mysql) => Ok(mysql.acquire().await.map(ConnectionKind::MySQL)?),
         }
     }
 
@@ -247,7 +264,7 @@ impl<'c> Executor<'c> for &'c DatabaseKind {
                     .err_into()
                     .map_ok(|ret| QueryResult {
                         rows_affected: ret.rows_affected(),
-                        last_insert_id: None,
+                        last_insert_id: Some(ret.last_insert_id() as i64),
                     })
                     .boxed()
             }
@@ -295,7 +312,7 @@ impl<'c> Executor<'c> for &'c DatabaseKind {
                         .await
                         .map_ok(|ret| QueryResult {
                             rows_affected: ret.rows_affected(),
-                            last_insert_id: None,
+                            last_insert_id: Some(ret.last_insert_id() as i64),
                         })
                         .err_into()
                         .boxed()
@@ -309,6 +326,7 @@ impl<'c> Executor<'c> for &'c DatabaseKind {
 #[derive(Clone)]
 pub struct Database {
     pub(crate) kind: DatabaseKind,
+    soft_delete: Arc<SoftDelete>,
 }
 
 impl Database {
@@ -317,15 +335,37 @@ impl Database {
 
         let kind = cfg.build().await?;
 
-        Ok(Database { kind })
+        Ok(Database {
+            kind,
+            soft_delete: Arc::default(),
+        })
     }
 
     pub fn dialect(&self) -> Dialect {
         self.kind.dialect()
     }
 
+    /// Rewrites statements run through this database and its transactions
+    /// with `policy`, see `SoftDelete`. Clones of the database share it.
+    pub fn with_soft_delete(mut self, policy: SoftDelete) -> Database {
+        self.soft_delete = Arc::new(policy);
+        self
+    }
+
+    pub fn soft_delete(&self) -> &SoftDelete {
+        &self.soft_delete
+    }
+
     pub async fn begin<'c>(&'c self) -> Result<DatabaseTransaction<'c>, Error> {
-        self.kind.begin().await
+        let kind = self.kind.begin().await?;
+        Ok(DatabaseTransaction::new(kind, self.soft_delete.clone()))
+    }
+
+    /// Checks a connection out of the pool, for statements that have to
+    /// run on the same connection.
+    pub async fn acquire(&self) -> Result<DatabaseConnection, Error> {
+        let kind = self.kind.acquire().await?;
+        Ok(DatabaseConnection::new(kind, self.soft_delete.clone()))
     }
 }
 
@@ -333,6 +373,10 @@ impl<'c> Executor<'c> for &'c Database {
     fn dialect(&self) -> Dialect {
         self.kind.dialect()
     }
+
+    fn soft_delete(&self) -> &SoftDelete {
+        &self.soft_delete
+    }
     fn fetch_one<'e, 'q, E>(
         self,
         execute: E,
@@ -5,4 +5,8 @@ use thiserror::Error as ThisError;
 pub enum Error {
     #[error("sqlx error")]
     Sqlx(#[from] SqlxError),
+    #[error("could not build statement: {0}")]
+    Build(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("could not decode result: {0}")]
+    Decode(String),
 }
@@ -1,8 +1,11 @@
 use super::error::*;
 use super::row::DatabaseRow;
 use futures::{future::BoxFuture, stream::BoxStream};
+use nibard_dsl::ast::SoftDelete;
 use nibard_shared::{Dialect, Value};
 
+static NO_SOFT_DELETE: SoftDelete = SoftDelete::new();
+
 pub struct QueryResult {
     pub rows_affected: u64,
     pub last_insert_id: Option<i64>,
@@ -11,6 +14,12 @@ pub struct QueryResult {
 pub trait Executor<'c> {
     fn dialect(&self) -> Dialect;
 
+    /// The soft-delete policy statements run through this executor are
+    /// rewritten with, see `Database::with_soft_delete`.
+    fn soft_delete(&self) -> &SoftDelete {
+        &NO_SOFT_DELETE
+    }
+
     fn fetch_one<'e, 'q, E>(self, execute: E) -> BoxFuture<'e, Result<DatabaseRow, Error>>
     where
         'q: 'e,
@@ -18,14 +18,16 @@ pub mod de;
 #[macro_use]
 mod macros;
 
+mod connection;
 mod database;
 mod error;
 mod executor;
+mod null;
 // mod query;
 mod row;
 mod transaction;
 
-pub use self::{database::*, executor::*, row::*, transaction::*};
+pub use self::{connection::DatabaseConnection, database::*, executor::*, row::*, transaction::*};
 
 // #[cfg(test)]
 // mod test {
@@ -5,14 +5,17 @@ macro_rules! bind_value {
             Value::Int(i) => $query.bind(i),
             Value::SmallInt(i) => $query.bind(i),
             Value::BigInt(i) => $query.bind(i),
+            Value::Float(f) => $query.bind(f),
+            Value::Real(f) => $query.bind(f),
             Value::Bool(b) => $query.bind(b),
             #[cfg(feature = "time")]
             Value::Date(date) => $query.bind(date),
             #[cfg(feature = "time")]
             Value::DateTime(date) => $query.bind(date),
             Value::Binary(blob) => $query.bind(blob),
-            // worm_types::Value::Null => $query.bind(None),
-            _ => panic!("value not implemented {:?}", $val),
+            #[cfg(feature = "json")]
+            Value::Json(json) => $query.bind(sqlx::types::Json(json)),
+            Value::Null => $query.bind(None::<$crate::null::Null>),
         }
     };
 }
@@ -0,0 +1,40 @@
+use sqlx::encode::{Encode, IsNull};
+use sqlx::{Database, Type};
+
+/// Bound as `None::<Null>` for `Value::Null`. Postgres gets no declared type
+/// for the parameter and infers it from the statement, so a NULL can go
+/// into any column; the other databases do not type parameters.
+pub(crate) struct Null;
+
+impl<'q, DB: Database> Encode<'q, DB> for Null
+where
+    Null: Type<DB>,
+{
+    fn encode_by_ref(
+        &self,
+        _: &mut <DB as sqlx::database::HasArguments<'q>>::ArgumentBuffer,
+    ) -> IsNull {
+        IsNull::Yes
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl Type<sqlx::Postgres> for Null {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_oid(0)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Type<sqlx::Sqlite> for Null {
+    fn type_info() -> sqlx::sqlite::SqliteTypeInfo {
+        <String as Type<sqlx::Sqlite>>::type_info()
+    }
+}
+
+#[cfg(feature = "mysql")]
+impl Type<sqlx::MySql> for Null {
+    fn type_info() -> sqlx::mysql::MySqlTypeInfo {
+        <String as Type<sqlx::MySql>>::type_info()
+    }
+}
@@ -2,6 +2,8 @@ use super::Error;
 #[cfg(feature = "time")]
 use chrono::NaiveDateTime;
 use nibard_shared::{Type, Value};
+#[cfg(feature = "json")]
+use serde_json::Value as JsonValue;
 use sqlx::{Column as _, Row as SqlxRow, TypeInfo, ValueRef as SqlxValueRef};
 
 pub enum DatabaseRow {
@@ -61,6 +63,10 @@ impl Row for DatabaseRow {
     }
 }
 
+fn unsupported(column: &str, ty: &str) -> Error {
+    Error::Decode(format!("{}: unsupported column type {}", column, ty))
+}
+
 impl DatabaseRow {
     #[cfg(feature = "serialize")]
     pub fn try_into<'de, S: serde::de::Deserialize<'de>>(self) -> Result<S, nibard_shared::Error> {
@@ -79,11 +85,31 @@ impl Row for sqlx::postgres::PgRow {
         }
 
         let v = match type_info.name() {
+            "BOOL" => {
+                let v: bool = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Bool(v)
+            }
+            "INT2" => {
+                let v: i16 = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::SmallInt(v)
+            }
             "INT4" => {
                 let v: i32 = <Self as sqlx::Row>::try_get(self, name)?;
                 Value::Int(v)
             }
-            "TEXT" => {
+            "INT8" => {
+                let v: i64 = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::BigInt(v)
+            }
+            "FLOAT4" => {
+                let v: f32 = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Real(v)
+            }
+            "FLOAT8" => {
+                let v: f64 = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Float(v)
+            }
+            "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" => {
                 let v: String = <Self as sqlx::Row>::try_get(self, name)?;
                 Value::Text(v)
             }
@@ -91,9 +117,27 @@ impl Row for sqlx::postgres::PgRow {
                 let v: Vec<u8> = <Self as sqlx::Row>::try_get(self, name)?;
                 Value::Binary(v)
             }
-            _ => {
-                unimplemented!("type not implemented {}", type_info.name());
+            #[cfg(feature = "time")]
+            "DATE" => {
+                let v: chrono::NaiveDate = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Date(v)
+            }
+            #[cfg(feature = "time")]
+            "TIMESTAMP" => {
+                let v: NaiveDateTime = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::DateTime(v)
+            }
+            #[cfg(feature = "time")]
+            "TIMESTAMPTZ" => {
+                let v: chrono::DateTime<chrono::Utc> = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::DateTime(v.naive_utc())
+            }
+            #[cfg(feature = "json")]
+            "JSON" | "JSONB" => {
+                let v: JsonValue = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Json(v)
             }
+            other => return Err(unsupported(name, other)),
         };
 
         Ok(v)
@@ -117,6 +161,13 @@ impl Row for sqlx::sqlite::SqliteRow {
             return Ok(Value::Null);
         }
 
+        // Sqlite stores json as text, so it is only decoded when asked for.
+        #[cfg(feature = "json")]
+        if Some(Type::JSON) == ty {
+            let v: sqlx::types::Json<JsonValue> = <Self as sqlx::Row>::try_get(self, name)?;
+            return Ok(Value::Json(v.0));
+        }
+
         let v = match type_info.name() {
             #[cfg(feature = "time")]
             "TEXT" => {
@@ -143,11 +194,12 @@ impl Row for sqlx::sqlite::SqliteRow {
                     let v: bool = <Self as sqlx::Row>::try_get(self, name)?;
                     Value::Bool(v)
                 } else {
-                    let v: i32 = <Self as sqlx::Row>::try_get(self, name)?;
-                    Value::Int(v)
+                    // Sqlite integers are 64 bit, whatever the declared type
+                    let v: i64 = <Self as sqlx::Row>::try_get(self, name)?;
+                    Value::BigInt(v)
                 }
             }
-            "FLOAT" => {
+            "REAL" | "FLOAT" => {
                 let v: f64 = <Self as sqlx::Row>::try_get(self, name)?;
                 Value::Float(v)
             }
@@ -155,9 +207,7 @@ impl Row for sqlx::sqlite::SqliteRow {
                 let v: Vec<u8> = <Self as sqlx::Row>::try_get(self, name)?;
                 Value::Binary(v)
             }
-            _ => {
-                unimplemented!("type not implemented: {}", type_info.name());
-            }
+            other => return Err(unsupported(name, other)),
         };
 
         Ok(v)
@@ -172,8 +222,66 @@ impl Row for sqlx::sqlite::SqliteRow {
 
 #[cfg(feature = "mysql")]
 impl Row for sqlx::mysql::MySqlRow {
-    fn try_get(&self, name: &str) -> Result<Value, Error> {
-        unimplemented!("")
+    fn try_get(&self, name: &str, ty: Option<Type>) -> Result<Value, Error> {
+        let value_ref = self.try_get_raw(name)?;
+        let type_info = value_ref.type_info();
+
+        if value_ref.is_null() {
+            return Ok(Value::Null);
+        }
+
+        let v = match type_info.name() {
+            "BOOLEAN" => {
+                let v: bool = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Bool(v)
+            }
+            "TINYINT" | "SMALLINT" => {
+                let v: i16 = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::SmallInt(v)
+            }
+            "INT" | "MEDIUMINT" => {
+                let v: i32 = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Int(v)
+            }
+            "BIGINT" => {
+                let v: i64 = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::BigInt(v)
+            }
+            "FLOAT" => {
+                let v: f32 = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Real(v)
+            }
+            "DOUBLE" => {
+                let v: f64 = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Float(v)
+            }
+            "CHAR" | "VARCHAR" | "TINYTEXT" | "TEXT" | "MEDIUMTEXT" | "LONGTEXT" => {
+                let v: String = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Text(v)
+            }
+            "BINARY" | "VARBINARY" | "TINYBLOB" | "BLOB" | "MEDIUMBLOB" | "LONGBLOB" => {
+                let v: Vec<u8> = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Binary(v)
+            }
+            #[cfg(feature = "time")]
+            "DATE" => {
+                let v: chrono::NaiveDate = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Date(v)
+            }
+            #[cfg(feature = "time")]
+            "DATETIME" | "TIMESTAMP" => {
+                let v: NaiveDateTime = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::DateTime(v)
+            }
+            #[cfg(feature = "json")]
+            "JSON" => {
+                let v: JsonValue = <Self as sqlx::Row>::try_get(self, name)?;
+                Value::Json(v)
+            }
+            other => return Err(unsupported(name, other)),
+        };
+
+        Ok(v)
     }
 
     fn columns<'a>(&'a self) -> Vec<Column<'a>> {
@@ -183,3 +291,79 @@ impl Row for sqlx::mysql::MySqlRow {
             .collect::<Vec<_>>()
     }
 }
+
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use super::*;
+    use crate::{Database, Executor};
+
+    #[tokio::test]
+    async fn sqlite_values() {
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        let row = db
+            .fetch_one("SELECT 1 AS id, 0.5 AS score, 'a' AS label, NULL AS note")
+            .await
+            .unwrap();
+        assert_eq!(row.try_get("id", None).unwrap(), Value::BigInt(1));
+        assert_eq!(row.try_get("score", None).unwrap(), Value::Float(0.5));
+        assert_eq!(row.try_get("label", None).unwrap(), Value::Text("a".into()));
+        assert_eq!(row.try_get("note", None).unwrap(), Value::Null);
+        assert!(row.try_get("missing", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn sqlite_big_integers() {
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        let row = db
+            .fetch_one("SELECT 9007199254740993 AS id, 1 AS flag")
+            .await
+            .unwrap();
+        assert_eq!(
+            row.try_get("id", None).unwrap(),
+            Value::BigInt(9_007_199_254_740_993)
+        );
+        assert_eq!(
+            row.try_get("flag", Some(Type::Bool)).unwrap(),
+            Value::Bool(true)
+        );
+    }
+
+    #[test]
+    fn unsupported_type() {
+        assert_eq!(
+            unsupported("price", "DECIMAL").to_string(),
+            "could not decode result: price: unsupported column type DECIMAL"
+        );
+    }
+}
+
+#[cfg(all(test, feature = "postgres", feature = "time"))]
+mod pg_test {
+    use super::*;
+    use crate::{Database, Executor};
+    use chrono::NaiveDate;
+
+    #[tokio::test]
+    #[ignore = "needs a Postgres server at NIBARD_TEST_POSTGRES"]
+    async fn pg_dates() {
+        let url = std::env::var("NIBARD_TEST_POSTGRES").unwrap();
+        let db = Database::open(&url).await.unwrap();
+        let row = db
+            .fetch_one(
+                "SELECT DATE '2024-02-29' AS day, TIMESTAMP '2024-02-29 12:30:00' AS at, \
+                 TIMESTAMPTZ '2024-02-29 12:30:00+02' AS at_tz",
+            )
+            .await
+            .unwrap();
+        let day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(row.try_get("day", None).unwrap(), Value::Date(day));
+        assert_eq!(
+            row.try_get("at", None).unwrap(),
+            Value::DateTime(day.and_hms_opt(12, 30, 0).unwrap())
+        );
+        assert_eq!(
+            row.try_get("at_tz", None).unwrap(),
+            Value::DateTime(day.and_hms_opt(10, 30, 0).unwrap())
+        );
+    }
+}
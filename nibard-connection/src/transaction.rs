@@ -7,9 +7,16 @@ use futures::{
     future::{BoxFuture, FutureExt, TryFutureExt},
     stream::{BoxStream, StreamExt, TryStreamExt},
 };
+use nibard_dsl::ast::SoftDelete;
 use nibard_shared::{Dialect, Value};
+use std::sync::Arc;
 
-pub enum DatabaseTransaction<'c> {
+pub struct DatabaseTransaction<'c> {
+    kind: TransactionKind<'c>,
+    soft_delete: Arc<SoftDelete>,
+}
+
+pub(crate) enum TransactionKind<'c> {
     #[cfg(feature = "postgres")]
     Pg(sqlx::Transaction<'c, sqlx::Postgres>),
     #[cfg(feature = "sqlite")]
@@ -25,29 +32,38 @@ pub enum DatabaseTransaction<'c> {
 }
 
 impl<'c> DatabaseTransaction<'c> {
+    pub(crate) fn new(kind: TransactionKind<'c>, soft_delete: Arc<SoftDelete>) -> Self {
+        DatabaseTransaction { kind, soft_delete }
+    }
+
     pub fn dialect(&self) -> Dialect {
-        match self {
+        match &self.kind {
             #[cfg(feature = "postgres")]
-            DatabaseTransaction::Pg(_) => Dialect::Pg,
+            TransactionKind::Pg(_) => Dialect::Pg,
             #[cfg(feature = "sqlite")]
-            DatabaseTransaction::Sqlite(_) => Dialect::Sqlite,
+            TransactionKind::Sqlite(_) => Dialect::Sqlite,
             #[cfg(feature = "mysql")]
-            DatabaseTransaction::MySQL(_) => Dialect::MySQL,
+            TransactionKind::MySQL(_) => Dialect::MySQL,
         }
     }
 
+    /// The soft-delete policy of the database the transaction was begun on.
+    pub fn soft_delete(&self) -> &SoftDelete {
+        &self.soft_delete
+    }
+
     pub async fn commit(self) -> Result<(), Error> {
-        match self {
+        match self.kind {
             #[cfg(feature = "postgres")]
-            DatabaseTransaction::Pg(pg) => {
+            TransactionKind::Pg(pg) => {
                 pg.commit().await?;
             }
             #[cfg(feature = "sqlite")]
-            DatabaseTransaction::Sqlite(sqlite) => {
+            TransactionKind::Sqlite(sqlite) => {
                 sqlite.commit().await?;
             }
             #[cfg(feature = "mysql")]
-            DatabaseTransaction::MySQL(mysql) => {
+            TransactionKind::MySQL(mysql) => {
                 mysql.commit().await?;
             }
         }
@@ -60,6 +76,10 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
     fn dialect(&self) -> Dialect {
         (&**self).dialect()
     }
+
+    fn soft_delete(&self) -> &SoftDelete {
+        &self.soft_delete
+    }
     fn fetch_one<'e, 'q, E>(
         self,
         execute: E,
@@ -70,19 +90,19 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
         E: 'q + Execute<'q>,
     {
         let fut = async move {
-            let row = match self {
+            let row = match &mut self.kind {
                 #[cfg(feature = "postgres")]
-                DatabaseTransaction::Pg(pg) => {
+                TransactionKind::Pg(pg) => {
                     let q = query_and_bind!(execute);
                     q.fetch_one(pg).await.map(DatabaseRow::Pg)?
                 }
                 #[cfg(feature = "sqlite")]
-                DatabaseTransaction::Sqlite(sqlite) => {
+                TransactionKind::Sqlite(sqlite) => {
                     let q = query_and_bind!(execute);
                     q.fetch_one(sqlite).await.map(DatabaseRow::Sqlite)?
                 }
                 #[cfg(feature = "mysql")]
-                DatabaseTransaction::MySQL(mysql) => {
+                TransactionKind::MySQL(mysql) => {
                     let q = query_and_bind!(execute);
                     q.fetch_one(mysql).await.map(DatabaseRow::MySQL)?
                 }
@@ -100,9 +120,9 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
         'c: 'e,
         E: 'q + Execute<'q>,
     {
-        let row = match self {
+        let row = match &mut self.kind {
             #[cfg(feature = "postgres")]
-            DatabaseTransaction::Pg(pg) => {
+            TransactionKind::Pg(pg) => {
                 let q = query_and_bind!(execute);
                 q.fetch(pg)
                     .map_ok(|pg| DatabaseRow::Pg(pg))
@@ -110,7 +130,7 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
                     .boxed()
             }
             #[cfg(feature = "sqlite")]
-            DatabaseTransaction::Sqlite(sqlite) => {
+            TransactionKind::Sqlite(sqlite) => {
                 let q = query_and_bind!(execute);
                 q.fetch(sqlite)
                     .map_ok(|sqlite| DatabaseRow::Sqlite(sqlite))
@@ -118,7 +138,7 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
                     .boxed()
             }
             #[cfg(feature = "mysql")]
-            DatabaseTransaction::MySQL(mysql) => {
+            TransactionKind::MySQL(mysql) => {
                 let q = query_and_bind!(execute);
                 q.fetch(mysql)
                     .map_ok(|mysql| DatabaseRow::MySQL(mysql))
@@ -135,9 +155,9 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
         'c: 'e,
         E: 'q + Execute<'q>,
     {
-        match self {
+        match &mut self.kind {
             #[cfg(feature = "postgres")]
-            DatabaseTransaction::Pg(pg) => {
+            TransactionKind::Pg(pg) => {
                 let q = query_and_bind!(execute);
                 q.execute(pg)
                     .err_into()
@@ -148,7 +168,7 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
                     .boxed()
             }
             #[cfg(feature = "sqlite")]
-            DatabaseTransaction::Sqlite(sqlite) => {
+            TransactionKind::Sqlite(sqlite) => {
                 let q = query_and_bind!(execute);
                 q.execute(sqlite)
                     .map_ok(|ret| QueryResult {
@@ -159,7 +179,7 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
                     .boxed()
             }
             #[cfg(feature = "mysql")]
-            DatabaseTransaction::MySQL(mysql) => {
+            TransactionKind::MySQL(mysql) => {
                 let q = query_and_bind!(execute);
                 q.execute(mysql)
                     .err_into()
@@ -182,9 +202,9 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
         E: 'q + Execute<'q>,
     {
         async move {
-            match self {
+            match &mut self.kind {
                 #[cfg(feature = "postgres")]
-                DatabaseTransaction::Pg(pg) => {
+                TransactionKind::Pg(pg) => {
                     let q = query_and_bind!(execute);
                     q.execute_many(pg)
                         .await
@@ -196,7 +216,7 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
                         .boxed()
                 }
                 #[cfg(feature = "sqlite")]
-                DatabaseTransaction::Sqlite(sqlite) => {
+                TransactionKind::Sqlite(sqlite) => {
                     let q = query_and_bind!(execute);
                     q.execute_many(sqlite)
                         .await
@@ -208,7 +228,7 @@ impl<'c, 't> Executor<'c> for &'c mut DatabaseTransaction<'t> {
                         .boxed()
                 }
                 #[cfg(feature = "mysql")]
-                DatabaseTransaction::MySQL(mysql) => {
+                TransactionKind::MySQL(mysql) => {
                     let q = query_and_bind!(execute);
                     q.execute_many(mysql)
                         .await
@@ -0,0 +1,23 @@
+mod table;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Derives a typed table definition for a struct.
+///
+/// Generates a `{Struct}Table` marker implementing `Table` and `Target`, a
+/// `TypedColumn` constant per struct field (`Todo::ID`, `Todo::LABEL`), a
+/// `table()` function returning the marker and a `create_table()` function
+/// returning the matching `CreateTable`. Primary key and `auto` columns are
+/// always `NOT NULL`.
+///
+/// Container attributes: `#[nibard(table = "todos")]`.
+/// Field attributes: `primary_key`, `auto`, `skip`, `rename = "..."` and
+/// `ty = "VarChar(255)"` to override the inferred `Type`.
+#[proc_macro_derive(Table, attributes(nibard))]
+pub fn derive_table(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    table::expand(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
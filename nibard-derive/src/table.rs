@@ -0,0 +1,247 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Expr, Fields, GenericArgument, LitStr, PathArguments, Result, Type,
+};
+
+struct TableAttrs {
+    name: Option<String>,
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    ty: Option<Expr>,
+    primary_key: bool,
+    auto: bool,
+    skip: bool,
+}
+
+fn table_attrs(input: &DeriveInput) -> Result<TableAttrs> {
+    let mut attrs = TableAttrs { name: None };
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("nibard")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                let lit: LitStr = meta.value()?.parse()?;
+                attrs.name = Some(lit.value());
+                Ok(())
+            } else {
+                Err(meta.error("unknown nibard attribute"))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+fn field_attrs(field: &syn::Field) -> Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("nibard")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let lit: LitStr = meta.value()?.parse()?;
+                attrs.rename = Some(lit.value());
+            } else if meta.path.is_ident("ty") {
+                let lit: LitStr = meta.value()?.parse()?;
+                attrs.ty = Some(lit.parse()?);
+            } else if meta.path.is_ident("primary_key") {
+                attrs.primary_key = true;
+            } else if meta.path.is_ident("auto") {
+                attrs.auto = true;
+            } else if meta.path.is_ident("skip") {
+                attrs.skip = true;
+            } else {
+                return Err(meta.error("unknown nibard attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attrs)
+}
+
+fn snake_case(ident: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Strips `Option<T>`, returning the inner type and whether it was optional.
+fn unwrap_option(ty: &Type) -> (&Type, bool) {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if segment.ident == "Option" {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                        return (inner, true);
+                    }
+                }
+            }
+        }
+    }
+    (ty, false)
+}
+
+/// Maps a Rust type to the name of its `nibard_shared::Type` variant.
+fn sql_type(ty: &Type) -> Option<TokenStream> {
+    let segment = match ty {
+        Type::Reference(reference) => return sql_type(&reference.elem),
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+
+    let ty = match segment.ident.to_string().as_str() {
+        "i8" | "u8" | "i16" => quote!(SmallInt),
+        "u16" | "i32" => quote!(Int),
+        "u32" | "i64" => quote!(BigInt),
+        "f32" => quote!(Real),
+        "f64" => quote!(Float(53)),
+        "bool" => quote!(Bool),
+        "String" | "str" => quote!(Text),
+        "NaiveDate" => quote!(Date),
+        "NaiveDateTime" | "DateTime" => quote!(DateTime),
+        "Vec" => match &segment.arguments {
+            PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(GenericArgument::Type(Type::Path(inner))) if inner.path.is_ident("u8") => {
+                    quote!(Binary)
+                }
+                _ => return None,
+            },
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(ty)
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "Table can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Table can only be derived for structs",
+            ))
+        }
+    };
+
+    let attrs = table_attrs(&input)?;
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let marker = format_ident!("{}Table", ident);
+    let table = attrs.name.unwrap_or_else(|| snake_case(&ident.to_string()));
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut consts = Vec::new();
+    let mut columns = Vec::new();
+
+    for field in fields {
+        let attrs = field_attrs(field)?;
+        if attrs.skip {
+            continue;
+        }
+
+        let field_ident = field.ident.as_ref().expect("named field");
+        let field_name = field_ident.to_string();
+        let field_name = field_name.trim_start_matches("r#");
+        let name = attrs.rename.unwrap_or_else(|| field_name.to_owned());
+        let const_ident = syn::Ident::new(&field_name.to_uppercase(), Span::call_site());
+
+        let (inner, nullable) = unwrap_option(&field.ty);
+        let ty = if attrs.auto {
+            quote!(Auto)
+        } else if let Some(ty) = attrs.ty {
+            quote!(#ty)
+        } else {
+            sql_type(inner).ok_or_else(|| {
+                Error::new_spanned(
+                    &field.ty,
+                    "cannot infer the column type, use #[nibard(ty = \"...\")]",
+                )
+            })?
+        };
+
+        // Primary keys are never null, leaving them nullable would write
+        // `DEFAULT NULL` after the key
+        let not_null = if nullable && !attrs.primary_key && !attrs.auto {
+            quote!()
+        } else {
+            quote!(.not_null())
+        };
+        let primary_key = if attrs.primary_key {
+            quote!(.primary_key())
+        } else {
+            quote!()
+        };
+
+        let field_ty = &field.ty;
+        consts.push(quote! {
+            #vis const #const_ident: ::nibard::dsl::query::TypedColumn<
+                #field_ty,
+                ::nibard::dsl::query::Field<#marker>,
+            > = ::nibard::dsl::query::TypedColumn::new(
+                ::nibard::dsl::query::Field::new(#marker, #name),
+            );
+        });
+
+        columns.push(quote! {
+            .column(
+                ::nibard::dsl::create::Column::new(#name, ::nibard::Type::#ty)
+                    #not_null
+                    #primary_key
+            )
+        });
+    }
+
+    Ok(quote! {
+        #[derive(Clone, Copy, Debug, Default, PartialEq)]
+        #vis struct #marker;
+
+        impl #marker {
+            #vis const NAME: &'static str = #table;
+        }
+
+        impl<C: ::nibard::dsl::Context> ::nibard::dsl::Table<C> for #marker {
+            fn build(&self, ctx: &mut C) -> ::std::result::Result<(), ::nibard::dsl::Error> {
+                ctx.write_str(#table)?;
+                Ok(())
+            }
+        }
+
+        impl<C: ::nibard::dsl::Context> ::nibard::dsl::query::Target<C> for #marker {
+            fn build(&self, ctx: &mut C) -> ::std::result::Result<(), ::nibard::dsl::Error> {
+                ctx.write_str(#table)?;
+                Ok(())
+            }
+        }
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #vis fn table() -> #marker {
+                #marker
+            }
+
+            #(#consts)*
+
+            #vis fn create_table() -> ::nibard::dsl::create::CreateTable<'static> {
+                ::nibard::dsl::create::CreateTable::new(#table)
+                    #(#columns)*
+            }
+        }
+    })
+}
@@ -0,0 +1,58 @@
+use nibard::dsl::prelude::*;
+use nibard::{Dialect, Table};
+
+#[derive(Table)]
+#[nibard(table = "todos")]
+pub struct Todo {
+    #[nibard(primary_key, auto)]
+    pub id: i32,
+    #[nibard(ty = "VarChar(100)")]
+    pub label: String,
+    pub done: bool,
+    pub note: Option<String>,
+    #[nibard(rename = "pos")]
+    pub position: Option<i32>,
+    #[nibard(skip)]
+    pub cached: Vec<String>,
+}
+
+#[derive(Table)]
+pub struct UserGroup {
+    #[nibard(primary_key)]
+    pub code: Option<String>,
+    pub table: String,
+}
+
+#[test]
+fn create_table() {
+    assert_eq!(
+        Todo::create_table().to_sql_literal(Dialect::Pg).unwrap(),
+        "CREATE TABLE IF NOT EXISTS todos(id SERIAL PRIMARY KEY NOT NULL, label VARCHAR(100) NOT NULL, \
+         done BOOL NOT NULL, note TEXT DEFAULT NULL, pos INTEGER DEFAULT NULL)"
+    );
+    assert_eq!(
+        Todo::create_table()
+            .to_sql_literal(Dialect::Sqlite)
+            .unwrap(),
+        "CREATE TABLE IF NOT EXISTS todos(id INTEGER PRIMARY KEY AUTOINCREMENT, \
+         label VARCHAR(100) NOT NULL, done BOOL NOT NULL, note TEXT DEFAULT NULL, \
+         pos INTEGER DEFAULT NULL)"
+    );
+}
+
+#[test]
+fn columns() {
+    assert_eq!(TodoTable::NAME, "todos");
+    assert_eq!(UserGroupTable::NAME, "user_group");
+    assert_eq!(Todo::table(), TodoTable);
+    assert_eq!(Todo::POSITION.column().name(), "pos");
+    assert_eq!(UserGroup::TABLE.column().name(), "table");
+
+    let select = Todo::table()
+        .select(Todo::LABEL)
+        .filter(Todo::DONE.eql(false).and(Todo::POSITION.gte(2)));
+    assert_eq!(
+        select.to_sql_literal(Dialect::Sqlite).unwrap(),
+        "SELECT todos.label FROM todos WHERE todos.done = 0 AND todos.pos >= 2"
+    );
+}
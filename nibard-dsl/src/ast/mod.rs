@@ -0,0 +1,920 @@
+mod soft_delete;
+mod visit;
+
+pub use self::{soft_delete::*, visit::*};
+
+use crate::explain::Explain;
+use crate::query::{BinaryOperator, Expression, IntoValue, JoinType, Order, Select, Sql};
+use crate::{Context, Error, Statement};
+use nibard_shared::{Dialect, Value};
+
+/// An owned statement tree. Unlike the typed builders it can be inspected
+/// and rewritten after construction, see `Visitor` and `VisitorMut`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stmt {
+    Select(SelectStmt),
+    Insert(InsertStmt),
+    Update(UpdateStmt),
+    Delete(DeleteStmt),
+    /// `EXPLAIN` of the inner statement, see `nibard_dsl::explain::Explain`.
+    Explain {
+        statement: Box<Stmt>,
+        analyze: bool,
+    },
+}
+
+impl Stmt {
+    /// Names of every table the statement reads or writes, including those
+    /// in joins and subqueries.
+    pub fn tables(&self) -> Vec<String> {
+        let mut tables = Tables::default();
+        tables.visit_stmt(self);
+        tables.into_inner()
+    }
+
+    pub fn rewrite<V: VisitorMut>(mut self, visitor: &mut V) -> Stmt {
+        visitor.visit_stmt(&mut self);
+        self
+    }
+}
+
+impl From<SelectStmt> for Stmt {
+    fn from(select: SelectStmt) -> Stmt {
+        Stmt::Select(select)
+    }
+}
+
+impl From<InsertStmt> for Stmt {
+    fn from(insert: InsertStmt) -> Stmt {
+        Stmt::Insert(insert)
+    }
+}
+
+impl From<UpdateStmt> for Stmt {
+    fn from(update: UpdateStmt) -> Stmt {
+        Stmt::Update(update)
+    }
+}
+
+impl From<DeleteStmt> for Stmt {
+    fn from(delete: DeleteStmt) -> Stmt {
+        Stmt::Delete(delete)
+    }
+}
+
+impl<C: Context> Statement<C> for Stmt {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        match self {
+            Stmt::Select(select) => Statement::build(select, ctx),
+            Stmt::Insert(insert) => insert.build(ctx),
+            Stmt::Update(update) => update.build(ctx),
+            Stmt::Delete(delete) => delete.build(ctx),
+            Stmt::Explain { statement, analyze } => {
+                let explain = Explain::new(&**statement);
+                let explain = if *analyze { explain.analyze() } else { explain };
+                explain.build(ctx)
+            }
+        }
+    }
+
+    fn to_ast(&self) -> Option<Stmt> {
+        Some(self.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableRef {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl TableRef {
+    pub fn new(name: impl Into<String>) -> TableRef {
+        TableRef {
+            name: name.into(),
+            alias: None,
+        }
+    }
+
+    pub fn alias(mut self, alias: impl Into<String>) -> TableRef {
+        self.alias = Some(alias.into());
+        self
+    }
+
+    /// The name columns of this table are qualified with.
+    pub fn qualifier(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
+    fn build<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
+        ctx.write_str(&self.name)?;
+        if let Some(alias) = &self.alias {
+            write!(ctx, " AS {}", alias)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> From<&'a str> for TableRef {
+    fn from(name: &'a str) -> TableRef {
+        TableRef::new(name)
+    }
+}
+
+impl From<String> for TableRef {
+    fn from(name: String) -> TableRef {
+        TableRef::new(name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Source {
+    Table(TableRef),
+    Subquery {
+        select: Box<SelectStmt>,
+        alias: String,
+    },
+}
+
+impl Source {
+    fn build<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
+        match self {
+            Source::Table(table) => table.build(ctx),
+            Source::Subquery { select, alias } => {
+                ctx.write_char('(')?;
+                Select::build(&**select, ctx)?;
+                write!(ctx, ") AS {}", alias)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<TableRef> for Source {
+    fn from(table: TableRef) -> Source {
+        Source::Table(table)
+    }
+}
+
+impl<'a> From<&'a str> for Source {
+    fn from(name: &'a str) -> Source {
+        Source::Table(TableRef::new(name))
+    }
+}
+
+impl From<String> for Source {
+    fn from(name: String) -> Source {
+        Source::Table(TableRef::new(name))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JoinClause {
+    pub kind: JoinType,
+    pub source: Source,
+    pub on: Option<Expr>,
+}
+
+impl JoinClause {
+    pub fn new(kind: JoinType, source: impl Into<Source>) -> JoinClause {
+        JoinClause {
+            kind,
+            source: source.into(),
+            on: None,
+        }
+    }
+
+    pub fn inner(source: impl Into<Source>) -> JoinClause {
+        JoinClause::new(JoinType::Inner, source)
+    }
+
+    pub fn left(source: impl Into<Source>) -> JoinClause {
+        JoinClause::new(JoinType::Left, source)
+    }
+
+    pub fn on(mut self, expr: Expr) -> JoinClause {
+        self.on = Some(expr);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelectStmt {
+    /// Selected expressions; an empty selection is written as `*`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub selection: Vec<Expr>,
+    pub from: Source,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub joins: Vec<JoinClause>,
+    pub filter: Option<Expr>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub order: Vec<(Expr, Order)>,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub deleted: Deleted,
+}
+
+impl SelectStmt {
+    pub fn new(from: impl Into<Source>) -> SelectStmt {
+        SelectStmt {
+            selection: Vec::default(),
+            from: from.into(),
+            joins: Vec::default(),
+            filter: None,
+            order: Vec::default(),
+            limit: None,
+            offset: None,
+            deleted: Deleted::default(),
+        }
+    }
+
+    pub fn column(mut self, expr: impl Into<Expr>) -> Self {
+        self.selection.push(expr.into());
+        self
+    }
+
+    pub fn join(mut self, join: JoinClause) -> Self {
+        self.joins.push(join);
+        self
+    }
+
+    /// Adds `expr` to the filter, joined with `AND` to any existing one.
+    pub fn filter(mut self, expr: Expr) -> Self {
+        self.filter = Some(Expr::and_opt(self.filter.take(), expr));
+        self
+    }
+
+    pub fn order_by(mut self, expr: impl Into<Expr>, order: Order) -> Self {
+        self.order.push((expr.into(), order));
+        self
+    }
+
+    pub fn limit(mut self, limit: impl Into<Option<u64>>) -> Self {
+        self.limit = limit.into();
+        self
+    }
+
+    pub fn offset(mut self, offset: impl Into<Option<u64>>) -> Self {
+        self.offset = offset.into();
+        self
+    }
+
+    /// Includes soft-deleted rows, see `SoftDelete`.
+    pub fn with_deleted(mut self) -> Self {
+        self.deleted = Deleted::Include;
+        self
+    }
+
+    /// Only selects soft-deleted rows, see `SoftDelete`.
+    pub fn only_deleted(mut self) -> Self {
+        self.deleted = Deleted::Only;
+        self
+    }
+}
+
+impl<C: Context> Select<C> for SelectStmt {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        ctx.write_str("SELECT ")?;
+        if self.selection.is_empty() {
+            ctx.write_char('*')?;
+        }
+        write_list(&self.selection, ctx)?;
+        ctx.write_str(" FROM ")?;
+        self.from.build(ctx)?;
+
+        for join in &self.joins {
+            ctx.write_char(' ')?;
+            join.kind.build(ctx)?;
+            ctx.write_char(' ')?;
+            join.source.build(ctx)?;
+            if let Some(on) = &join.on {
+                ctx.write_str(" ON ")?;
+                on.build(ctx)?;
+            }
+        }
+
+        if let Some(filter) = &self.filter {
+            ctx.write_str(" WHERE ")?;
+            filter.build(ctx)?;
+        }
+
+        for (idx, (expr, order)) in self.order.iter().enumerate() {
+            ctx.write_str(if idx == 0 { " ORDER BY " } else { ", " })?;
+            expr.build(ctx)?;
+            match order {
+                Order::Asc => ctx.write_str(" ASC")?,
+                Order::Desc => ctx.write_str(" DESC")?,
+            }
+        }
+
+        if let Some(limit) = self.limit {
+            write!(ctx, " LIMIT {}", limit)?;
+        }
+
+        if let Some(offset) = self.offset {
+            write!(ctx, " OFFSET {}", offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: Context> Statement<C> for SelectStmt {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        Select::build(self, ctx)
+    }
+
+    fn to_ast(&self) -> Option<Stmt> {
+        Some(self.clone().into())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InsertSource {
+    /// One list of expressions per row.
+    Values(Vec<Vec<Expr>>),
+    Select(Box<SelectStmt>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InsertStmt {
+    pub table: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub columns: Vec<String>,
+    pub source: InsertSource,
+}
+
+impl InsertStmt {
+    pub fn new(table: impl Into<String>) -> InsertStmt {
+        InsertStmt {
+            table: table.into(),
+            columns: Vec::default(),
+            source: InsertSource::Values(Vec::default()),
+        }
+    }
+
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        self.columns = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn values<I, E>(mut self, row: I) -> Self
+    where
+        E: Into<Expr>,
+        I: IntoIterator<Item = E>,
+    {
+        let row = row.into_iter().map(Into::into).collect();
+        match &mut self.source {
+            InsertSource::Values(rows) => rows.push(row),
+            InsertSource::Select(_) => self.source = InsertSource::Values(vec![row]),
+        }
+        self
+    }
+
+    pub fn select(mut self, select: SelectStmt) -> Self {
+        self.source = InsertSource::Select(Box::new(select));
+        self
+    }
+}
+
+impl<C: Context> Statement<C> for InsertStmt {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        if self.columns.is_empty() || self.source == InsertSource::Values(Vec::default()) {
+            return Err(Error::InvalidStatement {
+                dialect: *ctx.dialect(),
+                description: format!("insert into {} has no values", self.table),
+            });
+        }
+
+        write!(
+            ctx,
+            "INSERT INTO {} ({}) ",
+            self.table,
+            self.columns.join(", ")
+        )?;
+        match &self.source {
+            InsertSource::Values(rows) => {
+                ctx.write_str("VALUES ")?;
+                for (idx, row) in rows.iter().enumerate() {
+                    if idx > 0 {
+                        ctx.write_str(", ")?;
+                    }
+                    ctx.write_char('(')?;
+                    write_list(row, ctx)?;
+                    ctx.write_char(')')?;
+                }
+            }
+            InsertSource::Select(select) => Select::build(&**select, ctx)?,
+        }
+        Ok(())
+    }
+
+    fn to_ast(&self) -> Option<Stmt> {
+        Some(self.clone().into())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UpdateStmt {
+    pub table: TableRef,
+    pub set: Vec<(String, Expr)>,
+    pub filter: Option<Expr>,
+}
+
+impl UpdateStmt {
+    pub fn new(table: impl Into<TableRef>) -> UpdateStmt {
+        UpdateStmt {
+            table: table.into(),
+            set: Vec::default(),
+            filter: None,
+        }
+    }
+
+    pub fn set(mut self, column: impl Into<String>, expr: impl Into<Expr>) -> Self {
+        self.set.push((column.into(), expr.into()));
+        self
+    }
+
+    /// Adds `expr` to the filter, joined with `AND` to any existing one.
+    pub fn filter(mut self, expr: Expr) -> Self {
+        self.filter = Some(Expr::and_opt(self.filter.take(), expr));
+        self
+    }
+}
+
+impl<C: Context> Statement<C> for UpdateStmt {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        if self.set.is_empty() {
+            return Err(Error::InvalidStatement {
+                dialect: *ctx.dialect(),
+                description: format!("update of {} sets no columns", self.table.name),
+            });
+        }
+
+        ctx.write_str("UPDATE ")?;
+        self.table.build(ctx)?;
+        ctx.write_str(" SET ")?;
+        for (idx, (column, expr)) in self.set.iter().enumerate() {
+            if idx > 0 {
+                ctx.write_str(", ")?;
+            }
+            write!(ctx, "{} = ", column)?;
+            expr.build(ctx)?;
+        }
+        if let Some(filter) = &self.filter {
+            ctx.write_str(" WHERE ")?;
+            filter.build(ctx)?;
+        }
+        Ok(())
+    }
+
+    fn to_ast(&self) -> Option<Stmt> {
+        Some(self.clone().into())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeleteStmt {
+    pub table: TableRef,
+    pub filter: Option<Expr>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub deleted: Deleted,
+}
+
+impl DeleteStmt {
+    pub fn new(table: impl Into<TableRef>) -> DeleteStmt {
+        DeleteStmt {
+            table: table.into(),
+            filter: None,
+            deleted: Deleted::default(),
+        }
+    }
+
+    /// Deletes rows for real instead of marking them deleted, see
+    /// `SoftDelete`.
+    pub fn with_deleted(mut self) -> Self {
+        self.deleted = Deleted::Include;
+        self
+    }
+
+    /// Purges rows which are already soft-deleted, see `SoftDelete`.
+    pub fn only_deleted(mut self) -> Self {
+        self.deleted = Deleted::Only;
+        self
+    }
+
+    /// Adds `expr` to the filter, joined with `AND` to any existing one.
+    pub fn filter(mut self, expr: Expr) -> Self {
+        self.filter = Some(Expr::and_opt(self.filter.take(), expr));
+        self
+    }
+}
+
+impl<C: Context> Statement<C> for DeleteStmt {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        ctx.write_str("DELETE FROM ")?;
+        self.table.build(ctx)?;
+        if let Some(filter) = &self.filter {
+            ctx.write_str(" WHERE ")?;
+            filter.build(ctx)?;
+        }
+        Ok(())
+    }
+
+    fn to_ast(&self) -> Option<Stmt> {
+        Some(self.clone().into())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Expr {
+    Column {
+        table: Option<String>,
+        name: String,
+    },
+    Value(Value),
+    Param(String),
+    Binary {
+        operator: BinaryOperator,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Not(Box<Expr>),
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+    /// A parenthesized list, for the right-hand side of `IN`.
+    List(Vec<Expr>),
+    Subquery(Box<SelectStmt>),
+    Exists {
+        select: Box<SelectStmt>,
+        negated: bool,
+    },
+    Func {
+        name: String,
+        args: Vec<Expr>,
+    },
+    Alias {
+        expr: Box<Expr>,
+        alias: String,
+    },
+    /// A raw fragment. Visitors cannot see the tables it refers to.
+    Raw(Sql<'static>),
+}
+
+impl Expr {
+    /// A column, qualified when `name` has the form `table.column`.
+    pub fn col(name: &str) -> Expr {
+        match name.split_once('.') {
+            Some((table, name)) => Expr::Column {
+                table: Some(table.to_owned()),
+                name: name.to_owned(),
+            },
+            None => Expr::Column {
+                table: None,
+                name: name.to_owned(),
+            },
+        }
+    }
+
+    pub fn value(value: impl Into<Value>) -> Expr {
+        Expr::Value(value.into())
+    }
+
+    pub fn param(name: impl Into<String>) -> Expr {
+        Expr::Param(name.into())
+    }
+
+    pub fn binary(self, operator: BinaryOperator, right: impl Into<Expr>) -> Expr {
+        Expr::Binary {
+            operator,
+            left: Box::new(self),
+            right: Box::new(right.into()),
+        }
+    }
+
+    pub fn eql(self, right: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOperator::Eq, right)
+    }
+
+    pub fn neq(self, right: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOperator::NotEq, right)
+    }
+
+    pub fn has(self, right: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOperator::In, right)
+    }
+
+    pub fn and(self, right: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOperator::And, right)
+    }
+
+    pub fn or(self, right: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOperator::Or, right)
+    }
+
+    pub fn is_null(self) -> Expr {
+        Expr::IsNull {
+            expr: Box::new(self),
+            negated: false,
+        }
+    }
+
+    pub fn is_not_null(self) -> Expr {
+        Expr::IsNull {
+            expr: Box::new(self),
+            negated: true,
+        }
+    }
+
+    pub fn alias(self, alias: impl Into<String>) -> Expr {
+        Expr::Alias {
+            expr: Box::new(self),
+            alias: alias.into(),
+        }
+    }
+
+    pub fn exists(select: SelectStmt) -> Expr {
+        Expr::Exists {
+            select: Box::new(select),
+            negated: false,
+        }
+    }
+
+    pub fn not_exists(select: SelectStmt) -> Expr {
+        Expr::Exists {
+            select: Box::new(select),
+            negated: true,
+        }
+    }
+
+    pub(crate) fn and_opt(left: Option<Expr>, right: Expr) -> Expr {
+        match left {
+            Some(left) => left.and(right),
+            None => right,
+        }
+    }
+
+    /// Binding strength, used to parenthesize operands so that rewriting a
+    /// filter never changes how an existing `OR` groups.
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary {
+                operator: BinaryOperator::Or,
+                ..
+            } => 1,
+            Expr::Binary {
+                operator: BinaryOperator::And,
+                ..
+            }
+            | Expr::Not(_) => 2,
+            Expr::Binary { .. } => 3,
+            _ => 4,
+        }
+    }
+
+    fn build_operand<C: Context>(&self, ctx: &mut C, precedence: u8) -> Result<(), Error> {
+        if self.precedence() < precedence {
+            ctx.write_char('(')?;
+            self.build(ctx)?;
+            ctx.write_char(')')?;
+            Ok(())
+        } else {
+            self.build(ctx)
+        }
+    }
+}
+
+impl std::ops::Not for Expr {
+    type Output = Expr;
+    fn not(self) -> Expr {
+        Expr::Not(Box::new(self))
+    }
+}
+
+impl From<Value> for Expr {
+    fn from(value: Value) -> Expr {
+        Expr::Value(value)
+    }
+}
+
+impl From<SelectStmt> for Expr {
+    fn from(select: SelectStmt) -> Expr {
+        Expr::Subquery(Box::new(select))
+    }
+}
+
+impl<'a> From<&'a str> for Expr {
+    fn from(name: &'a str) -> Expr {
+        Expr::col(name)
+    }
+}
+
+impl<C: Context> Expression<C> for Expr {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        match self {
+            Expr::Column { table, name } => {
+                if let Some(table) = table {
+                    write!(ctx, "{}.", table)?;
+                }
+                ctx.write_str(name)?;
+            }
+            Expr::Value(value) => {
+                ctx.push(value.clone())?;
+            }
+            Expr::Param(name) => {
+                ctx.push_param(name)?;
+            }
+            Expr::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let precedence = self.precedence();
+                left.build_operand(ctx, precedence)?;
+                ctx.write_char(' ')?;
+                operator.build(ctx)?;
+                ctx.write_char(' ')?;
+                right.build_operand(ctx, precedence)?;
+            }
+            Expr::Not(expr) => {
+                ctx.write_str("NOT ")?;
+                expr.build_operand(ctx, 3)?;
+            }
+            Expr::IsNull { expr, negated } => {
+                expr.build_operand(ctx, 4)?;
+                ctx.write_str(if *negated { " IS NOT NULL" } else { " IS NULL" })?;
+            }
+            Expr::List(items) => {
+                let dialect = *ctx.dialect();
+                if items.is_empty() && dialect != Dialect::Sqlite {
+                    return Err(Error::EmptyList {
+                        dialect,
+                        description: "IN () needs at least one value".to_owned(),
+                    });
+                }
+                ctx.write_char('(')?;
+                write_list(items, ctx)?;
+                ctx.write_char(')')?;
+            }
+            Expr::Subquery(select) => {
+                ctx.write_char('(')?;
+                Select::build(&**select, ctx)?;
+                ctx.write_char(')')?;
+            }
+            Expr::Exists { select, negated } => {
+                if *negated {
+                    ctx.write_str("NOT ")?;
+                }
+                ctx.write_str("EXISTS (")?;
+                Select::build(&**select, ctx)?;
+                ctx.write_char(')')?;
+            }
+            Expr::Func { name, args } => {
+                write!(ctx, "{}(", name)?;
+                write_list(args, ctx)?;
+                ctx.write_char(')')?;
+            }
+            Expr::Alias { expr, alias } => {
+                expr.build(ctx)?;
+                write!(ctx, " AS {}", alias)?;
+            }
+            Expr::Raw(sql) => sql.build(ctx)?,
+        }
+        Ok(())
+    }
+}
+
+impl<C: Context> IntoValue<C> for Expr {
+    type Expression = Expr;
+    fn into_expression(self) -> Self::Expression {
+        self
+    }
+}
+
+fn write_list<C: Context>(exprs: &[Expr], ctx: &mut C) -> Result<(), Error> {
+    for (idx, expr) in exprs.iter().enumerate() {
+        if idx > 0 {
+            ctx.write_str(", ")?;
+        }
+        expr.build(ctx)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+
+    #[test]
+    fn tenant_scope() {
+        let posts = SelectStmt::new(TableRef::new("posts").alias("p"))
+            .column("p.id")
+            .filter(Expr::col("p.owner_id").eql(Expr::col("u.id")));
+        let select = SelectStmt::new(TableRef::new("users").alias("u"))
+            .column("u.name")
+            .column("companies.name")
+            .join(
+                JoinClause::left("companies")
+                    .on(Expr::col("companies.id").eql(Expr::col("u.company_id"))),
+            )
+            .filter(
+                Expr::col("u.active")
+                    .eql(Expr::value(true))
+                    .or(Expr::exists(posts)),
+            );
+
+        let mut scope = ScopeFilter::column_eq(["users", "posts", "companies"], "tenant_id", 7);
+        let stmt = Stmt::from(select).rewrite(&mut scope);
+        let (sql, values) = build(Dialect::Pg, &stmt).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT u.name, companies.name FROM users AS u LEFT JOIN companies \
+             ON companies.id = u.company_id AND companies.tenant_id = $1 \
+             WHERE u.tenant_id = $2 AND (u.active = $3 OR EXISTS \
+             (SELECT p.id FROM posts AS p WHERE p.tenant_id = $4 AND p.owner_id = u.id))"
+        );
+        assert_eq!(values.len(), 4);
+        assert_eq!(stmt.tables(), vec!["users", "companies", "posts"]);
+
+        let delete =
+            Stmt::from(DeleteStmt::new("users").filter(Expr::col("id").eql(Expr::value(1))))
+                .rewrite(&mut scope)
+                .rewrite(&mut RenameTable::new("users", "accounts"));
+        let (sql, _) = build(Dialect::Sqlite, &delete).unwrap();
+        assert_eq!(
+            sql,
+            "DELETE FROM accounts WHERE accounts.tenant_id = ? AND id = ?"
+        );
+    }
+
+    #[test]
+    fn scope_outer_joins() {
+        let select = SelectStmt::new(TableRef::new("users").alias("u"))
+            .column("u.name")
+            .column("c.name")
+            .join(
+                JoinClause::new(JoinType::Right, TableRef::new("companies").alias("c"))
+                    .on(Expr::col("c.id").eql(Expr::col("u.company_id"))),
+            )
+            .join(
+                JoinClause::new(JoinType::Full, "teams")
+                    .on(Expr::col("teams.id").eql(Expr::col("u.team_id"))),
+            )
+            .filter(Expr::col("u.active").eql(Expr::value(true)));
+
+        let mut scope = ScopeFilter::column_eq(["users", "companies"], "tenant_id", 7);
+        let stmt = Stmt::from(select).rewrite(&mut scope);
+        let (sql, values) = build(Dialect::Pg, &stmt).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT u.name, c.name FROM (SELECT * FROM users AS u WHERE u.tenant_id = $1) AS u \
+             RIGHT JOIN (SELECT * FROM companies AS c WHERE c.tenant_id = $2) AS c \
+             ON c.id = u.company_id FULL OUTER JOIN teams ON teams.id = u.team_id WHERE u.active = $3"
+        );
+        assert_eq!(
+            values,
+            vec![Value::Int(7), Value::Int(7), Value::Bool(true)]
+        );
+    }
+
+    #[cfg(feature = "schema-json")]
+    #[test]
+    fn from_json() {
+        let stmt: Stmt = serde_json::from_str(
+            r#"{ "Select": {
+                "selection": [{ "Column": { "table": null, "name": "name" } }],
+                "from": { "Table": { "name": "users", "alias": null } },
+                "filter": { "Binary": {
+                    "operator": "Eq",
+                    "left": { "Column": { "table": null, "name": "id" } },
+                    "right": { "Value": { "type": "Int", "value": 1 } }
+                } },
+                "limit": null,
+                "offset": null
+            } }"#,
+        )
+        .unwrap();
+
+        let (sql, values) = build(Dialect::Pg, &stmt).unwrap();
+        assert_eq!(sql, "SELECT name FROM users WHERE id = $1");
+        assert_eq!(values, vec![Value::Int(1)]);
+    }
+}
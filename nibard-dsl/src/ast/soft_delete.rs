@@ -0,0 +1,159 @@
+use super::visit::{and_front, scope_select, walk_delete_mut, walk_select_mut, walk_stmt_mut};
+use super::{DeleteStmt, Expr, SelectStmt, Stmt, TableRef, UpdateStmt, VisitorMut};
+use crate::query::Sql;
+use nibard_shared::Value;
+
+/// Which soft-deleted rows a statement sees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Deleted {
+    /// Rows marked deleted are hidden, and deletes only mark rows.
+    #[default]
+    Exclude,
+    /// Every row, and deletes remove rows for real.
+    Include,
+    /// Only rows marked deleted.
+    Only,
+}
+
+/// Rewrites statements on tables whose rows are marked deleted by setting a
+/// timestamp column instead of being removed. Selects get
+/// `column IS NULL` for every such table they read, joins and subqueries
+/// included, and a delete becomes `UPDATE ... SET column = CURRENT_TIMESTAMP`.
+/// `with_deleted` and `only_deleted` on the statement change this.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SoftDelete {
+    tables: Vec<(String, String)>,
+}
+
+impl SoftDelete {
+    pub const fn new() -> SoftDelete {
+        SoftDelete { tables: Vec::new() }
+    }
+
+    pub fn table(mut self, table: impl Into<String>, column: impl Into<String>) -> Self {
+        self.register(table, column);
+        self
+    }
+
+    pub fn register(&mut self, table: impl Into<String>, column: impl Into<String>) {
+        let table = table.into();
+        let column = column.into();
+        match self.tables.iter_mut().find(|(name, _)| *name == table) {
+            Some(entry) => entry.1 = column,
+            None => self.tables.push((table, column)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// The column marking rows of `table` deleted, if it has one.
+    pub fn column(&self, table: &str) -> Option<&str> {
+        self.tables
+            .iter()
+            .find(|(name, _)| name == table)
+            .map(|(_, column)| column.as_str())
+    }
+
+    fn predicate(&self, table: &TableRef, deleted: Deleted) -> Option<Expr> {
+        let column = Expr::Column {
+            table: Some(table.qualifier().to_owned()),
+            name: self.column(&table.name)?.to_owned(),
+        };
+        match deleted {
+            Deleted::Exclude => Some(column.is_null()),
+            Deleted::Include => None,
+            Deleted::Only => Some(column.is_not_null()),
+        }
+    }
+
+    fn mark_deleted(&self, delete: DeleteStmt) -> UpdateStmt {
+        let column = self.column(&delete.table.name).unwrap_or_default();
+        let now = Expr::Raw(Sql::new("CURRENT_TIMESTAMP", Vec::<Value>::new()));
+        let predicate = Expr::Column {
+            table: Some(delete.table.qualifier().to_owned()),
+            name: column.to_owned(),
+        }
+        .is_null();
+
+        UpdateStmt {
+            set: vec![(column.to_owned(), now)],
+            filter: Some(and_front(predicate, delete.filter)),
+            table: delete.table,
+        }
+    }
+}
+
+impl VisitorMut for SoftDelete {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        if let Stmt::Delete(delete) = stmt {
+            if delete.deleted == Deleted::Exclude && self.column(&delete.table.name).is_some() {
+                let delete = std::mem::replace(delete, DeleteStmt::new(""));
+                *stmt = Stmt::Update(self.mark_deleted(delete));
+            }
+        }
+        walk_stmt_mut(self, stmt)
+    }
+
+    fn visit_select(&mut self, select: &mut SelectStmt) {
+        walk_select_mut(self, select);
+        let deleted = select.deleted;
+        scope_select(select, |table| self.predicate(table, deleted));
+    }
+
+    fn visit_delete(&mut self, delete: &mut DeleteStmt) {
+        walk_delete_mut(self, delete);
+        if delete.deleted != Deleted::Only {
+            return;
+        }
+        if let Some(predicate) = self.predicate(&delete.table, delete.deleted) {
+            delete.filter = Some(and_front(predicate, delete.filter.take()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::JoinClause;
+    use crate::build;
+    use nibard_shared::Dialect;
+
+    #[test]
+    fn soft_delete() {
+        let mut policy = SoftDelete::new().table("posts", "deleted_at");
+
+        let select = SelectStmt::new("users")
+            .column("users.name")
+            .join(JoinClause::inner("posts").on(Expr::col("posts.owner_id").eql("users.id")));
+        let stmt = Stmt::from(select.clone()).rewrite(&mut policy);
+        let (sql, _) = build(Dialect::Sqlite, &stmt).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT users.name FROM users INNER JOIN posts \
+             ON posts.owner_id = users.id AND posts.deleted_at IS NULL"
+        );
+
+        let stmt = Stmt::from(select.with_deleted()).rewrite(&mut policy);
+        let (sql, _) = build(Dialect::Sqlite, &stmt).unwrap();
+        assert!(!sql.contains("deleted_at"));
+
+        let delete = DeleteStmt::new("posts").filter(Expr::col("id").eql(Expr::value(4)));
+        let stmt = Stmt::from(delete.clone()).rewrite(&mut policy);
+        let (sql, _) = build(Dialect::Pg, &stmt).unwrap();
+        assert_eq!(
+            sql,
+            "UPDATE posts SET deleted_at = CURRENT_TIMESTAMP \
+             WHERE posts.deleted_at IS NULL AND id = $1"
+        );
+
+        let stmt = Stmt::from(delete.only_deleted()).rewrite(&mut policy);
+        let (sql, _) = build(Dialect::Pg, &stmt).unwrap();
+        assert_eq!(
+            sql,
+            "DELETE FROM posts WHERE posts.deleted_at IS NOT NULL AND id = $1"
+        );
+    }
+}
@@ -0,0 +1,422 @@
+use super::{
+    DeleteStmt, Expr, InsertSource, InsertStmt, JoinClause, SelectStmt, Source, Stmt, TableRef,
+    UpdateStmt,
+};
+use crate::query::JoinType;
+use nibard_shared::Value;
+
+/// Walks a statement tree. Every method defaults to visiting the children,
+/// so an implementation only overrides the nodes it cares about and calls
+/// the matching `walk_*` function to keep descending.
+pub trait Visitor {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_select(&mut self, select: &SelectStmt) {
+        walk_select(self, select)
+    }
+
+    fn visit_insert(&mut self, insert: &InsertStmt) {
+        walk_insert(self, insert)
+    }
+
+    fn visit_update(&mut self, update: &UpdateStmt) {
+        walk_update(self, update)
+    }
+
+    fn visit_delete(&mut self, delete: &DeleteStmt) {
+        walk_delete(self, delete)
+    }
+
+    fn visit_table(&mut self, table: &TableRef) {
+        let _ = table;
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr)
+    }
+}
+
+pub fn walk_stmt<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+    match stmt {
+        Stmt::Select(select) => visitor.visit_select(select),
+        Stmt::Insert(insert) => visitor.visit_insert(insert),
+        Stmt::Update(update) => visitor.visit_update(update),
+        Stmt::Delete(delete) => visitor.visit_delete(delete),
+        Stmt::Explain { statement, .. } => visitor.visit_stmt(statement),
+    }
+}
+
+fn walk_source<V: Visitor + ?Sized>(visitor: &mut V, source: &Source) {
+    match source {
+        Source::Table(table) => visitor.visit_table(table),
+        Source::Subquery { select, .. } => visitor.visit_select(select),
+    }
+}
+
+pub fn walk_select<V: Visitor + ?Sized>(visitor: &mut V, select: &SelectStmt) {
+    walk_source(visitor, &select.from);
+    for join in &select.joins {
+        walk_source(visitor, &join.source);
+        if let Some(on) = &join.on {
+            visitor.visit_expr(on);
+        }
+    }
+    for expr in &select.selection {
+        visitor.visit_expr(expr);
+    }
+    if let Some(filter) = &select.filter {
+        visitor.visit_expr(filter);
+    }
+    for (expr, _) in &select.order {
+        visitor.visit_expr(expr);
+    }
+}
+
+pub fn walk_insert<V: Visitor + ?Sized>(visitor: &mut V, insert: &InsertStmt) {
+    visitor.visit_table(&TableRef::new(insert.table.as_str()));
+    match &insert.source {
+        InsertSource::Values(rows) => rows
+            .iter()
+            .flatten()
+            .for_each(|expr| visitor.visit_expr(expr)),
+        InsertSource::Select(select) => visitor.visit_select(select),
+    }
+}
+
+pub fn walk_update<V: Visitor + ?Sized>(visitor: &mut V, update: &UpdateStmt) {
+    visitor.visit_table(&update.table);
+    for (_, expr) in &update.set {
+        visitor.visit_expr(expr);
+    }
+    if let Some(filter) = &update.filter {
+        visitor.visit_expr(filter);
+    }
+}
+
+pub fn walk_delete<V: Visitor + ?Sized>(visitor: &mut V, delete: &DeleteStmt) {
+    visitor.visit_table(&delete.table);
+    if let Some(filter) = &delete.filter {
+        visitor.visit_expr(filter);
+    }
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Binary { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        Expr::Not(expr) | Expr::IsNull { expr, .. } | Expr::Alias { expr, .. } => {
+            visitor.visit_expr(expr)
+        }
+        Expr::List(exprs) | Expr::Func { args: exprs, .. } => {
+            exprs.iter().for_each(|expr| visitor.visit_expr(expr))
+        }
+        Expr::Subquery(select) | Expr::Exists { select, .. } => visitor.visit_select(select),
+        Expr::Column { .. } | Expr::Value(_) | Expr::Param(_) | Expr::Raw(_) => {}
+    }
+}
+
+/// The rewriting counterpart of `Visitor`.
+pub trait VisitorMut {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt)
+    }
+
+    fn visit_select(&mut self, select: &mut SelectStmt) {
+        walk_select_mut(self, select)
+    }
+
+    fn visit_insert(&mut self, insert: &mut InsertStmt) {
+        walk_insert_mut(self, insert)
+    }
+
+    fn visit_update(&mut self, update: &mut UpdateStmt) {
+        walk_update_mut(self, update)
+    }
+
+    fn visit_delete(&mut self, delete: &mut DeleteStmt) {
+        walk_delete_mut(self, delete)
+    }
+
+    fn visit_table(&mut self, table: &mut TableRef) {
+        let _ = table;
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr)
+    }
+}
+
+pub fn walk_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt) {
+    match stmt {
+        Stmt::Select(select) => visitor.visit_select(select),
+        Stmt::Insert(insert) => visitor.visit_insert(insert),
+        Stmt::Update(update) => visitor.visit_update(update),
+        Stmt::Delete(delete) => visitor.visit_delete(delete),
+        Stmt::Explain { statement, .. } => visitor.visit_stmt(statement),
+    }
+}
+
+fn walk_source_mut<V: VisitorMut + ?Sized>(visitor: &mut V, source: &mut Source) {
+    match source {
+        Source::Table(table) => visitor.visit_table(table),
+        Source::Subquery { select, .. } => visitor.visit_select(select),
+    }
+}
+
+pub fn walk_select_mut<V: VisitorMut + ?Sized>(visitor: &mut V, select: &mut SelectStmt) {
+    walk_source_mut(visitor, &mut select.from);
+    for join in &mut select.joins {
+        walk_source_mut(visitor, &mut join.source);
+        if let Some(on) = &mut join.on {
+            visitor.visit_expr(on);
+        }
+    }
+    for expr in &mut select.selection {
+        visitor.visit_expr(expr);
+    }
+    if let Some(filter) = &mut select.filter {
+        visitor.visit_expr(filter);
+    }
+    for (expr, _) in &mut select.order {
+        visitor.visit_expr(expr);
+    }
+}
+
+pub fn walk_insert_mut<V: VisitorMut + ?Sized>(visitor: &mut V, insert: &mut InsertStmt) {
+    let mut table = TableRef::new(std::mem::take(&mut insert.table));
+    visitor.visit_table(&mut table);
+    insert.table = table.name;
+    match &mut insert.source {
+        InsertSource::Values(rows) => rows
+            .iter_mut()
+            .flatten()
+            .for_each(|expr| visitor.visit_expr(expr)),
+        InsertSource::Select(select) => visitor.visit_select(select),
+    }
+}
+
+pub fn walk_update_mut<V: VisitorMut + ?Sized>(visitor: &mut V, update: &mut UpdateStmt) {
+    visitor.visit_table(&mut update.table);
+    for (_, expr) in &mut update.set {
+        visitor.visit_expr(expr);
+    }
+    if let Some(filter) = &mut update.filter {
+        visitor.visit_expr(filter);
+    }
+}
+
+pub fn walk_delete_mut<V: VisitorMut + ?Sized>(visitor: &mut V, delete: &mut DeleteStmt) {
+    visitor.visit_table(&mut delete.table);
+    if let Some(filter) = &mut delete.filter {
+        visitor.visit_expr(filter);
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Binary { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        Expr::Not(expr) | Expr::IsNull { expr, .. } | Expr::Alias { expr, .. } => {
+            visitor.visit_expr(expr)
+        }
+        Expr::List(exprs) | Expr::Func { args: exprs, .. } => {
+            exprs.iter_mut().for_each(|expr| visitor.visit_expr(expr))
+        }
+        Expr::Subquery(select) | Expr::Exists { select, .. } => visitor.visit_select(select),
+        Expr::Column { .. } | Expr::Value(_) | Expr::Param(_) | Expr::Raw(_) => {}
+    }
+}
+
+/// Collects the distinct table names of a statement in order of appearance.
+#[derive(Debug, Default)]
+pub struct Tables(Vec<String>);
+
+impl Tables {
+    pub fn into_inner(self) -> Vec<String> {
+        self.0
+    }
+}
+
+impl Visitor for Tables {
+    fn visit_table(&mut self, table: &TableRef) {
+        if !self.0.contains(&table.name) {
+            self.0.push(table.name.clone());
+        }
+    }
+}
+
+/// Renames a table everywhere, including columns qualified with its name.
+#[derive(Debug, Clone)]
+pub struct RenameTable {
+    from: String,
+    to: String,
+}
+
+impl RenameTable {
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> RenameTable {
+        RenameTable {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+}
+
+impl VisitorMut for RenameTable {
+    fn visit_table(&mut self, table: &mut TableRef) {
+        if table.name == self.from {
+            table.name = self.to.clone();
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Column {
+            table: Some(table), ..
+        } = expr
+        {
+            if *table == self.from {
+                *table = self.to.clone();
+            }
+        }
+        walk_expr_mut(self, expr)
+    }
+}
+
+/// Adds a predicate to every select, update and delete of the scoped tables,
+/// such as `tenant_id = ?` for multi-tenancy. The predicate is built for the
+/// qualifier (alias or name) of each occurrence, so aliased tables, joins,
+/// derived tables and subqueries are all covered. Tables referenced from
+/// `Expr::Raw` fragments cannot be seen and are left alone.
+pub struct ScopeFilter<'a> {
+    tables: Vec<String>,
+    predicate: Box<dyn Fn(&str) -> Expr + Send + Sync + 'a>,
+}
+
+impl<'a> ScopeFilter<'a> {
+    pub fn new<I, S, F>(tables: I, predicate: F) -> ScopeFilter<'a>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+        F: Fn(&str) -> Expr + Send + Sync + 'a,
+    {
+        ScopeFilter {
+            tables: tables.into_iter().map(Into::into).collect(),
+            predicate: Box::new(predicate),
+        }
+    }
+
+    /// Scopes `tables` to rows where `column` equals `value`.
+    pub fn column_eq<I, S>(tables: I, column: &'a str, value: impl Into<Value>) -> ScopeFilter<'a>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = S>,
+    {
+        let value = value.into();
+        ScopeFilter::new(tables, move |qualifier| {
+            Expr::Column {
+                table: Some(qualifier.to_owned()),
+                name: column.to_owned(),
+            }
+            .eql(value.clone())
+        })
+    }
+
+    pub fn applies_to(&self, table: &str) -> bool {
+        self.tables.iter().any(|scoped| scoped == table)
+    }
+
+    fn predicate_for(&self, table: &TableRef) -> Option<Expr> {
+        if self.applies_to(&table.name) {
+            Some((self.predicate)(table.qualifier()))
+        } else {
+            None
+        }
+    }
+}
+
+pub(crate) fn and_front(predicate: Expr, filter: Option<Expr>) -> Expr {
+    match filter {
+        Some(filter) => predicate.and(filter),
+        None => predicate,
+    }
+}
+
+/// Adds the predicate returned for each table a select reads from.
+///
+/// Predicates of the FROM table go into WHERE and those of inner and left
+/// joined tables into their ON. With a right or full join a table can be
+/// the optional side of one join and the preserved side of another, where
+/// neither keeps its unmatched rows, so every scoped table is replaced by a
+/// filtered subquery under the same name instead.
+pub(crate) fn scope_select<F>(select: &mut SelectStmt, predicate_for: F)
+where
+    F: Fn(&TableRef) -> Option<Expr>,
+{
+    let outer_join = select
+        .joins
+        .iter()
+        .any(|join| matches!(join.kind, JoinType::Right | JoinType::Full));
+    if outer_join {
+        for source in
+            std::iter::once(&mut select.from).chain(select.joins.iter_mut().map(|j| &mut j.source))
+        {
+            if let Source::Table(table) = source {
+                if let Some(predicate) = predicate_for(table) {
+                    let alias = table.qualifier().to_owned();
+                    let mut scoped = SelectStmt::new(table.clone());
+                    scoped.filter = Some(predicate);
+                    *source = Source::Subquery {
+                        select: Box::new(scoped),
+                        alias,
+                    };
+                }
+            }
+        }
+        return;
+    }
+
+    let predicate_for = |source: &Source| match source {
+        Source::Table(table) => predicate_for(table),
+        Source::Subquery { .. } => None,
+    };
+
+    if let Some(predicate) = predicate_for(&select.from) {
+        select.filter = Some(and_front(predicate, select.filter.take()));
+    }
+
+    // The joined side is optional, so the predicate belongs in ON to keep
+    // unmatched rows of the other side.
+    for JoinClause { source, on, .. } in &mut select.joins {
+        if let Some(predicate) = predicate_for(source) {
+            *on = Some(Expr::and_opt(on.take(), predicate));
+        }
+    }
+}
+
+impl<'a> VisitorMut for ScopeFilter<'a> {
+    fn visit_select(&mut self, select: &mut SelectStmt) {
+        walk_select_mut(self, select);
+        scope_select(select, |table| self.predicate_for(table));
+    }
+
+    fn visit_update(&mut self, update: &mut UpdateStmt) {
+        walk_update_mut(self, update);
+        if self.applies_to(&update.table.name) {
+            let predicate = (self.predicate)(update.table.qualifier());
+            update.filter = Some(and_front(predicate, update.filter.take()));
+        }
+    }
+
+    fn visit_delete(&mut self, delete: &mut DeleteStmt) {
+        walk_delete_mut(self, delete);
+        if self.applies_to(&delete.table.name) {
+            let predicate = (self.predicate)(delete.table.qualifier());
+            delete.filter = Some(and_front(predicate, delete.filter.take()));
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use crate::write_identifier;
+use crate::ast::SoftDelete;
+use crate::{write_identifier, Template};
 
 use super::error::Error;
 use nibard_shared::{Dialect, Value};
@@ -8,8 +9,23 @@ pub trait Context: fmt::Write {
     fn dialect(&self) -> &Dialect;
     fn push(&mut self, value: Value) -> Result<&mut Self, Error>;
 
+    /// Reserves a placeholder for a value which is bound by `name` later.
+    fn push_param(&mut self, name: &str) -> Result<&mut Self, Error> {
+        let _ = name;
+        Err(Error::Unsupported {
+            dialect: *self.dialect(),
+            feature: "named parameters in this context".to_owned(),
+        })
+    }
+
     fn build(self) -> Result<(String, Vec<Value>), Error>;
 
+    /// The soft-delete policy the typed builders apply while building, see
+    /// `DefaultContext::with_soft_delete`.
+    fn soft_delete(&self) -> Option<&SoftDelete> {
+        None
+    }
+
     fn push_identifier(&mut self, identifier: &str) -> Result<&mut Self, Error>
     where
         Self: Sized,
@@ -20,43 +36,136 @@ pub trait Context: fmt::Write {
     }
 }
 
-pub struct DefaultContext(Dialect, Vec<Value>, String);
+pub struct DefaultContext {
+    dialect: Dialect,
+    values: Vec<Value>,
+    params: Vec<(usize, String)>,
+    sql: String,
+    soft_delete: Option<SoftDelete>,
+}
 
 impl DefaultContext {
     pub fn new(dialect: Dialect) -> DefaultContext {
-        DefaultContext(dialect, Vec::default(), String::new())
+        DefaultContext {
+            dialect,
+            values: Vec::default(),
+            params: Vec::default(),
+            sql: String::new(),
+            soft_delete: None,
+        }
+    }
+
+    /// Hides the rows `policy` marks deleted from the typed selects: a
+    /// registered table is read through a subquery filtering them, and a
+    /// typed delete marks rows instead of removing them. Statements built
+    /// from `ast` are rewritten with `Stmt::rewrite` instead.
+    pub fn with_soft_delete(mut self, policy: SoftDelete) -> Self {
+        self.soft_delete = Some(policy);
+        self
+    }
+
+    /// Builds a template which keeps the named parameters open for binding.
+    pub fn build_template(self) -> Result<Template, Error> {
+        Ok(Template::new(self.sql, self.values, self.params))
+    }
+
+    /// Checks that one more bind parameter fits the dialect's limit.
+    fn reserve(&self) -> Result<(), Error> {
+        let max = match self.dialect {
+            Dialect::Sqlite => 32766,
+            Dialect::Pg | Dialect::MySQL => 65535,
+        };
+        if self.values.len() >= max {
+            return Err(Error::TooManyParameters {
+                dialect: self.dialect,
+                count: self.values.len() + 1,
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    fn write_placeholder(&mut self) -> fmt::Result {
+        match self.dialect {
+            Dialect::MySQL => self.write_str("?"),
+            Dialect::Sqlite => self.write_str("?"),
+            Dialect::Pg => {
+                write!(self.sql, "${}", self.values.len())
+            }
+        }
     }
 }
 
 impl fmt::Write for DefaultContext {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        self.2.write_str(s)
+        self.sql.write_str(s)
     }
 }
 
 impl Context for DefaultContext {
     fn dialect(&self) -> &Dialect {
-        &self.0
+        &self.dialect
     }
     fn push(&mut self, value: Value) -> Result<&mut Self, Error> {
         if value == Value::Null {
             self.write_str("NULL")?;
         } else {
-            self.1.push(value);
-            match self.0 {
-                Dialect::MySQL => self.write_str("?"),
-                Dialect::Sqlite => self.write_str("?"),
-                Dialect::Pg => {
-                    write!(self.2, "${}", self.1.len())
-                }
-            }?;
+            self.reserve()?;
+            self.values.push(value);
+            self.write_placeholder()?;
         }
 
         Ok(self)
     }
 
+    fn push_param(&mut self, name: &str) -> Result<&mut Self, Error> {
+        self.reserve()?;
+        self.params.push((self.values.len(), name.to_owned()));
+        self.values.push(Value::Null);
+        self.write_placeholder()?;
+        Ok(self)
+    }
+
     fn build(self) -> Result<(String, Vec<Value>), Error> {
-        Ok((self.2, self.1))
+        if let Some((_, name)) = self.params.into_iter().next() {
+            return Err(Error::MissingParam(name));
+        }
+        Ok((self.sql, self.values))
+    }
+
+    fn soft_delete(&self) -> Option<&SoftDelete> {
+        self.soft_delete.as_ref()
+    }
+}
+
+/// A context that writes values inline as escaped literals instead of bind
+/// parameters. Needed for DDL, which cannot take parameters.
+pub struct LiteralContext(Dialect, String);
+
+impl LiteralContext {
+    pub fn new(dialect: Dialect) -> LiteralContext {
+        LiteralContext(dialect, String::new())
+    }
+}
+
+impl fmt::Write for LiteralContext {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.1.write_str(s)
+    }
+}
+
+impl Context for LiteralContext {
+    fn dialect(&self) -> &Dialect {
+        &self.0
+    }
+
+    fn push(&mut self, value: Value) -> Result<&mut Self, Error> {
+        value.write_sql(&mut self.1, self.0)?;
+        Ok(self)
+    }
+
+    fn build(self) -> Result<(String, Vec<Value>), Error> {
+        Ok((self.1, Vec::default()))
     }
 }
 
@@ -64,7 +173,18 @@ pub fn build<S: crate::Statement<DefaultContext>>(
     dialect: Dialect,
     stmt: S,
 ) -> Result<(String, Vec<Value>), Error> {
-    let mut ctx = DefaultContext(dialect, Vec::default(), String::default());
+    let mut ctx = DefaultContext::new(dialect);
     stmt.build(&mut ctx)?;
     Ok(ctx.build()?)
 }
+
+/// Builds `stmt` into a reusable `Template` whose named parameters are bound
+/// on each execution.
+pub fn prepare<S: crate::Statement<DefaultContext>>(
+    dialect: Dialect,
+    stmt: S,
+) -> Result<Template, Error> {
+    let mut ctx = DefaultContext::new(dialect);
+    stmt.build(&mut ctx)?;
+    ctx.build_template()
+}
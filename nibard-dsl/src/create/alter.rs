@@ -1,6 +1,7 @@
 use crate::{Context, Error, Statement};
 
-use super::{Column, ForeignKey};
+use super::{Column, CreateIndex, CreateTable, ForeignKey, ReferentialAction};
+use nibard_shared::{Dialect, Type, Value};
 use std::borrow::Cow;
 use std::fmt::Write;
 
@@ -9,6 +10,12 @@ use std::fmt::Write;
 pub struct AlterTable<'a> {
     pub table: Cow<'a, str>,
     pub ty: AlterTableType<'a>,
+    /// The current definition of the table. Sqlite cannot alter columns in
+    /// place, so it needs this to rebuild the table.
+    pub schema: Option<CreateTable<'a>>,
+    /// The indexes on the table, created again after a Sqlite rebuild.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub indexes: Vec<CreateIndex<'a>>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -17,7 +24,21 @@ pub enum AlterTableType<'a> {
     Rename(Cow<'a, str>),
     AddColumn(Column<'a>),
     RemoveColumn(Cow<'a, str>),
-    RenameColumn,
+    RenameColumn {
+        from: Cow<'a, str>,
+        to: Cow<'a, str>,
+    },
+    AlterColumnType {
+        column: Cow<'a, str>,
+        ty: Type,
+    },
+    SetDefault {
+        column: Cow<'a, str>,
+        value: Value,
+    },
+    DropDefault(Cow<'a, str>),
+    SetNotNull(Cow<'a, str>),
+    DropNotNull(Cow<'a, str>),
     ForeignKey(AlterForeignKey<'a>),
 }
 
@@ -29,24 +50,459 @@ pub struct AlterForeignKey<'a> {
     pub fk: ForeignKey<'a>,
 }
 
+impl<'a> AlterTable<'a> {
+    pub fn new(table: impl Into<Cow<'a, str>>, ty: AlterTableType<'a>) -> AlterTable<'a> {
+        AlterTable {
+            table: table.into(),
+            ty,
+            schema: None,
+            indexes: Vec::new(),
+        }
+    }
+
+    pub fn rename(table: impl Into<Cow<'a, str>>, to: impl Into<Cow<'a, str>>) -> AlterTable<'a> {
+        AlterTable::new(table, AlterTableType::Rename(to.into()))
+    }
+
+    pub fn add_column(table: impl Into<Cow<'a, str>>, column: Column<'a>) -> AlterTable<'a> {
+        AlterTable::new(table, AlterTableType::AddColumn(column))
+    }
+
+    pub fn remove_column(
+        table: impl Into<Cow<'a, str>>,
+        column: impl Into<Cow<'a, str>>,
+    ) -> AlterTable<'a> {
+        AlterTable::new(table, AlterTableType::RemoveColumn(column.into()))
+    }
+
+    pub fn rename_column(
+        table: impl Into<Cow<'a, str>>,
+        from: impl Into<Cow<'a, str>>,
+        to: impl Into<Cow<'a, str>>,
+    ) -> AlterTable<'a> {
+        AlterTable::new(
+            table,
+            AlterTableType::RenameColumn {
+                from: from.into(),
+                to: to.into(),
+            },
+        )
+    }
+
+    pub fn alter_column_type(
+        table: impl Into<Cow<'a, str>>,
+        column: impl Into<Cow<'a, str>>,
+        ty: Type,
+    ) -> AlterTable<'a> {
+        AlterTable::new(
+            table,
+            AlterTableType::AlterColumnType {
+                column: column.into(),
+                ty,
+            },
+        )
+    }
+
+    pub fn set_default(
+        table: impl Into<Cow<'a, str>>,
+        column: impl Into<Cow<'a, str>>,
+        value: impl Into<Value>,
+    ) -> AlterTable<'a> {
+        AlterTable::new(
+            table,
+            AlterTableType::SetDefault {
+                column: column.into(),
+                value: value.into(),
+            },
+        )
+    }
+
+    pub fn drop_default(
+        table: impl Into<Cow<'a, str>>,
+        column: impl Into<Cow<'a, str>>,
+    ) -> AlterTable<'a> {
+        AlterTable::new(table, AlterTableType::DropDefault(column.into()))
+    }
+
+    pub fn set_not_null(
+        table: impl Into<Cow<'a, str>>,
+        column: impl Into<Cow<'a, str>>,
+    ) -> AlterTable<'a> {
+        AlterTable::new(table, AlterTableType::SetNotNull(column.into()))
+    }
+
+    pub fn drop_not_null(
+        table: impl Into<Cow<'a, str>>,
+        column: impl Into<Cow<'a, str>>,
+    ) -> AlterTable<'a> {
+        AlterTable::new(table, AlterTableType::DropNotNull(column.into()))
+    }
+
+    pub fn foreign_key(
+        table: impl Into<Cow<'a, str>>,
+        name: impl Into<Cow<'a, str>>,
+        column: impl Into<Cow<'a, str>>,
+        fk: ForeignKey<'a>,
+    ) -> AlterTable<'a> {
+        AlterTable::new(
+            table,
+            AlterTableType::ForeignKey(AlterForeignKey {
+                name: name.into(),
+                column: column.into(),
+                fk,
+            }),
+        )
+    }
+
+    pub fn schema(mut self, schema: CreateTable<'a>) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    pub fn index(mut self, index: CreateIndex<'a>) -> Self {
+        self.indexes.push(index);
+        self
+    }
+
+    pub(crate) fn needs_rebuild(&self, dialect: Dialect) -> bool {
+        if dialect != Dialect::Sqlite {
+            return false;
+        }
+        !matches!(
+            self.ty,
+            AlterTableType::Rename(_)
+                | AlterTableType::AddColumn(_)
+                | AlterTableType::RemoveColumn(_)
+                | AlterTableType::RenameColumn { .. }
+        )
+    }
+
+    pub(crate) fn column(&self, name: &str) -> Option<&Column<'a>> {
+        self.schema
+            .as_ref()
+            .and_then(|schema| schema.fields.iter().find(|field| field.name == name))
+    }
+
+    fn require_column(&self, dialect: Dialect, name: &str) -> Result<&Column<'a>, Error> {
+        self.column(name).ok_or_else(|| Error::Unsupported {
+            dialect,
+            feature: format!(
+                "altering column {}.{} without the table schema",
+                self.table, name
+            ),
+        })
+    }
+
+    /// Applies the alteration to the table schema, returning the new
+    /// definition.
+    pub(crate) fn apply(&self, dialect: Dialect) -> Result<CreateTable<'a>, Error> {
+        let mut schema = match &self.schema {
+            Some(schema) => schema.clone(),
+            None => {
+                return Err(Error::Unsupported {
+                    dialect,
+                    feature: format!("altering table {} without the table schema", self.table),
+                })
+            }
+        };
+
+        let name = match &self.ty {
+            AlterTableType::Rename(_) | AlterTableType::AddColumn(_) => None,
+            AlterTableType::RemoveColumn(column)
+            | AlterTableType::RenameColumn { from: column, .. }
+            | AlterTableType::AlterColumnType { column, .. }
+            | AlterTableType::SetDefault { column, .. }
+            | AlterTableType::DropDefault(column)
+            | AlterTableType::SetNotNull(column)
+            | AlterTableType::DropNotNull(column) => Some(column),
+            AlterTableType::ForeignKey(fk) => Some(&fk.column),
+        };
+
+        if let Some(name) = name {
+            self.require_column(dialect, name)?;
+        }
+
+        let field = name.and_then(|name| schema.fields.iter_mut().find(|f| f.name == *name));
+
+        match (&self.ty, field) {
+            (AlterTableType::Rename(to), _) => schema.name = to.clone(),
+            (AlterTableType::AddColumn(column), _) => schema.fields.push(column.clone()),
+            (AlterTableType::RemoveColumn(column), _) => {
+                schema.fields.retain(|field| field.name != *column)
+            }
+            (AlterTableType::RenameColumn { to, .. }, Some(field)) => field.name = to.clone(),
+            (AlterTableType::AlterColumnType { ty, .. }, Some(field)) => field.ty = *ty,
+            (AlterTableType::SetDefault { value, .. }, Some(field)) => {
+                field.default = Some(value.clone())
+            }
+            (AlterTableType::DropDefault(_), Some(field)) => field.default = None,
+            (AlterTableType::SetNotNull(_), Some(field)) => field.required = true,
+            (AlterTableType::DropNotNull(_), Some(field)) => field.required = false,
+            (AlterTableType::ForeignKey(fk), Some(field)) => {
+                field.foreign_key = Some(fk.fk.clone())
+            }
+            (_, None) => unreachable!("column checked above"),
+        }
+
+        Ok(schema)
+    }
+
+    /// Sqlite can only rename tables, and add, drop or rename columns.
+    /// Everything else goes through the documented procedure: with foreign
+    /// keys off, create a new table, copy the rows, drop the old table,
+    /// rename the new one and create the indexes given with `index` again.
+    ///
+    /// `PRAGMA foreign_keys` is a no-op inside a transaction, so the
+    /// statements have to run outside of one, or rows referencing the table
+    /// are deleted or rejected with it. The foreign keys are not checked
+    /// afterwards: `PRAGMA foreign_key_check` reports violations as rows, so
+    /// the caller has to run and read it (`Migrator` does). Triggers on the
+    /// old table are dropped with it and must be recreated by the caller.
+    fn build_rebuild<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        let mut schema = self.apply(dialect)?;
+        let tmp = format!("new_{}", self.table);
+        schema.name = Cow::Owned(tmp.clone());
+        schema.force = true;
+
+        ctx.write_str("PRAGMA foreign_keys = OFF; ")?;
+        schema.build(ctx)?;
+
+        let (from, to): (Vec<_>, Vec<_>) = self
+            .schema
+            .iter()
+            .flat_map(|schema| schema.fields.iter())
+            .map(|field| match &self.ty {
+                AlterTableType::RenameColumn { from, to } if field.name == *from => {
+                    (field.name.clone(), to.clone())
+                }
+                _ => (field.name.clone(), field.name.clone()),
+            })
+            .unzip();
+
+        write!(
+            ctx,
+            "; INSERT INTO {} ({}) SELECT {} FROM {}",
+            tmp,
+            to.join(", "),
+            from.join(", "),
+            self.table
+        )?;
+        write!(ctx, "; DROP TABLE {}", self.table)?;
+        write!(ctx, "; ALTER TABLE {} RENAME TO {}", tmp, self.table)?;
+        for index in &self.indexes {
+            ctx.write_str("; ")?;
+            index.build(ctx)?;
+        }
+        ctx.write_str("; PRAGMA foreign_keys = ON")?;
+
+        Ok(())
+    }
+}
+
 impl<'a, C: Context> Statement<C> for AlterTable<'a> {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+
+        if self.needs_rebuild(dialect) {
+            return self.build_rebuild(ctx);
+        }
+
         write!(ctx, "ALTER TABLE {}", self.table)?;
 
         match &self.ty {
+            AlterTableType::Rename(to) => {
+                write!(ctx, " RENAME TO {}", to)?;
+            }
+            AlterTableType::AddColumn(column) => {
+                ctx.write_str(" ADD COLUMN ")?;
+                column.build(ctx)?;
+            }
+            AlterTableType::RemoveColumn(column) => {
+                write!(ctx, " DROP COLUMN {}", column)?;
+            }
+            AlterTableType::RenameColumn { from, to } => {
+                write!(ctx, " RENAME COLUMN {} TO {}", from, to)?;
+            }
+            AlterTableType::AlterColumnType { column, ty } => match dialect {
+                // MODIFY COLUMN restates the whole column, so without its
+                // definition NOT NULL and the default would be lost
+                Dialect::MySQL => {
+                    let mut field = self.require_column(dialect, column)?.clone();
+                    field.ty = *ty;
+                    ctx.write_str(" MODIFY COLUMN ")?;
+                    field.build(ctx)?;
+                }
+                _ => {
+                    write!(ctx, " ALTER COLUMN {} TYPE ", column)?;
+                    ty.write_sql(ctx, dialect)?;
+                }
+            },
+            AlterTableType::SetDefault { column, value } => {
+                write!(ctx, " ALTER COLUMN {} SET DEFAULT ", column)?;
+                value.write_sql(ctx, dialect)?;
+            }
+            AlterTableType::DropDefault(column) => {
+                write!(ctx, " ALTER COLUMN {} DROP DEFAULT", column)?;
+            }
+            AlterTableType::SetNotNull(column) | AlterTableType::DropNotNull(column) => {
+                let required = matches!(self.ty, AlterTableType::SetNotNull(_));
+                match dialect {
+                    Dialect::MySQL => {
+                        let mut field = self.require_column(dialect, column)?.clone();
+                        field.required = required;
+                        ctx.write_str(" MODIFY COLUMN ")?;
+                        field.build(ctx)?;
+                    }
+                    _ if required => write!(ctx, " ALTER COLUMN {} SET NOT NULL", column)?,
+                    _ => write!(ctx, " ALTER COLUMN {} DROP NOT NULL", column)?,
+                }
+            }
             AlterTableType::ForeignKey(a) => {
-                //
                 write!(
                     ctx,
                     " ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({})",
                     a.name, a.column, a.fk.table, a.fk.column
                 )?;
-            }
-            _ => {
-                unimplemented!("not implemeted")
+                if a.fk.on_update != ReferentialAction::NoAction {
+                    write!(ctx, " ON UPDATE {}", a.fk.on_update)?;
+                }
+                if a.fk.on_delete != ReferentialAction::NoAction {
+                    write!(ctx, " ON DELETE {}", a.fk.on_delete)?;
+                }
             }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+
+    fn todos() -> CreateTable<'static> {
+        CreateTable::new("todos")
+            .column(Column::new("id", Type::Auto).primary_key())
+            .column(Column::new("label", Type::Text).not_null())
+    }
+
+    #[test]
+    fn rename_column() {
+        let (sql, _) = build(
+            Dialect::Pg,
+            AlterTable::rename_column("todos", "label", "title"),
+        )
+        .unwrap();
+        assert_eq!(sql, "ALTER TABLE todos RENAME COLUMN label TO title");
+    }
+
+    #[test]
+    fn set_default() {
+        let (sql, values) = build(
+            Dialect::Pg,
+            AlterTable::set_default("todos", "label", "it's"),
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "ALTER TABLE todos ALTER COLUMN label SET DEFAULT 'it''s'"
+        );
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn foreign_key_actions() {
+        let (sql, _) = build(
+            Dialect::Pg,
+            AlterTable::foreign_key(
+                "todos",
+                "todos_list",
+                "list_id",
+                ForeignKey::new("lists", "id")
+                    .on_update(ReferentialAction::Restrict)
+                    .on_delete(ReferentialAction::Cascade),
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "ALTER TABLE todos ADD CONSTRAINT todos_list FOREIGN KEY (list_id) \
+             REFERENCES lists (id) ON UPDATE RESTRICT ON DELETE CASCADE"
+        );
+    }
+
+    #[test]
+    fn mysql_not_null_needs_schema() {
+        let ret = build(Dialect::MySQL, AlterTable::drop_not_null("todos", "label"));
+        assert!(matches!(ret, Err(Error::Unsupported { .. })));
+
+        let (sql, _) = build(
+            Dialect::MySQL,
+            AlterTable::drop_not_null("todos", "label").schema(todos()),
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "ALTER TABLE todos MODIFY COLUMN label TEXT DEFAULT NULL"
+        );
+    }
+
+    #[test]
+    fn sqlite_rebuild() {
+        let (sql, _) = build(
+            Dialect::Sqlite,
+            AlterTable::alter_column_type("todos", "label", Type::VarChar(100)).schema(todos()),
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "PRAGMA foreign_keys = OFF; \
+             CREATE TABLE new_todos(id INTEGER PRIMARY KEY AUTOINCREMENT, label VARCHAR(100) NOT NULL); \
+             INSERT INTO new_todos (id, label) SELECT id, label FROM todos; \
+             DROP TABLE todos; \
+             ALTER TABLE new_todos RENAME TO todos; \
+             PRAGMA foreign_keys = ON"
+        );
+    }
+
+    #[test]
+    fn sqlite_rebuild_indexes() {
+        let (sql, _) = build(
+            Dialect::Sqlite,
+            AlterTable::set_not_null("todos", "label")
+                .schema(todos())
+                .index(CreateIndex::new(
+                    "todos",
+                    "todos_label",
+                    vec!["label".into()],
+                )),
+        )
+        .unwrap();
+        assert!(sql.ends_with(
+            "ALTER TABLE new_todos RENAME TO todos; \
+             CREATE INDEX todos_label ON todos (label); \
+             PRAGMA foreign_keys = ON"
+        ));
+    }
+
+    #[test]
+    fn mysql_column_type_needs_schema() {
+        let ret = build(
+            Dialect::MySQL,
+            AlterTable::alter_column_type("todos", "label", Type::VarChar(100)),
+        );
+        assert!(matches!(ret, Err(Error::Unsupported { .. })));
+
+        let (sql, _) = build(
+            Dialect::MySQL,
+            AlterTable::alter_column_type("todos", "label", Type::VarChar(100)).schema(todos()),
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "ALTER TABLE todos MODIFY COLUMN label VARCHAR(100) NOT NULL"
+        );
+    }
+}
@@ -8,8 +8,10 @@ use std::fmt::Write;
 pub struct CreateTable<'a> {
     pub name: Cow<'a, str>,
     pub fields: Vec<Column<'a>>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub force: bool,
     #[allow(unused)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub temporary: bool,
 }
 
@@ -38,6 +40,12 @@ impl<'a, C: Context> Statement<C> for CreateTable<'a> {
                 ", FOREIGN KEY ({}) REFERENCES {}({})",
                 name, fk.table, fk.column
             )?;
+            if fk.on_update != ReferentialAction::NoAction {
+                write!(ctx, " ON UPDATE {}", fk.on_update)?;
+            }
+            if fk.on_delete != ReferentialAction::NoAction {
+                write!(ctx, " ON DELETE {}", fk.on_delete)?;
+            }
         }
         ctx.write_str(")")?;
         Ok(())
@@ -69,9 +77,13 @@ impl<'a> CreateTable<'a> {
 pub struct Column<'a> {
     pub name: Cow<'a, str>,
     pub ty: Type,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub required: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub primary_key: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub default: Option<Value>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub foreign_key: Option<ForeignKey<'a>>,
 }
 
@@ -101,19 +113,20 @@ impl<'a> Column<'a> {
         self.foreign_key = Some(fk);
         self
     }
+
+    pub fn default(mut self, value: impl Into<Value>) -> Self {
+        self.default = Some(value.into());
+        self
+    }
 }
 
 impl<'a> Column<'a> {
-    fn build<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
+    pub(crate) fn build<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
         write!(ctx, "{} ", self.name)?;
 
         let dialect = *ctx.dialect();
 
-        if !(self.ty.is_auto() && dialect == Dialect::Pg) {
-            self.ty.write_sql(ctx, dialect)?;
-        } else {
-            ctx.write_str("SERIAL")?;
-        }
+        self.ty.write_sql(ctx, dialect)?;
 
         if self.primary_key {
             write!(ctx, " PRIMARY KEY")?;
@@ -122,19 +135,30 @@ impl<'a> Column<'a> {
         if !(self.ty.is_auto() && dialect == Dialect::Sqlite) {
             if self.required {
                 ctx.write_str(" NOT NULL")?;
-            } else {
-                ctx.write_str(" DEFAULT NULL")?;
+            }
+
+            match &self.default {
+                Some(default) => {
+                    ctx.write_str(" DEFAULT ")?;
+                    default.write_sql(ctx, dialect)?;
+                }
+                // Pg rejects a second default on serial columns, and MySQL
+                // a null default on primary keys
+                None if !self.required && !self.primary_key && !self.ty.is_auto() => {
+                    ctx.write_str(" DEFAULT NULL")?
+                }
+                None => {}
             }
         }
 
         if self.ty.is_auto() {
-            if dialect == Dialect::Sqlite {
-                ctx.write_str(" AUTOINCREMENT")?;
+            match dialect {
+                Dialect::Sqlite => ctx.write_str(" AUTOINCREMENT")?,
+                Dialect::MySQL => ctx.write_str(" AUTO_INCREMENT")?,
+                Dialect::Pg => {}
             }
         }
 
-        if let Some(_default) = &self.default {}
-
         Ok(())
     }
 }
@@ -146,8 +170,10 @@ pub struct ForeignKey<'a> {
     pub table: Cow<'a, str>,
     // #[cfg_attr(feature = "serde", serde(borrow))]
     pub column: Cow<'a, str>,
-    on_update: ReferentialAction,
-    on_delete: ReferentialAction,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub on_update: ReferentialAction,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub on_delete: ReferentialAction,
 }
 
 impl<'a> ForeignKey<'a> {
@@ -159,14 +185,90 @@ impl<'a> ForeignKey<'a> {
             on_update: ReferentialAction::NoAction,
         }
     }
+
+    pub fn on_update(mut self, action: ReferentialAction) -> Self {
+        self.on_update = action;
+        self
+    }
+
+    pub fn on_delete(mut self, action: ReferentialAction) -> Self {
+        self.on_delete = action;
+        self
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReferentialAction {
     Cascade,
     Restrict,
     SetNull,
     SetDefault,
+    #[default]
     NoAction,
 }
+
+impl std::fmt::Display for ReferentialAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self {
+            ReferentialAction::Cascade => "CASCADE",
+            ReferentialAction::Restrict => "RESTRICT",
+            ReferentialAction::SetNull => "SET NULL",
+            ReferentialAction::SetDefault => "SET DEFAULT",
+            ReferentialAction::NoAction => "NO ACTION",
+        };
+        f.write_str(action)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::StatementExt;
+
+    fn table() -> CreateTable<'static> {
+        CreateTable::new("files")
+            .column(Column::new("id", Type::BigAuto).primary_key())
+            .column(Column::new("data", Type::Binary).not_null())
+            .column(Column::new("folder_id", Type::Int).foreign_key(
+                ForeignKey::new("folders", "id").on_delete(ReferentialAction::Cascade),
+            ))
+    }
+
+    #[test]
+    fn mysql() {
+        assert_eq!(
+            table().to_sql_literal(Dialect::MySQL).unwrap(),
+            "CREATE TABLE IF NOT EXISTS files(id BIGINT PRIMARY KEY AUTO_INCREMENT, \
+             data BLOB NOT NULL, folder_id INTEGER DEFAULT NULL, \
+             FOREIGN KEY (folder_id) REFERENCES folders(id) ON DELETE CASCADE)"
+        );
+        let auto = CreateTable::new("tags").column(Column::new("id", Type::Auto).primary_key());
+        assert_eq!(
+            auto.to_sql_literal(Dialect::MySQL).unwrap(),
+            "CREATE TABLE IF NOT EXISTS tags(id INTEGER PRIMARY KEY AUTO_INCREMENT)"
+        );
+        let key =
+            CreateTable::new("versions").column(Column::new("version", Type::BigInt).primary_key());
+        assert_eq!(
+            key.to_sql_literal(Dialect::MySQL).unwrap(),
+            "CREATE TABLE IF NOT EXISTS versions(version BIGINT PRIMARY KEY)"
+        );
+    }
+
+    #[test]
+    fn auto_increment() {
+        assert_eq!(
+            table().to_sql_literal(Dialect::Pg).unwrap(),
+            "CREATE TABLE IF NOT EXISTS files(id BIGSERIAL PRIMARY KEY, \
+             data BYTEA NOT NULL, folder_id INTEGER DEFAULT NULL, \
+             FOREIGN KEY (folder_id) REFERENCES folders(id) ON DELETE CASCADE)"
+        );
+        assert_eq!(
+            table().to_sql_literal(Dialect::Sqlite).unwrap(),
+            "CREATE TABLE IF NOT EXISTS files(id INTEGER PRIMARY KEY AUTOINCREMENT, \
+             data BYTEA NOT NULL, folder_id INTEGER DEFAULT NULL, \
+             FOREIGN KEY (folder_id) REFERENCES folders(id) ON DELETE CASCADE)"
+        );
+    }
+}
@@ -0,0 +1,466 @@
+use super::{AlterTable, AlterTableType, Column, CreateIndex, CreateTable, Schema};
+use crate::drop::{DropIndex, DropTable};
+use crate::{Context, Error, Statement, StatementExt};
+use nibard_shared::{Dialect, Type};
+use std::fmt;
+
+/// A single step of a `SchemaDiff`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<'a> {
+    CreateTable(CreateTable<'a>),
+    DropTable(DropTable<'a>),
+    AlterTable(AlterTable<'a>),
+    CreateIndex(CreateIndex<'a>),
+    DropIndex(DropIndex<'a>),
+}
+
+impl<'a> Change<'a> {
+    /// Whether applying the change can lose data: dropped tables and
+    /// columns, and column types that cannot hold every current value.
+    pub fn is_destructive(&self) -> bool {
+        match self {
+            Change::DropTable(_) => true,
+            Change::AlterTable(alter) => match &alter.ty {
+                AlterTableType::RemoveColumn(_) => true,
+                AlterTableType::AlterColumnType { column, ty } => match alter.column(column) {
+                    Some(current) => !widens(current.ty, *ty),
+                    None => true,
+                },
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl<'a, C: Context> Statement<C> for Change<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        match self {
+            Change::CreateTable(create) => create.build(ctx),
+            Change::DropTable(drop) => drop.build(ctx),
+            Change::AlterTable(alter) => alter.build(ctx),
+            Change::CreateIndex(index) => index.build(ctx),
+            Change::DropIndex(drop) => drop.build(ctx),
+        }
+    }
+}
+
+impl<'a> fmt::Display for Change<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::CreateTable(create) => write!(f, "create table {}", create.name),
+            Change::DropTable(drop) => write!(f, "drop table {}", drop.name),
+            Change::CreateIndex(index) => write!(f, "create index {}", index.name),
+            Change::DropIndex(drop) => write!(f, "drop index {}", drop.name),
+            Change::AlterTable(alter) => {
+                let table = &alter.table;
+                match &alter.ty {
+                    AlterTableType::Rename(to) => write!(f, "rename table {} to {}", table, to),
+                    AlterTableType::AddColumn(column) => {
+                        write!(f, "add column {}.{}", table, column.name)
+                    }
+                    AlterTableType::RemoveColumn(column) => {
+                        write!(f, "drop column {}.{}", table, column)
+                    }
+                    AlterTableType::RenameColumn { from, to } => {
+                        write!(f, "rename column {}.{} to {}", table, from, to)
+                    }
+                    AlterTableType::AlterColumnType { column, ty } => {
+                        write!(f, "change type of {}.{} to {:?}", table, column, ty)
+                    }
+                    AlterTableType::SetDefault { column, .. } => {
+                        write!(f, "set default of {}.{}", table, column)
+                    }
+                    AlterTableType::DropDefault(column) => {
+                        write!(f, "drop default of {}.{}", table, column)
+                    }
+                    AlterTableType::SetNotNull(column) => {
+                        write!(f, "make {}.{} not null", table, column)
+                    }
+                    AlterTableType::DropNotNull(column) => {
+                        write!(f, "make {}.{} nullable", table, column)
+                    }
+                    AlterTableType::ForeignKey(fk) => {
+                        write!(f, "add foreign key on {}.{}", table, fk.column)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The changes turning one schema into another, in an order the database
+/// accepts: indexes that go away are dropped first, then new tables are
+/// created in foreign key order, existing tables altered, columns dropped,
+/// old tables dropped with their dependents first, and finally new indexes
+/// created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaDiff<'a> {
+    pub dialect: Dialect,
+    pub changes: Vec<Change<'a>>,
+    allow_destructive: bool,
+}
+
+impl<'a> SchemaDiff<'a> {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn destructive(&self) -> impl Iterator<Item = &Change<'a>> {
+        self.changes.iter().filter(|change| change.is_destructive())
+    }
+
+    /// Lets `statements` include changes that can lose data.
+    pub fn allow_destructive(mut self) -> Self {
+        self.allow_destructive = true;
+        self
+    }
+
+    /// The DDL applying the changes, or `Error::Destructive` listing the
+    /// changes that can lose data unless they were allowed.
+    pub fn statements(&self) -> Result<Vec<String>, Error> {
+        if !self.allow_destructive {
+            let destructive = self
+                .destructive()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            if !destructive.is_empty() {
+                return Err(Error::Destructive(destructive.join(", ")));
+            }
+        }
+
+        self.changes
+            .iter()
+            .map(|change| change.to_sql_literal(self.dialect))
+            .collect()
+    }
+}
+
+impl<'a> Schema<'a> {
+    /// The changes turning this schema, usually the introspected one, into
+    /// `desired` on `dialect`.
+    ///
+    /// Tables, columns and indexes are matched by name, so a rename shows
+    /// up as a drop and an add. Indexes are compared as written, and one
+    /// whose definition differs is dropped and created again. Changing a
+    /// primary key or dropping a foreign key is not supported.
+    pub fn diff(&self, desired: &Schema<'a>, dialect: Dialect) -> Result<SchemaDiff<'a>, Error> {
+        let find = |schema: &'_ Schema<'a>, name: &str| {
+            schema.tables.iter().position(|table| table.name == name)
+        };
+
+        let mut alters = Vec::new();
+        let mut removals = Vec::new();
+        // Sqlite rebuilds tables for most alterations, which drops their
+        // indexes
+        let mut rebuilt = Vec::new();
+        for table in &desired.tables {
+            let current = match find(self, &table.name) {
+                Some(idx) => &self.tables[idx],
+                None => continue,
+            };
+            let (table_alters, table_removals) = alter_table(dialect, current, table)?;
+            if table_alters
+                .iter()
+                .chain(&table_removals)
+                .any(|alter| alter.needs_rebuild(dialect))
+            {
+                rebuilt.push(table.name.clone());
+            }
+            alters.extend(table_alters);
+            removals.extend(table_removals);
+        }
+
+        let dropped_tables = Schema {
+            tables: self
+                .tables
+                .iter()
+                .filter(|table| find(desired, &table.name).is_none())
+                .cloned()
+                .collect(),
+            indexes: Vec::new(),
+        };
+        let created_tables = Schema {
+            tables: desired
+                .tables
+                .iter()
+                .filter(|table| find(self, &table.name).is_none())
+                .cloned()
+                .collect(),
+            indexes: Vec::new(),
+        };
+
+        let mut changes = Vec::new();
+
+        for index in &self.indexes {
+            let gone = dropped_tables.tables.iter().any(|t| t.name == index.table)
+                || rebuilt.contains(&index.table);
+            let unchanged = desired.indexes.iter().any(|i| i == index);
+            if !gone && !unchanged {
+                changes.push(Change::DropIndex(
+                    DropIndex::new(index.name.clone()).on(index.table.clone()),
+                ));
+            }
+        }
+
+        for table in created_tables.ordered_tables()? {
+            changes.push(Change::CreateTable(table.clone()));
+        }
+        changes.extend(alters.into_iter().map(Change::AlterTable));
+        changes.extend(removals.into_iter().map(Change::AlterTable));
+        for table in dropped_tables.ordered_tables()?.into_iter().rev() {
+            changes.push(Change::DropTable(DropTable::new(table.name.clone())));
+        }
+
+        for index in &desired.indexes {
+            let unchanged = self.indexes.iter().any(|i| i == index);
+            if !unchanged || rebuilt.contains(&index.table) {
+                changes.push(Change::CreateIndex(index.clone()));
+            }
+        }
+
+        Ok(SchemaDiff {
+            dialect,
+            changes,
+            allow_destructive: false,
+        })
+    }
+}
+
+/// The alterations of a table present in both schemas, with column
+/// removals kept apart so they can run after every other alteration. Each
+/// alteration carries the table as it is at that point, which Sqlite and
+/// MySQL need to rebuild or restate columns.
+fn alter_table<'a>(
+    dialect: Dialect,
+    current: &CreateTable<'a>,
+    desired: &CreateTable<'a>,
+) -> Result<(Vec<AlterTable<'a>>, Vec<AlterTable<'a>>), Error> {
+    let mut state = current.clone();
+    let mut alters = Vec::new();
+
+    let mut push =
+        |alters: &mut Vec<AlterTable<'a>>, ty: AlterTableType<'a>| -> Result<(), Error> {
+            let alter = AlterTable::new(desired.name.clone(), ty).schema(state.clone());
+            state = alter.apply(dialect)?;
+            alters.push(alter);
+            Ok(())
+        };
+
+    for column in &desired.fields {
+        let existing = match current.fields.iter().find(|f| f.name == column.name) {
+            Some(existing) => existing,
+            None => {
+                let mut added = column.clone();
+                added.foreign_key = None;
+                push(&mut alters, AlterTableType::AddColumn(added))?;
+                if let Some(fk) = &column.foreign_key {
+                    push(&mut alters, foreign_key(desired, column, fk.clone()))?;
+                }
+                continue;
+            }
+        };
+
+        if existing.primary_key != column.primary_key {
+            return Err(Error::Unsupported {
+                dialect,
+                feature: format!("changing the primary key of {}", desired.name),
+            });
+        }
+
+        if !same_type(dialect, existing.ty, column.ty) {
+            push(
+                &mut alters,
+                AlterTableType::AlterColumnType {
+                    column: column.name.clone(),
+                    ty: column.ty,
+                },
+            )?;
+        }
+
+        if existing.default != column.default {
+            let ty = match &column.default {
+                Some(value) => AlterTableType::SetDefault {
+                    column: column.name.clone(),
+                    value: value.clone(),
+                },
+                None => AlterTableType::DropDefault(column.name.clone()),
+            };
+            push(&mut alters, ty)?;
+        }
+
+        if is_required(existing) != is_required(column) {
+            let ty = if is_required(column) {
+                AlterTableType::SetNotNull(column.name.clone())
+            } else {
+                AlterTableType::DropNotNull(column.name.clone())
+            };
+            push(&mut alters, ty)?;
+        }
+
+        match (&existing.foreign_key, &column.foreign_key) {
+            (current, Some(fk)) if current.as_ref() != Some(fk) => {
+                push(&mut alters, foreign_key(desired, column, fk.clone()))?;
+            }
+            (Some(_), None) => {
+                return Err(Error::Unsupported {
+                    dialect,
+                    feature: format!(
+                        "dropping the foreign key on {}.{}",
+                        desired.name, column.name
+                    ),
+                })
+            }
+            _ => {}
+        }
+    }
+
+    let mut removals = Vec::new();
+    for column in &current.fields {
+        if !desired.fields.iter().any(|f| f.name == column.name) {
+            push(
+                &mut removals,
+                AlterTableType::RemoveColumn(column.name.clone()),
+            )?;
+        }
+    }
+
+    Ok((alters, removals))
+}
+
+fn foreign_key<'a>(
+    table: &CreateTable<'a>,
+    column: &Column<'a>,
+    fk: super::ForeignKey<'a>,
+) -> AlterTableType<'a> {
+    AlterTableType::ForeignKey(super::AlterForeignKey {
+        name: format!("fk_{}_{}", table.name, column.name).into(),
+        column: column.name.clone(),
+        fk,
+    })
+}
+
+/// Primary keys are never null, whether or not the column says so.
+fn is_required(column: &Column) -> bool {
+    column.required || column.primary_key
+}
+
+/// Types are the same when the dialect writes them the same, eg. `Auto`
+/// and `Int` on MySQL.
+fn same_type(dialect: Dialect, a: Type, b: Type) -> bool {
+    if a == b {
+        return true;
+    }
+    let sql = |ty: Type| {
+        let mut out = String::new();
+        ty.write_sql(&mut out, dialect).map(|_| out).ok()
+    };
+    sql(a).is_some() && sql(a) == sql(b)
+}
+
+/// Whether every value of type `from` fits in `to`.
+fn widens(from: Type, to: Type) -> bool {
+    match (from, to) {
+        _ if from == to => true,
+        (Type::SmallInt, Type::Int | Type::BigInt) => true,
+        (Type::Int | Type::Auto, Type::Int | Type::BigInt) => true,
+        (Type::BigAuto, Type::BigInt) => true,
+        (Type::Real, Type::Float(_)) => true,
+        (Type::Float(a), Type::Float(b)) => a <= b,
+        (Type::Char(a), Type::Char(b) | Type::VarChar(b)) => a <= b,
+        (Type::VarChar(a), Type::VarChar(b)) => a <= b,
+        (Type::Char(_) | Type::VarChar(_), Type::Text) => true,
+        (Type::Date, Type::DateTime) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::create::ForeignKey;
+    use nibard_shared::Value;
+
+    fn users() -> CreateTable<'static> {
+        CreateTable::new("users")
+            .column(Column::new("id", Type::Auto).primary_key())
+            .column(Column::new("name", Type::Text).not_null())
+            .column(Column::new("age", Type::Int))
+    }
+
+    #[test]
+    fn diff() {
+        let current = Schema::new()
+            .table(users())
+            .table(CreateTable::new("posts").column(
+                Column::new("owner_id", Type::Int).foreign_key(ForeignKey::new("users", "id")),
+            ))
+            .index(CreateIndex::new(
+                "users",
+                "users_age_idx",
+                vec!["age".into()],
+            ));
+
+        let desired = Schema::new()
+            .table(
+                CreateTable::new("users")
+                    .column(Column::new("id", Type::Auto).primary_key())
+                    .column(Column::new("name", Type::VarChar(64)).not_null())
+                    .column(Column::new("email", Type::Text).not_null().default(""))
+                    .column(
+                        Column::new("team_id", Type::Int)
+                            .foreign_key(ForeignKey::new("teams", "id")),
+                    ),
+            )
+            .table(
+                CreateTable::new("teams")
+                    .column(Column::new("id", Type::Auto).primary_key().not_null()),
+            )
+            .index(CreateIndex::new("users", "users_email_idx", vec!["email".into()]).unique());
+
+        let diff = current.diff(&desired, Dialect::Pg).unwrap();
+        assert!(matches!(diff.statements(), Err(Error::Destructive(_))));
+        assert_eq!(
+            diff.destructive()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "change type of users.name to VarChar(64)",
+                "drop column users.age",
+                "drop table posts",
+            ]
+        );
+
+        assert_eq!(
+            diff.allow_destructive().statements().unwrap(),
+            vec![
+                "DROP INDEX users_age_idx",
+                "CREATE TABLE IF NOT EXISTS teams(id SERIAL PRIMARY KEY NOT NULL)",
+                "ALTER TABLE users ALTER COLUMN name TYPE VARCHAR(64)",
+                "ALTER TABLE users ADD COLUMN email TEXT NOT NULL DEFAULT ''",
+                "ALTER TABLE users ADD COLUMN team_id INTEGER DEFAULT NULL",
+                "ALTER TABLE users ADD CONSTRAINT fk_users_team_id FOREIGN KEY (team_id) \
+                 REFERENCES teams (id)",
+                "ALTER TABLE users DROP COLUMN age",
+                "DROP TABLE posts",
+                "CREATE UNIQUE INDEX users_email_idx ON users (email)",
+            ]
+        );
+
+        let same = current.diff(&current, Dialect::Pg).unwrap();
+        assert!(same.is_empty());
+
+        let widened = Schema::new().table(
+            CreateTable::new("users")
+                .column(Column::new("id", Type::Auto).primary_key())
+                .column(Column::new("name", Type::Text).not_null())
+                .column(Column::new("age", Type::BigInt).default(Value::BigInt(0))),
+        );
+        let diff = Schema::new()
+            .table(users())
+            .diff(&widened, Dialect::Pg)
+            .unwrap();
+        assert_eq!(diff.destructive().count(), 0);
+        assert_eq!(diff.changes.len(), 2);
+    }
+}
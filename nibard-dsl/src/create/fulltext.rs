@@ -0,0 +1,134 @@
+use crate::query::FullText;
+use crate::{Context, Error, Statement};
+use nibard_shared::Dialect;
+
+/// DDL for a `FullText` index. On Sqlite this creates the FTS5 table, fills
+/// it from the existing rows and adds insert, update and delete triggers.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CreateFullText<'a> {
+    pub index: FullText<'a>,
+}
+
+impl<'a> CreateFullText<'a> {
+    pub fn new(index: FullText<'a>) -> CreateFullText<'a> {
+        CreateFullText { index }
+    }
+
+    fn build_sqlite<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
+        let index = &self.index;
+        let name = index.index_name();
+        let columns = index.columns.join(", ");
+        let values = |prefix: &str| {
+            index
+                .columns
+                .iter()
+                .map(|column| format!("{}.{}", prefix, column))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let insert = format!(
+            "INSERT INTO {}(rowid, {}) VALUES (new.{}, {});",
+            name,
+            columns,
+            index.key,
+            values("new")
+        );
+        let delete = format!(
+            "INSERT INTO {}({}, rowid, {}) VALUES ('delete', old.{}, {});",
+            name,
+            name,
+            columns,
+            index.key,
+            values("old")
+        );
+
+        write!(
+            ctx,
+            "CREATE VIRTUAL TABLE IF NOT EXISTS {} USING fts5({}, content='{}', content_rowid='{}')",
+            name, columns, index.table, index.key
+        )?;
+        write!(ctx, "; INSERT INTO {}({}) VALUES ('rebuild')", name, name)?;
+        write!(
+            ctx,
+            "; CREATE TRIGGER IF NOT EXISTS {}_ai AFTER INSERT ON {} BEGIN {} END",
+            name, index.table, insert
+        )?;
+        write!(
+            ctx,
+            "; CREATE TRIGGER IF NOT EXISTS {}_ad AFTER DELETE ON {} BEGIN {} END",
+            name, index.table, delete
+        )?;
+        write!(
+            ctx,
+            "; CREATE TRIGGER IF NOT EXISTS {}_au AFTER UPDATE ON {} BEGIN {} {} END",
+            name, index.table, delete, insert
+        )?;
+        Ok(())
+    }
+}
+
+impl<'a, C: Context> Statement<C> for CreateFullText<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let index = &self.index;
+        if index.columns.is_empty() {
+            return Err(Error::InvalidStatement {
+                dialect: *ctx.dialect(),
+                description: format!("full-text index on {} has no columns", index.table),
+            });
+        }
+
+        match *ctx.dialect() {
+            Dialect::Sqlite => self.build_sqlite(ctx)?,
+            Dialect::Pg => {
+                write!(
+                    ctx,
+                    "CREATE INDEX IF NOT EXISTS {} ON {} USING GIN (",
+                    index.index_name(),
+                    index.table
+                )?;
+                index.write_tsvector(ctx, false)?;
+                ctx.write_char(')')?;
+            }
+            Dialect::MySQL => {
+                write!(
+                    ctx,
+                    "CREATE FULLTEXT INDEX {} ON {} ({})",
+                    index.index_name(),
+                    index.table,
+                    index.columns.join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+
+    #[test]
+    fn ddl() {
+        let index = FullText::new("docs", ["title", "body"]);
+
+        let (sql, _) = build(Dialect::Pg, index.create()).unwrap();
+        assert_eq!(
+            sql,
+            "CREATE INDEX IF NOT EXISTS docs_fts ON docs USING GIN \
+             (to_tsvector('english', coalesce(title, '') || ' ' || coalesce(body, '')))"
+        );
+
+        let (sql, _) = build(Dialect::Sqlite, index.create()).unwrap();
+        assert!(sql.starts_with(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS docs_fts USING fts5(title, body, \
+             content='docs', content_rowid='id'); INSERT INTO docs_fts(docs_fts) VALUES ('rebuild')"
+        ));
+        assert!(sql.ends_with(
+            "AFTER UPDATE ON docs BEGIN \
+             INSERT INTO docs_fts(docs_fts, rowid, title, body) VALUES ('delete', old.id, old.title, old.body); \
+             INSERT INTO docs_fts(rowid, title, body) VALUES (new.id, new.title, new.body); END"
+        ));
+    }
+}
@@ -1,15 +1,32 @@
 use std::borrow::Cow;
-use std::fmt::Write;
+use std::fmt;
+use std::sync::Arc;
 
-use crate::{Context, Error, Statement};
+pub use crate::query::Order;
+
+use crate::query::Expression;
+use crate::statement::inline;
+use crate::{Context, Error, LiteralContext, Statement};
+use nibard_shared::Dialect;
 
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CreateIndex<'a> {
-    name: Cow<'a, str>,
-    unique: bool,
-    table: Cow<'a, str>,
-    columns: Vec<Cow<'a, str>>,
+    pub name: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub unique: bool,
+    pub table: Cow<'a, str>,
+    pub columns: Vec<IndexColumn<'a>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub method: Option<IndexMethod>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub include: Vec<Cow<'a, str>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub predicate: Option<IndexPredicate<'a>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub concurrently: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub if_not_exists: bool,
 }
 
 impl<'a> CreateIndex<'a> {
@@ -22,7 +39,12 @@ impl<'a> CreateIndex<'a> {
             name: name.into(),
             table: table.into(),
             unique: false,
-            columns,
+            columns: columns.into_iter().map(IndexColumn::new).collect(),
+            method: None,
+            include: Vec::default(),
+            predicate: None,
+            concurrently: false,
+            if_not_exists: false,
         }
     }
 
@@ -30,21 +52,308 @@ impl<'a> CreateIndex<'a> {
         self.unique = true;
         self
     }
+
+    pub fn column(mut self, column: impl Into<IndexColumn<'a>>) -> Self {
+        self.columns.push(column.into());
+        self
+    }
+
+    pub fn using(mut self, method: IndexMethod) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    pub fn include(mut self, column: impl Into<Cow<'a, str>>) -> Self {
+        self.include.push(column.into());
+        self
+    }
+
+    /// Makes this a partial index covering only rows matching `expr`.
+    pub fn filter<E>(mut self, expr: E) -> Self
+    where
+        E: Expression<LiteralContext> + Send + Sync + 'a,
+    {
+        self.predicate = Some(IndexPredicate::Expr(Arc::new(expr)));
+        self
+    }
+
+    pub fn filter_sql(mut self, sql: impl Into<Cow<'a, str>>) -> Self {
+        self.predicate = Some(IndexPredicate::Sql(sql.into()));
+        self
+    }
+
+    pub fn concurrently(mut self) -> Self {
+        self.concurrently = true;
+        self
+    }
+
+    /// Skips creating the index if one with the same name exists. MySQL
+    /// does not support this.
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+
+    fn validate(&self, dialect: Dialect) -> Result<(), Error> {
+        let unsupported = |feature: &str| {
+            Err(Error::Unsupported {
+                dialect,
+                feature: feature.to_owned(),
+            })
+        };
+
+        if self.concurrently && dialect != Dialect::Pg {
+            return unsupported("CREATE INDEX CONCURRENTLY");
+        }
+
+        if !self.include.is_empty() && dialect != Dialect::Pg {
+            return unsupported("CREATE INDEX ... INCLUDE");
+        }
+
+        if self.predicate.is_some() && dialect == Dialect::MySQL {
+            return unsupported("partial indexes");
+        }
+
+        if self.if_not_exists && dialect == Dialect::MySQL {
+            return unsupported("CREATE INDEX IF NOT EXISTS");
+        }
+
+        match (dialect, self.method) {
+            (_, None) | (Dialect::Pg, _) => {}
+            (Dialect::MySQL, Some(IndexMethod::BTree | IndexMethod::Hash)) => {}
+            (_, Some(method)) => {
+                return unsupported(&format!("index method {}", method));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a, C: Context> Statement<C> for CreateIndex<'a> {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        self.validate(dialect)?;
+
         ctx.write_str("CREATE ")?;
         if self.unique {
             ctx.write_str("UNIQUE ")?;
         }
-        write!(
-            ctx,
-            "INDEX IF NOT EXISTS {} ON {} ({})",
-            self.name,
-            self.table,
-            self.columns.join(", ")
-        )?;
+        ctx.write_str("INDEX ")?;
+        if self.concurrently {
+            ctx.write_str("CONCURRENTLY ")?;
+        }
+        if self.if_not_exists {
+            ctx.write_str("IF NOT EXISTS ")?;
+        }
+        write!(ctx, "{} ON {}", self.name, self.table)?;
+
+        if let (Dialect::Pg, Some(method)) = (dialect, self.method) {
+            write!(ctx, " USING {}", method)?;
+        }
+
+        ctx.write_str(" (")?;
+        for (idx, column) in self.columns.iter().enumerate() {
+            if idx > 0 {
+                ctx.write_str(", ")?;
+            }
+            match &column.part {
+                IndexPart::Column(name) => ctx.write_str(name)?,
+                IndexPart::Expr(sql) => write!(ctx, "({})", sql)?,
+            }
+            match column.order {
+                Some(Order::Asc) => ctx.write_str(" ASC")?,
+                Some(Order::Desc) => ctx.write_str(" DESC")?,
+                None => {}
+            }
+        }
+        ctx.write_str(")")?;
+
+        if let (Dialect::MySQL, Some(method)) = (dialect, self.method) {
+            write!(ctx, " USING {}", method)?;
+        }
+
+        if !self.include.is_empty() {
+            write!(ctx, " INCLUDE ({})", self.include.join(", "))?;
+        }
+
+        match &self.predicate {
+            Some(IndexPredicate::Sql(sql)) => write!(ctx, " WHERE {}", sql)?,
+            Some(IndexPredicate::Expr(expr)) => {
+                let sql = inline(dialect, |literal| expr.build(literal))?;
+                write!(ctx, " WHERE {}", sql)?;
+            }
+            None => {}
+        }
+
         Ok(())
     }
 }
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexColumn<'a> {
+    pub part: IndexPart<'a>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub order: Option<Order>,
+}
+
+impl<'a> IndexColumn<'a> {
+    pub fn new(name: impl Into<Cow<'a, str>>) -> IndexColumn<'a> {
+        IndexColumn {
+            part: IndexPart::Column(name.into()),
+            order: None,
+        }
+    }
+
+    /// An expression index part, such as `lower(email)`.
+    pub fn expr(sql: impl Into<Cow<'a, str>>) -> IndexColumn<'a> {
+        IndexColumn {
+            part: IndexPart::Expr(sql.into()),
+            order: None,
+        }
+    }
+
+    pub fn asc(mut self) -> Self {
+        self.order = Some(Order::Asc);
+        self
+    }
+
+    pub fn desc(mut self) -> Self {
+        self.order = Some(Order::Desc);
+        self
+    }
+}
+
+impl<'a> From<&'a str> for IndexColumn<'a> {
+    fn from(name: &'a str) -> IndexColumn<'a> {
+        IndexColumn::new(name)
+    }
+}
+
+impl<'a> From<String> for IndexColumn<'a> {
+    fn from(name: String) -> IndexColumn<'a> {
+        IndexColumn::new(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IndexPart<'a> {
+    Column(Cow<'a, str>),
+    Expr(Cow<'a, str>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IndexMethod {
+    BTree,
+    Hash,
+    Gin,
+    Gist,
+    Brin,
+}
+
+impl fmt::Display for IndexMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let m = match self {
+            IndexMethod::BTree => "BTREE",
+            IndexMethod::Hash => "HASH",
+            IndexMethod::Gin => "GIN",
+            IndexMethod::Gist => "GIST",
+            IndexMethod::Brin => "BRIN",
+        };
+        f.write_str(m)
+    }
+}
+
+/// The `WHERE` clause of a partial index. DSL expressions are rendered with
+/// their values inlined, since DDL cannot take bind parameters.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IndexPredicate<'a> {
+    Sql(Cow<'a, str>),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Expr(Arc<dyn Expression<LiteralContext> + Send + Sync + 'a>),
+}
+
+impl<'a> fmt::Debug for IndexPredicate<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexPredicate::Sql(sql) => f.debug_tuple("Sql").field(sql).finish(),
+            IndexPredicate::Expr(_) => f.debug_tuple("Expr").finish(),
+        }
+    }
+}
+
+impl<'a> PartialEq for IndexPredicate<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (IndexPredicate::Sql(a), IndexPredicate::Sql(b)) => a == b,
+            (IndexPredicate::Expr(a), IndexPredicate::Expr(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+    use crate::prelude::*;
+
+    #[test]
+    fn partial_index() {
+        let index = CreateIndex::new("users", "users_email_idx", Vec::default())
+            .column(IndexColumn::expr("lower(email)"))
+            .column(IndexColumn::new("created_at").desc())
+            .filter("deleted".eql(false).and("name".neq("o'neil")))
+            .unique()
+            .if_not_exists();
+
+        let (sql, values) = build(Dialect::Pg, &index).unwrap();
+        assert_eq!(
+            sql,
+            "CREATE UNIQUE INDEX IF NOT EXISTS users_email_idx ON users \
+             ((lower(email)), created_at DESC) WHERE deleted = FALSE AND name != 'o''neil'"
+        );
+        assert!(values.is_empty());
+
+        assert!(build(Dialect::MySQL, index).is_err());
+    }
+
+    #[test]
+    fn pg_method() {
+        let index = CreateIndex::new("docs", "docs_body_idx", vec!["body".into()])
+            .using(IndexMethod::Gin)
+            .include("id")
+            .concurrently();
+
+        let (sql, _) = build(Dialect::Pg, &index).unwrap();
+        assert_eq!(
+            sql,
+            "CREATE INDEX CONCURRENTLY docs_body_idx ON docs USING GIN (body) INCLUDE (id)"
+        );
+        assert!(build(Dialect::Sqlite, &index).is_err());
+    }
+
+    #[test]
+    fn if_not_exists() {
+        let index = CreateIndex::new("users", "users_name_idx", vec!["name".into()]);
+        for dialect in [Dialect::Sqlite, Dialect::Pg, Dialect::MySQL] {
+            let (sql, _) = build(dialect, &index).unwrap();
+            assert_eq!(sql, "CREATE INDEX users_name_idx ON users (name)");
+        }
+
+        let index = index.if_not_exists();
+        let (sql, _) = build(Dialect::Sqlite, &index).unwrap();
+        assert_eq!(
+            sql,
+            "CREATE INDEX IF NOT EXISTS users_name_idx ON users (name)"
+        );
+        assert!(matches!(
+            build(Dialect::MySQL, &index),
+            Err(Error::Unsupported { .. })
+        ));
+    }
+}
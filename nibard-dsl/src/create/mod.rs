@@ -1,5 +1,9 @@
 mod alter;
 mod create;
+mod diff;
+mod fulltext;
 mod index;
+mod schema;
+mod view;
 
-pub use self::{create::*, index::*, index::*};
+pub use self::{alter::*, create::*, diff::*, fulltext::*, index::*, schema::*, view::*};
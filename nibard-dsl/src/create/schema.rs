@@ -0,0 +1,209 @@
+use super::{CreateIndex, CreateTable};
+use crate::{Error, StatementExt};
+use nibard_shared::Dialect;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+/// Tables and indexes described together, eg. in a schema file, and turned
+/// into DDL for a dialect in an order the database accepts.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Schema<'a> {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tables: Vec<CreateTable<'a>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub indexes: Vec<CreateIndex<'a>>,
+}
+
+impl<'a> Schema<'a> {
+    pub fn new() -> Schema<'a> {
+        Schema::default()
+    }
+
+    pub fn table(mut self, table: CreateTable<'a>) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    pub fn index(mut self, index: CreateIndex<'a>) -> Self {
+        self.indexes.push(index);
+        self
+    }
+
+    /// The tables ordered so every table comes after the tables its foreign
+    /// keys reference. Tables keep their declared order otherwise.
+    /// References to tables outside the schema are assumed to exist.
+    pub fn ordered_tables(&self) -> Result<Vec<&CreateTable<'a>>, Error> {
+        let position = |name: &str| self.tables.iter().position(|table| table.name == name);
+        let depends = self
+            .tables
+            .iter()
+            .map(|table| {
+                let mut depends = table
+                    .fields
+                    .iter()
+                    .filter_map(|column| column.foreign_key.as_ref())
+                    .filter(|fk| fk.table != table.name)
+                    .filter_map(|fk| position(&fk.table))
+                    .collect::<Vec<_>>();
+                depends.dedup();
+                depends
+            })
+            .collect::<Vec<_>>();
+
+        let mut done = vec![false; self.tables.len()];
+        let mut ordered = Vec::with_capacity(self.tables.len());
+        while ordered.len() < self.tables.len() {
+            let next = (0..self.tables.len())
+                .find(|&idx| !done[idx] && depends[idx].iter().all(|&dep| done[dep]));
+            match next {
+                Some(idx) => {
+                    done[idx] = true;
+                    ordered.push(&self.tables[idx]);
+                }
+                None => {
+                    let cycle = (0..self.tables.len())
+                        .filter(|&idx| !done[idx])
+                        .map(|idx| self.tables[idx].name.as_ref())
+                        .collect::<Vec<_>>();
+                    return Err(Error::Schema(format!(
+                        "foreign keys form a cycle between {}",
+                        cycle.join(", ")
+                    )));
+                }
+            }
+        }
+        Ok(ordered)
+    }
+
+    /// The DDL creating the schema on `dialect`: tables in foreign key
+    /// order, then indexes. Like the tables, indexes are only created if
+    /// they do not exist, except on MySQL which has no
+    /// `CREATE INDEX IF NOT EXISTS`.
+    pub fn statements(&self, dialect: Dialect) -> Result<Vec<String>, Error> {
+        let mut statements = Vec::with_capacity(self.tables.len() + self.indexes.len());
+        for table in self.ordered_tables()? {
+            statements.push(table.to_sql_literal(dialect)?);
+        }
+        for index in &self.indexes {
+            if dialect != Dialect::MySQL && !index.if_not_exists {
+                statements.push(index.clone().if_not_exists().to_sql_literal(dialect)?);
+            } else {
+                statements.push(index.to_sql_literal(dialect)?);
+            }
+        }
+        Ok(statements)
+    }
+}
+
+impl Schema<'static> {
+    #[cfg(feature = "schema-json")]
+    pub fn from_json(json: &str) -> Result<Schema<'static>, Error> {
+        serde_json::from_str(json).map_err(|err| Error::Schema(err.to_string()))
+    }
+
+    #[cfg(feature = "schema-yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Schema<'static>, Error> {
+        serde_yaml::from_str(yaml).map_err(|err| Error::Schema(err.to_string()))
+    }
+
+    #[cfg(feature = "schema-toml")]
+    pub fn from_toml(toml: &str) -> Result<Schema<'static>, Error> {
+        toml::from_str(toml).map_err(|err| Error::Schema(err.to_string()))
+    }
+
+    /// Reads a schema file, picking the format from its extension among
+    /// the enabled `schema-*` features.
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Schema<'static>, Error> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let parse: fn(&str) -> Result<Schema<'static>, Error> = match extension {
+            #[cfg(feature = "schema-json")]
+            "json" => Schema::from_json,
+            #[cfg(feature = "schema-yaml")]
+            "yaml" | "yml" => Schema::from_yaml,
+            #[cfg(feature = "schema-toml")]
+            "toml" => Schema::from_toml,
+            _ => {
+                return Err(Error::Schema(format!(
+                    "{}: unsupported schema format",
+                    path.display()
+                )))
+            }
+        };
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| Error::Schema(format!("{}: {}", path.display(), err)))?;
+        parse(&content)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::create::{Column, ForeignKey};
+    use nibard_shared::Type;
+
+    #[test]
+    fn foreign_key_order() {
+        let schema = Schema::new()
+            .table(
+                CreateTable::new("posts")
+                    .column(Column::new("id", Type::Auto).primary_key())
+                    .column(
+                        Column::new("owner_id", Type::Int)
+                            .not_null()
+                            .foreign_key(ForeignKey::new("users", "id")),
+                    ),
+            )
+            .table(CreateTable::new("users").column(Column::new("id", Type::Auto).primary_key()))
+            .index(CreateIndex::new(
+                "posts",
+                "posts_owner_idx",
+                vec!["owner_id".into()],
+            ));
+
+        let statements = schema.statements(Dialect::MySQL).unwrap();
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].starts_with("CREATE TABLE IF NOT EXISTS users("));
+        assert!(statements[1].starts_with("CREATE TABLE IF NOT EXISTS posts("));
+        assert_eq!(
+            statements[2],
+            "CREATE INDEX posts_owner_idx ON posts (owner_id)"
+        );
+
+        let cycle = Schema::new()
+            .table(
+                CreateTable::new("a")
+                    .column(Column::new("b_id", Type::Int).foreign_key(ForeignKey::new("b", "id"))),
+            )
+            .table(
+                CreateTable::new("b")
+                    .column(Column::new("a_id", Type::Int).foreign_key(ForeignKey::new("a", "id"))),
+            );
+        assert!(matches!(cycle.ordered_tables(), Err(Error::Schema(_))));
+    }
+
+    #[cfg(feature = "schema-json")]
+    #[test]
+    fn from_json() {
+        let schema = Schema::from_json(
+            r#"{
+                "tables": [{
+                    "name": "users",
+                    "fields": [
+                        { "name": "id", "ty": "Auto", "primary_key": true, "required": true },
+                        { "name": "name", "ty": { "VarChar": 64 }, "required": true }
+                    ]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            schema.statements(Dialect::Pg).unwrap(),
+            vec!["CREATE TABLE IF NOT EXISTS users(id SERIAL PRIMARY KEY NOT NULL, name VARCHAR(64) NOT NULL)"]
+        );
+    }
+}
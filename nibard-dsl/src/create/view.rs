@@ -0,0 +1,238 @@
+use crate::query::Select;
+use crate::statement::inline;
+use crate::{Context, Error, LiteralContext, Statement};
+use nibard_shared::Dialect;
+use std::borrow::Cow;
+
+/// Views cannot take bind parameters, so the select is rendered with every
+/// pushed value inlined as an escaped literal.
+fn build_select<S, C>(select: &S, ctx: &mut C) -> Result<(), Error>
+where
+    S: Select<LiteralContext>,
+    C: Context,
+{
+    let sql = inline(*ctx.dialect(), |literal| select.build(literal))?;
+    ctx.write_str(&sql)?;
+    Ok(())
+}
+
+fn pg_only<C: Context>(ctx: &C, feature: &str) -> Result<(), Error> {
+    let dialect = *ctx.dialect();
+    if dialect != Dialect::Pg {
+        return Err(Error::Unsupported {
+            dialect,
+            feature: feature.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateView<'a, S> {
+    pub name: Cow<'a, str>,
+    pub select: S,
+    pub or_replace: bool,
+    pub temporary: bool,
+}
+
+impl<'a, S> CreateView<'a, S>
+where
+    S: Select<LiteralContext>,
+{
+    pub fn new(name: impl Into<Cow<'a, str>>, select: S) -> CreateView<'a, S> {
+        CreateView {
+            name: name.into(),
+            select,
+            or_replace: false,
+            temporary: false,
+        }
+    }
+
+    pub fn or_replace(mut self) -> Self {
+        self.or_replace = true;
+        self
+    }
+
+    pub fn temporary(mut self) -> Self {
+        self.temporary = true;
+        self
+    }
+}
+
+impl<'a, S, C: Context> Statement<C> for CreateView<'a, S>
+where
+    S: Select<LiteralContext>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        match dialect {
+            Dialect::Sqlite if self.or_replace => {
+                return Err(Error::Unsupported {
+                    dialect,
+                    feature: "CREATE OR REPLACE VIEW".to_owned(),
+                })
+            }
+            Dialect::MySQL if self.temporary => {
+                return Err(Error::Unsupported {
+                    dialect,
+                    feature: "CREATE TEMPORARY VIEW".to_owned(),
+                })
+            }
+            _ => {}
+        }
+
+        ctx.write_str("CREATE ")?;
+        if self.or_replace {
+            ctx.write_str("OR REPLACE ")?;
+        }
+        if self.temporary {
+            ctx.write_str("TEMPORARY ")?;
+        }
+        write!(ctx, "VIEW {} AS ", self.name)?;
+        build_select(&self.select, ctx)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateMaterializedView<'a, S> {
+    pub name: Cow<'a, str>,
+    pub select: S,
+    pub force: bool,
+    pub with_data: bool,
+}
+
+impl<'a, S> CreateMaterializedView<'a, S>
+where
+    S: Select<LiteralContext>,
+{
+    pub fn new(name: impl Into<Cow<'a, str>>, select: S) -> CreateMaterializedView<'a, S> {
+        CreateMaterializedView {
+            name: name.into(),
+            select,
+            force: false,
+            with_data: true,
+        }
+    }
+
+    pub fn force(mut self) -> Self {
+        self.force = true;
+        self
+    }
+
+    /// Creates the view without populating it. It has to be refreshed
+    /// before it can be queried.
+    pub fn with_no_data(mut self) -> Self {
+        self.with_data = false;
+        self
+    }
+}
+
+impl<'a, S, C: Context> Statement<C> for CreateMaterializedView<'a, S>
+where
+    S: Select<LiteralContext>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        pg_only(ctx, "materialized views")?;
+
+        ctx.write_str("CREATE MATERIALIZED VIEW ")?;
+        if !self.force {
+            ctx.write_str("IF NOT EXISTS ")?;
+        }
+        write!(ctx, "{} AS ", self.name)?;
+        build_select(&self.select, ctx)?;
+        if !self.with_data {
+            ctx.write_str(" WITH NO DATA")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefreshMaterializedView<'a> {
+    pub name: Cow<'a, str>,
+    pub concurrently: bool,
+    pub with_data: bool,
+}
+
+impl<'a> RefreshMaterializedView<'a> {
+    pub fn new(name: impl Into<Cow<'a, str>>) -> RefreshMaterializedView<'a> {
+        RefreshMaterializedView {
+            name: name.into(),
+            concurrently: false,
+            with_data: true,
+        }
+    }
+
+    /// Refreshes without locking out readers. Requires a unique index on
+    /// the view.
+    pub fn concurrently(mut self) -> Self {
+        self.concurrently = true;
+        self
+    }
+
+    pub fn with_no_data(mut self) -> Self {
+        self.with_data = false;
+        self
+    }
+}
+
+impl<'a, C: Context> Statement<C> for RefreshMaterializedView<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        pg_only(ctx, "materialized views")?;
+        if self.concurrently && !self.with_data {
+            return Err(Error::InvalidStatement {
+                dialect: *ctx.dialect(),
+                description: "a materialized view cannot be refreshed concurrently with no data"
+                    .to_owned(),
+            });
+        }
+
+        ctx.write_str("REFRESH MATERIALIZED VIEW ")?;
+        if self.concurrently {
+            ctx.write_str("CONCURRENTLY ")?;
+        }
+        ctx.write_str(&self.name)?;
+        if !self.with_data {
+            ctx.write_str(" WITH NO DATA")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+    use crate::prelude::*;
+
+    #[test]
+    fn inlines_values() {
+        let select = "users".select("users.*").filter("active".eql(true));
+        let (sql, values) = build(
+            Dialect::Pg,
+            CreateView::new("active_users", select).or_replace(),
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "CREATE OR REPLACE VIEW active_users AS SELECT users.* FROM users WHERE active = TRUE"
+        );
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn refresh() {
+        let refresh = RefreshMaterializedView::new("stats");
+        let (sql, _) = build(Dialect::Pg, refresh.clone().concurrently()).unwrap();
+        assert_eq!(sql, "REFRESH MATERIALIZED VIEW CONCURRENTLY stats");
+        let (sql, _) = build(Dialect::Pg, refresh.clone().with_no_data()).unwrap();
+        assert_eq!(sql, "REFRESH MATERIALIZED VIEW stats WITH NO DATA");
+
+        assert!(matches!(
+            build(Dialect::Pg, refresh.clone().concurrently().with_no_data()),
+            Err(Error::InvalidStatement { .. })
+        ));
+        assert!(build(Dialect::MySQL, refresh).is_err());
+    }
+}
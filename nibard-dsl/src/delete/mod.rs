@@ -1,3 +1,4 @@
+use crate::ast::{DeleteStmt, Stmt};
 use crate::query::{Expression, IntoValue};
 use crate::{Context, Error, Statement};
 use std::borrow::Cow;
@@ -24,11 +25,42 @@ impl<'a> Delete<'a> {
     }
 }
 
+impl<'a> Delete<'a> {
+    /// Writes the delete, or with a soft-delete policy for the table the
+    /// update marking the rows deleted, which already has a `WHERE` clause.
+    /// Returns whether it has one.
+    fn build_delete<C: Context>(&self, ctx: &mut C) -> Result<bool, Error> {
+        let column = ctx
+            .soft_delete()
+            .and_then(|policy| policy.column(&self.table))
+            .map(str::to_owned);
+        match column {
+            Some(column) => {
+                write!(
+                    ctx,
+                    "UPDATE {} SET {column} = CURRENT_TIMESTAMP WHERE {column} IS NULL",
+                    self.table,
+                    column = column
+                )?;
+                Ok(true)
+            }
+            None => {
+                write!(ctx, "DELETE FROM {}", self.table)?;
+                Ok(false)
+            }
+        }
+    }
+}
+
 impl<'a, C: Context> Statement<C> for Delete<'a> {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
-        write!(ctx, "DELETE FROM {}", self.table)?;
+        self.build_delete(ctx)?;
         Ok(())
     }
+
+    fn to_ast(&self) -> Option<Stmt> {
+        Some(DeleteStmt::new(self.table.as_ref()).into())
+    }
 }
 
 pub struct DeleteWhere<'a, E, C> {
@@ -42,9 +74,14 @@ where
     E: Expression<C>,
 {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
-        self.table.build(ctx)?;
-        write!(ctx, " WHERE ")?;
-        self.expr.build(ctx)?;
+        if self.table.build_delete(ctx)? {
+            ctx.write_str(" AND (")?;
+            self.expr.build(ctx)?;
+            ctx.write_str(")")?;
+        } else {
+            ctx.write_str(" WHERE ")?;
+            self.expr.build(ctx)?;
+        }
         Ok(())
     }
 }
@@ -52,3 +89,32 @@ where
 pub fn delete<'a>(table: impl Into<Cow<'a, str>>) -> Delete<'a> {
     Delete::new(table)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::SoftDelete;
+    use crate::query::Sql;
+    use crate::DefaultContext;
+    use nibard_shared::{Dialect, Value};
+
+    #[test]
+    fn soft_delete() {
+        let policy = SoftDelete::new().table("posts", "deleted_at");
+        let mut ctx = DefaultContext::new(Dialect::Sqlite).with_soft_delete(policy);
+        delete("posts")
+            .filter(Sql::new("id = 1 OR id = 2", Vec::<Value>::new()))
+            .build(&mut ctx)
+            .unwrap();
+        let (sql, values) = ctx.build().unwrap();
+        assert_eq!(
+            sql,
+            "UPDATE posts SET deleted_at = CURRENT_TIMESTAMP \
+             WHERE deleted_at IS NULL AND (id = 1 OR id = 2)"
+        );
+        assert!(values.is_empty());
+
+        let (sql, _) = crate::build(Dialect::Sqlite, delete("posts").filter(1)).unwrap();
+        assert_eq!(sql, "DELETE FROM posts WHERE ?");
+    }
+}
@@ -0,0 +1,368 @@
+use crate::{Context, Error, Statement};
+use nibard_shared::{Dialect, Value};
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DropTable<'a> {
+    pub name: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub if_exists: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cascade: bool,
+}
+
+impl<'a> DropTable<'a> {
+    pub fn new(name: impl Into<Cow<'a, str>>) -> DropTable<'a> {
+        DropTable {
+            name: name.into(),
+            if_exists: false,
+            cascade: false,
+        }
+    }
+
+    pub fn if_exists(mut self) -> Self {
+        self.if_exists = true;
+        self
+    }
+
+    pub fn cascade(mut self) -> Self {
+        self.cascade = true;
+        self
+    }
+}
+
+impl<'a, C: Context> Statement<C> for DropTable<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        if self.cascade && dialect == Dialect::Sqlite {
+            return Err(Error::Unsupported {
+                dialect,
+                feature: "DROP TABLE ... CASCADE".to_owned(),
+            });
+        }
+
+        ctx.write_str("DROP TABLE ")?;
+        if self.if_exists {
+            ctx.write_str("IF EXISTS ")?;
+        }
+        ctx.write_str(&self.name)?;
+        if self.cascade {
+            ctx.write_str(" CASCADE")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DropIndex<'a> {
+    pub name: Cow<'a, str>,
+    /// The table the index belongs to. Required by MySQL.
+    pub table: Option<Cow<'a, str>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub if_exists: bool,
+}
+
+impl<'a> DropIndex<'a> {
+    pub fn new(name: impl Into<Cow<'a, str>>) -> DropIndex<'a> {
+        DropIndex {
+            name: name.into(),
+            table: None,
+            if_exists: false,
+        }
+    }
+
+    pub fn on(mut self, table: impl Into<Cow<'a, str>>) -> Self {
+        self.table = Some(table.into());
+        self
+    }
+
+    pub fn if_exists(mut self) -> Self {
+        self.if_exists = true;
+        self
+    }
+}
+
+impl<'a, C: Context> Statement<C> for DropIndex<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        if dialect == Dialect::MySQL {
+            if self.if_exists {
+                return Err(Error::Unsupported {
+                    dialect,
+                    feature: "DROP INDEX IF EXISTS".to_owned(),
+                });
+            }
+            if self.table.is_none() {
+                return Err(Error::Unsupported {
+                    dialect,
+                    feature: "DROP INDEX without ON table".to_owned(),
+                });
+            }
+        }
+
+        ctx.write_str("DROP INDEX ")?;
+        if self.if_exists {
+            ctx.write_str("IF EXISTS ")?;
+        }
+        ctx.write_str(&self.name)?;
+        if let (Dialect::MySQL, Some(table)) = (dialect, &self.table) {
+            write!(ctx, " ON {}", table)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DropView<'a> {
+    pub name: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub materialized: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub if_exists: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cascade: bool,
+}
+
+impl<'a> DropView<'a> {
+    pub fn new(name: impl Into<Cow<'a, str>>) -> DropView<'a> {
+        DropView {
+            name: name.into(),
+            materialized: false,
+            if_exists: false,
+            cascade: false,
+        }
+    }
+
+    /// Drops a Pg materialized view.
+    pub fn materialized(mut self) -> Self {
+        self.materialized = true;
+        self
+    }
+
+    pub fn if_exists(mut self) -> Self {
+        self.if_exists = true;
+        self
+    }
+
+    pub fn cascade(mut self) -> Self {
+        self.cascade = true;
+        self
+    }
+}
+
+impl<'a, C: Context> Statement<C> for DropView<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        if self.materialized && dialect != Dialect::Pg {
+            return Err(Error::Unsupported {
+                dialect,
+                feature: "materialized views".to_owned(),
+            });
+        }
+        if self.cascade && dialect == Dialect::Sqlite {
+            return Err(Error::Unsupported {
+                dialect,
+                feature: "DROP VIEW ... CASCADE".to_owned(),
+            });
+        }
+
+        ctx.write_str("DROP ")?;
+        if self.materialized {
+            ctx.write_str("MATERIALIZED ")?;
+        }
+        ctx.write_str("VIEW ")?;
+        if self.if_exists {
+            ctx.write_str("IF EXISTS ")?;
+        }
+        ctx.write_str(&self.name)?;
+        if self.cascade {
+            ctx.write_str(" CASCADE")?;
+        }
+        Ok(())
+    }
+}
+
+/// Removes every row from a table. Sqlite has no TRUNCATE, so it is
+/// emitted as a `DELETE FROM`.
+///
+/// Sqlite and MySQL start the auto increment counter over as well, Pg only
+/// with `restart_identity`. On Sqlite that deletes the table's
+/// `sqlite_sequence` entry, and the `sqlite_sequence` table only exists
+/// once a table with AUTOINCREMENT has been created.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Truncate<'a> {
+    pub table: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cascade: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub restart_identity: bool,
+}
+
+impl<'a> Truncate<'a> {
+    pub fn new(table: impl Into<Cow<'a, str>>) -> Truncate<'a> {
+        Truncate {
+            table: table.into(),
+            cascade: false,
+            restart_identity: false,
+        }
+    }
+
+    pub fn cascade(mut self) -> Self {
+        self.cascade = true;
+        self
+    }
+
+    pub fn restart_identity(mut self) -> Self {
+        self.restart_identity = true;
+        self
+    }
+}
+
+impl<'a, C: Context> Statement<C> for Truncate<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        match dialect {
+            Dialect::Sqlite => {
+                if self.cascade {
+                    return Err(Error::Unsupported {
+                        dialect,
+                        feature: "TRUNCATE ... CASCADE".to_owned(),
+                    });
+                }
+                write!(ctx, "DELETE FROM {}", self.table)?;
+                ctx.write_str("; DELETE FROM sqlite_sequence WHERE name = ")?;
+                Value::Text(self.table.to_string()).write_sql(ctx, dialect)?;
+            }
+            Dialect::Pg => {
+                write!(ctx, "TRUNCATE TABLE {}", self.table)?;
+                if self.restart_identity {
+                    ctx.write_str(" RESTART IDENTITY")?;
+                }
+                if self.cascade {
+                    ctx.write_str(" CASCADE")?;
+                }
+            }
+            Dialect::MySQL => {
+                if self.cascade {
+                    return Err(Error::Unsupported {
+                        dialect,
+                        feature: "TRUNCATE ... CASCADE".to_owned(),
+                    });
+                }
+                write!(ctx, "TRUNCATE TABLE {}", self.table)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn drop_table<'a>(name: impl Into<Cow<'a, str>>) -> DropTable<'a> {
+    DropTable::new(name)
+}
+
+pub fn drop_index<'a>(name: impl Into<Cow<'a, str>>) -> DropIndex<'a> {
+    DropIndex::new(name)
+}
+
+pub fn drop_view<'a>(name: impl Into<Cow<'a, str>>) -> DropView<'a> {
+    DropView::new(name)
+}
+
+pub fn truncate<'a>(table: impl Into<Cow<'a, str>>) -> Truncate<'a> {
+    Truncate::new(table)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+
+    #[test]
+    fn drop_table() {
+        let stmt = DropTable::new("todos").if_exists().cascade();
+        let (sql, _) = build(Dialect::Pg, &stmt).unwrap();
+        assert_eq!(sql, "DROP TABLE IF EXISTS todos CASCADE");
+        let (sql, _) = build(Dialect::MySQL, &stmt).unwrap();
+        assert_eq!(sql, "DROP TABLE IF EXISTS todos CASCADE");
+        assert!(matches!(
+            build(Dialect::Sqlite, &stmt),
+            Err(Error::Unsupported { .. })
+        ));
+
+        let (sql, _) = build(Dialect::Sqlite, DropTable::new("todos")).unwrap();
+        assert_eq!(sql, "DROP TABLE todos");
+    }
+
+    #[test]
+    fn drop_index() {
+        let stmt = DropIndex::new("todos_label").on("todos");
+        let (sql, _) = build(Dialect::Pg, &stmt).unwrap();
+        assert_eq!(sql, "DROP INDEX todos_label");
+        let (sql, _) = build(Dialect::Sqlite, stmt.clone().if_exists()).unwrap();
+        assert_eq!(sql, "DROP INDEX IF EXISTS todos_label");
+        let (sql, _) = build(Dialect::MySQL, &stmt).unwrap();
+        assert_eq!(sql, "DROP INDEX todos_label ON todos");
+
+        assert!(build(Dialect::MySQL, DropIndex::new("todos_label")).is_err());
+        assert!(build(Dialect::MySQL, stmt.if_exists()).is_err());
+    }
+
+    #[test]
+    fn drop_view() {
+        let stmt = DropView::new("active_users").if_exists();
+        for dialect in [Dialect::Sqlite, Dialect::Pg, Dialect::MySQL] {
+            let (sql, _) = build(dialect, &stmt).unwrap();
+            assert_eq!(sql, "DROP VIEW IF EXISTS active_users");
+        }
+
+        let stmt = DropView::new("stats").materialized().cascade();
+        let (sql, _) = build(Dialect::Pg, &stmt).unwrap();
+        assert_eq!(sql, "DROP MATERIALIZED VIEW stats CASCADE");
+        assert!(build(Dialect::MySQL, &stmt).is_err());
+        assert!(build(Dialect::Sqlite, DropView::new("stats").cascade()).is_err());
+    }
+
+    #[test]
+    fn truncate() {
+        let stmt = Truncate::new("todos");
+        let (sql, _) = build(Dialect::Sqlite, &stmt).unwrap();
+        assert_eq!(
+            sql,
+            "DELETE FROM todos; DELETE FROM sqlite_sequence WHERE name = 'todos'"
+        );
+        let (sql, _) = build(Dialect::Pg, &stmt).unwrap();
+        assert_eq!(sql, "TRUNCATE TABLE todos");
+        let (sql, _) = build(Dialect::MySQL, &stmt).unwrap();
+        assert_eq!(sql, "TRUNCATE TABLE todos");
+
+        let stmt = stmt.restart_identity();
+        let (sql, _) = build(Dialect::Sqlite, &stmt).unwrap();
+        assert_eq!(
+            sql,
+            "DELETE FROM todos; DELETE FROM sqlite_sequence WHERE name = 'todos'"
+        );
+        let (sql, _) = build(Dialect::Pg, stmt.clone().cascade()).unwrap();
+        assert_eq!(sql, "TRUNCATE TABLE todos RESTART IDENTITY CASCADE");
+
+        assert!(build(Dialect::Sqlite, stmt.clone().cascade()).is_err());
+        assert!(build(Dialect::MySQL, stmt.cascade()).is_err());
+    }
+
+    #[cfg(feature = "schema-json")]
+    #[test]
+    fn from_json() {
+        let stmt: DropTable = serde_json::from_str(r#"{ "name": "todos" }"#).unwrap();
+        assert_eq!(stmt, DropTable::new("todos"));
+        let stmt: DropIndex = serde_json::from_str(r#"{ "name": "todos_label" }"#).unwrap();
+        assert_eq!(stmt, DropIndex::new("todos_label"));
+        let stmt: DropView =
+            serde_json::from_str(r#"{ "name": "stats", "cascade": true }"#).unwrap();
+        assert_eq!(stmt, DropView::new("stats").cascade());
+        let stmt: Truncate = serde_json::from_str(r#"{ "table": "todos" }"#).unwrap();
+        assert_eq!(stmt, Truncate::new("todos"));
+    }
+}
@@ -1,3 +1,4 @@
+use nibard_shared::Dialect;
 use std::fmt::Error as FormatError;
 use thiserror::Error as ThisError;
 
@@ -5,4 +6,30 @@ use thiserror::Error as ThisError;
 pub enum Error {
     #[error("format")]
     Format(#[from] FormatError),
+    #[error("{feature} is not supported by {dialect}")]
+    Unsupported { dialect: Dialect, feature: String },
+    #[error("no value bound for parameter {0}")]
+    MissingParam(String),
+    #[error("unknown parameter {0}")]
+    UnknownParam(String),
+    #[error("invalid statement for {dialect}: {description}")]
+    InvalidStatement {
+        dialect: Dialect,
+        description: String,
+    },
+    #[error("{count} parameters exceed the {dialect} limit of {max}")]
+    TooManyParameters {
+        dialect: Dialect,
+        count: usize,
+        max: usize,
+    },
+    #[error("empty IN list is not allowed by {dialect}: {description}")]
+    EmptyList {
+        dialect: Dialect,
+        description: String,
+    },
+    #[error("invalid schema: {0}")]
+    Schema(String),
+    #[error("destructive changes have to be allowed explicitly: {0}")]
+    Destructive(String),
 }
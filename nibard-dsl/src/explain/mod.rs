@@ -0,0 +1,93 @@
+use crate::ast::Stmt;
+use crate::{Context, Error, Statement};
+use nibard_shared::Dialect;
+
+/// Wraps a statement in the EXPLAIN syntax of the dialect, in the format the
+/// facade parses into a query plan: `EXPLAIN QUERY PLAN` for Sqlite,
+/// `EXPLAIN (FORMAT JSON)` for Pg and `EXPLAIN FORMAT=JSON` for MySQL.
+#[derive(Debug, Clone)]
+pub struct Explain<S> {
+    statement: S,
+    analyze: bool,
+}
+
+pub fn explain<S>(statement: S) -> Explain<S> {
+    Explain::new(statement)
+}
+
+impl<S> Explain<S> {
+    pub fn new(statement: S) -> Explain<S> {
+        Explain {
+            statement,
+            analyze: false,
+        }
+    }
+
+    /// Runs the statement and reports actual row counts alongside the
+    /// estimates. The statement is executed, so wrap writes in a
+    /// transaction. MySQL reports analyzed plans as a text tree rather than
+    /// JSON, and Sqlite has no equivalent.
+    pub fn analyze(mut self) -> Self {
+        self.analyze = true;
+        self
+    }
+
+    pub fn is_analyze(&self) -> bool {
+        self.analyze
+    }
+}
+
+impl<S, C: Context> Statement<C> for Explain<S>
+where
+    S: Statement<C>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        match (dialect, self.analyze) {
+            (Dialect::Sqlite, false) => ctx.write_str("EXPLAIN QUERY PLAN ")?,
+            (Dialect::Sqlite, true) => {
+                return Err(Error::Unsupported {
+                    dialect,
+                    feature: "EXPLAIN ANALYZE".to_owned(),
+                })
+            }
+            (Dialect::Pg, false) => ctx.write_str("EXPLAIN (FORMAT JSON) ")?,
+            (Dialect::Pg, true) => ctx.write_str("EXPLAIN (ANALYZE, FORMAT JSON) ")?,
+            (Dialect::MySQL, false) => ctx.write_str("EXPLAIN FORMAT=JSON ")?,
+            (Dialect::MySQL, true) => ctx.write_str("EXPLAIN ANALYZE ")?,
+        }
+        self.statement.build(ctx)
+    }
+
+    fn to_ast(&self) -> Option<Stmt> {
+        let statement = self.statement.to_ast()?;
+        Some(Stmt::Explain {
+            statement: Box::new(statement),
+            analyze: self.analyze,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+    use crate::prelude::*;
+
+    #[test]
+    fn explain_syntax() {
+        let select = "users".select("id").filter("email".eql("a@b.c"));
+
+        let (sql, _) = build(Dialect::Sqlite, explain(&select)).unwrap();
+        assert_eq!(
+            sql,
+            "EXPLAIN QUERY PLAN SELECT id FROM users WHERE email = ?"
+        );
+        let (sql, _) = build(Dialect::Pg, explain(&select).analyze()).unwrap();
+        assert_eq!(
+            sql,
+            "EXPLAIN (ANALYZE, FORMAT JSON) SELECT id FROM users WHERE email = $1"
+        );
+        assert!(build(Dialect::Sqlite, explain(&select).analyze()).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{query::Selection, Context, Error, Statement};
-use nibard_shared::{Value, ValueRef};
+use nibard_shared::{Dialect, Value, ValueRef};
 use std::borrow::Cow;
 use std::fmt::Write;
 use std::marker::PhantomData;
@@ -40,6 +40,13 @@ impl<'a> Insert<'a> {
 
 impl<'a, C: Context> Statement<C> for Insert<'a> {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        if self.keys.is_empty() {
+            return Err(Error::InvalidStatement {
+                dialect: *ctx.dialect(),
+                description: format!("insert into {} has no values", self.table),
+            });
+        }
+
         write!(
             ctx,
             "INSERT INTO {} ({}) VALUES (",
@@ -68,6 +75,14 @@ where
     S: Selection<C>,
 {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        if dialect == Dialect::MySQL {
+            return Err(Error::Unsupported {
+                dialect,
+                feature: "INSERT ... RETURNING".to_owned(),
+            });
+        }
+
         self.insert.build(ctx)?;
         write!(ctx, " RETURNING ")?;
         self.returning.build(ctx)?;
@@ -1,17 +1,23 @@
+pub mod ast;
 mod context;
 pub mod create;
 pub mod delete;
+pub mod drop;
 mod error;
+pub mod explain;
 pub mod insert;
 pub mod query;
 mod statement;
+mod template;
 pub mod update;
 mod util;
 
-pub use self::{context::*, error::Error, statement::*, util::*};
+pub use self::{context::*, error::Error, statement::*, template::*, util::*};
 
 pub mod prelude {
     pub use super::query::{
-        ColumnExt, FilterSelect, JoinSelect, LimitedSelect, SelectExt, TableExt, TargetExt,
+        ColumnExt, FilterSelect, JoinSelect, LimitedSelect, LockSelect, OrderSelect, SelectExt,
+        TableExt, TargetExt,
     };
+    pub use super::StatementExt;
 }
@@ -1,5 +1,9 @@
-use super::{Alias, BinaryExpression, BinaryOperator, Column, Expression, Selection};
+use super::{
+    Alias, BinaryExpression, BinaryOperator, Column, Expression, JsonContains, JsonGet, Selection,
+};
 use crate::{Context, Error};
+use nibard_shared::Value;
+use std::borrow::Cow;
 use std::marker::PhantomData;
 
 pub trait IntoValue<C: Context> {
@@ -72,6 +76,18 @@ pub trait ColumnExt<C: Context>: Column<C> + Sized {
         BinaryExpression::new(ColExpr::new(self), e.into_expression(), BinaryOperator::In)
     }
 
+    fn json_get<'a>(self, path: impl Into<Cow<'a, str>>) -> JsonGet<'a, Self, C> {
+        JsonGet::new(self, path)
+    }
+
+    fn json_get_text<'a>(self, path: impl Into<Cow<'a, str>>) -> JsonGet<'a, Self, C> {
+        JsonGet::text(self, path)
+    }
+
+    fn json_contains(self, value: impl Into<Value>) -> JsonContains<Self, C> {
+        JsonContains::new(self, value)
+    }
+
     fn column_alias<A: Alias<C>>(self, alias: A) -> ColAlias<Self, A, C> {
         ColAlias::new(self, alias)
     }
@@ -71,6 +71,7 @@ impl<'a, L, R, C: Context> BinaryExpression<L, R, C> {
 }
 
 #[derive(Clone, Debug, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryOperator {
     Eq,
     Lt,
@@ -85,7 +86,7 @@ pub enum BinaryOperator {
 }
 
 impl BinaryOperator {
-    fn build<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
+    pub(crate) fn build<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
         match self {
             Self::Eq => ctx.write_str("="),
             Self::Lt => ctx.write_str("<"),
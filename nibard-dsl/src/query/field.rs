@@ -0,0 +1,45 @@
+use super::{Column, Selection};
+use crate::{Context, Error, Table};
+
+/// A column of a statically known table, written as `table.name`. This is
+/// what `#[derive(Table)]` generates for each struct field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Field<T> {
+    table: T,
+    name: &'static str,
+}
+
+impl<T> Field<T> {
+    pub const fn new(table: T, name: &'static str) -> Field<T> {
+        Field { table, name }
+    }
+
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T, C: Context> Selection<C> for Field<T>
+where
+    T: Table<C>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        <T as Table<C>>::build(&self.table, ctx)?;
+        ctx.write_char('.')?;
+        ctx.write_str(self.name)?;
+        Ok(())
+    }
+}
+
+impl<T, C: Context> Column<C> for Field<T>
+where
+    T: Table<C>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        <Self as Selection<C>>::build(self, ctx)
+    }
+}
@@ -20,12 +20,25 @@ macro_rules! impl_into_value {
     };
 }
 
+impl_into_value!(i8);
+impl_into_value!(u8);
 impl_into_value!(i16);
-// impl_into_value!(u16);
+impl_into_value!(u16);
 impl_into_value!(i32);
+impl_into_value!(u32);
 impl_into_value!(i64);
+impl_into_value!(f32);
+impl_into_value!(f64);
+impl_into_value!(bool);
 impl_into_value!(String);
+impl_into_value!(Vec<u8>);
 impl_into_value!(Value);
+#[cfg(feature = "time")]
+impl_into_value!(chrono::NaiveDate);
+#[cfg(feature = "time")]
+impl_into_value!(chrono::NaiveDateTime);
+#[cfg(feature = "time")]
+impl_into_value!(chrono::DateTime<chrono::Utc>);
 
 impl<'a, C: Context> IntoValue<C> for &'a str {
     type Expression = Value;
@@ -40,3 +53,10 @@ impl<'a, C: Context> IntoValue<C> for &'a Value {
         self.clone().into()
     }
 }
+
+impl<C: Context, V: Into<Value>> IntoValue<C> for Option<V> {
+    type Expression = Value;
+    fn into_expression(self) -> Self::Expression {
+        self.map_or(Value::Null, Into::into)
+    }
+}
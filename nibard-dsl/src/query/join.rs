@@ -1,5 +1,6 @@
-use super::Expression;
-use crate::{Context, Error, Table};
+use super::{Expression, Target};
+use crate::{Context, Error};
+use nibard_shared::Dialect;
 
 pub trait Joinable<C: Context> {
     fn build(&self, ctx: &mut C) -> Result<(), Error>;
@@ -18,12 +19,12 @@ pub struct Join<T> {
 
 impl<T: Send + Sync, C: Context> Joinable<C> for Join<T>
 where
-    T: Table<C>,
+    T: Target<C>,
 {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
         self.kind.build(ctx)?;
         ctx.write_str(" ")?;
-        <T as Table<C>>::build(&self.table, ctx)?;
+        <T as Target<C>>::build(&self.table, ctx)?;
         Ok(())
     }
 }
@@ -42,6 +43,20 @@ impl<T> Join<T> {
             table,
         }
     }
+
+    pub fn right(table: T) -> Join<T> {
+        Join {
+            kind: JoinType::Right,
+            table,
+        }
+    }
+
+    pub fn full(table: T) -> Join<T> {
+        Join {
+            kind: JoinType::Full,
+            table,
+        }
+    }
     pub fn on<E>(self, e: E) -> JoinOn<T, E> {
         JoinOn { join: self, on: e }
     }
@@ -55,7 +70,7 @@ pub struct JoinOn<T, E> {
 impl<T, E, C: Context> Joinable<C> for JoinOn<T, E>
 where
     E: Expression<C> + Send + Sync,
-    T: Table<C> + Send + Sync,
+    T: Target<C> + Send + Sync,
 {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
         self.join.build(ctx)?;
@@ -65,24 +80,39 @@ where
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JoinType {
     Inner,
     Left,
     Right,
-    Outer,
+    Full,
 }
 
 impl JoinType {
-    fn build<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
-        match self {
-            JoinType::Inner => {
-                //
-                ctx.write_str("INNER JOIN")
+    pub(crate) fn build<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        match (self, dialect) {
+            (JoinType::Right, Dialect::Sqlite)
+            | (JoinType::Full, Dialect::Sqlite | Dialect::MySQL) => {
+                return Err(Error::Unsupported {
+                    dialect,
+                    feature: format!("{} JOIN", self.keyword()),
+                })
             }
-            JoinType::Left => ctx.write_str("LEFT JOIN"),
-            JoinType::Right => ctx.write_str("RIGHT JOIN"),
-            JoinType::Outer => ctx.write_str("OUTER JOIN"),
-        }?;
+            _ => {}
+        }
+
+        write!(ctx, "{} JOIN", self.keyword())?;
         Ok(())
     }
+
+    fn keyword(&self) -> &'static str {
+        match self {
+            JoinType::Inner => "INNER",
+            JoinType::Left => "LEFT",
+            JoinType::Right => "RIGHT",
+            JoinType::Full => "FULL OUTER",
+        }
+    }
 }
@@ -0,0 +1,232 @@
+use super::{Column, Expression, Selection};
+use crate::{Context, Error};
+use nibard_shared::{Dialect, Value};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+/// Extracts the value at a dotted path (`"a.b"`, `"items.0"`) from a json
+/// column. Numeric segments index into arrays.
+#[derive(Debug)]
+pub struct JsonGet<'a, Col, C> {
+    col: Col,
+    path: Cow<'a, str>,
+    text: bool,
+    _c: PhantomData<C>,
+}
+
+impl<'a, Col: Clone, C> Clone for JsonGet<'a, Col, C> {
+    fn clone(&self) -> Self {
+        JsonGet {
+            col: self.col.clone(),
+            path: self.path.clone(),
+            text: self.text,
+            _c: PhantomData,
+        }
+    }
+}
+
+impl<'a, Col, C> JsonGet<'a, Col, C> {
+    /// The json value at `path`: `->` on Pg, `json_extract` on Sqlite and
+    /// `JSON_EXTRACT` on MySQL.
+    pub fn new(col: Col, path: impl Into<Cow<'a, str>>) -> JsonGet<'a, Col, C> {
+        JsonGet {
+            col,
+            path: path.into(),
+            text: false,
+            _c: PhantomData,
+        }
+    }
+
+    /// The value at `path` as unquoted text: `->>` on Pg and
+    /// `JSON_UNQUOTE(JSON_EXTRACT(..))` on MySQL.
+    pub fn text(col: Col, path: impl Into<Cow<'a, str>>) -> JsonGet<'a, Col, C> {
+        JsonGet {
+            text: true,
+            ..JsonGet::new(col, path)
+        }
+    }
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('.').filter(|segment| !segment.is_empty())
+}
+
+fn is_index(segment: &str) -> bool {
+    segment.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// The `$.a[0].b` path syntax of Sqlite and MySQL.
+fn json_path(path: &str) -> String {
+    let mut out = String::from("$");
+    for segment in segments(path) {
+        if is_index(segment) {
+            out.push('[');
+            out.push_str(segment);
+            out.push(']');
+        } else {
+            out.push_str(".\"");
+            out.push_str(&segment.replace('"', "\\\""));
+            out.push('"');
+        }
+    }
+    out
+}
+
+impl<'a, Col, C: Context> Selection<C> for JsonGet<'a, Col, C>
+where
+    Col: Column<C>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        match dialect {
+            Dialect::Pg => {
+                ctx.write_char('(')?;
+                Column::build(&self.col, ctx)?;
+                let count = segments(&self.path).count();
+                for (idx, segment) in segments(&self.path).enumerate() {
+                    let op = if self.text && idx + 1 == count {
+                        " ->> "
+                    } else {
+                        " -> "
+                    };
+                    ctx.write_str(op)?;
+                    if is_index(segment) {
+                        ctx.write_str(segment)?;
+                    } else {
+                        Value::Text(segment.to_owned()).write_sql(ctx, dialect)?;
+                    }
+                }
+                ctx.write_char(')')?;
+            }
+            Dialect::Sqlite | Dialect::MySQL => {
+                let (outer, extract) = match (dialect, self.text) {
+                    (Dialect::Sqlite, false) => ("json_quote(", "json_extract("),
+                    (Dialect::Sqlite, true) => ("", "json_extract("),
+                    (_, false) => ("", "JSON_EXTRACT("),
+                    (_, true) => ("JSON_UNQUOTE(", "JSON_EXTRACT("),
+                };
+                ctx.write_str(outer)?;
+                ctx.write_str(extract)?;
+                Column::build(&self.col, ctx)?;
+                ctx.write_str(", ")?;
+                Value::Text(json_path(&self.path)).write_sql(ctx, dialect)?;
+                ctx.write_char(')')?;
+                if !outer.is_empty() {
+                    ctx.write_char(')')?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, Col, C: Context> Column<C> for JsonGet<'a, Col, C>
+where
+    Col: Column<C>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        <Self as Selection<C>>::build(self, ctx)
+    }
+}
+
+/// Whether a json column contains the given json document: `@>` on Pg and
+/// `JSON_CONTAINS` on MySQL. Sqlite has no containment operator.
+#[derive(Debug)]
+pub struct JsonContains<Col, C> {
+    col: Col,
+    value: Value,
+    _c: PhantomData<C>,
+}
+
+impl<Col: Clone, C> Clone for JsonContains<Col, C> {
+    fn clone(&self) -> Self {
+        JsonContains {
+            col: self.col.clone(),
+            value: self.value.clone(),
+            _c: PhantomData,
+        }
+    }
+}
+
+impl<Col, C> JsonContains<Col, C> {
+    pub fn new(col: Col, value: impl Into<Value>) -> JsonContains<Col, C> {
+        JsonContains {
+            col,
+            value: value.into(),
+            _c: PhantomData,
+        }
+    }
+}
+
+impl<Col, C: Context> Expression<C> for JsonContains<Col, C>
+where
+    Col: Column<C>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        match dialect {
+            Dialect::Pg => {
+                Column::build(&self.col, ctx)?;
+                ctx.write_str(" @> ")?;
+                ctx.push(self.value.clone())?;
+                ctx.write_str("::jsonb")?;
+            }
+            Dialect::MySQL => {
+                ctx.write_str("JSON_CONTAINS(")?;
+                Column::build(&self.col, ctx)?;
+                ctx.write_str(", ")?;
+                ctx.push(self.value.clone())?;
+                ctx.write_char(')')?;
+            }
+            Dialect::Sqlite => {
+                return Err(Error::Unsupported {
+                    dialect,
+                    feature: "json containment".to_owned(),
+                })
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::build;
+    use crate::prelude::*;
+    use nibard_shared::Dialect;
+
+    #[test]
+    fn json_paths() {
+        let select = "events"
+            .select("data".json_get("user.tags").column_alias("tags"))
+            .filter("data".json_get_text("user.names.0").eql("bob"));
+
+        let (sql, _) = build(Dialect::Pg, &select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT (data -> 'user' -> 'tags') AS tags FROM events \
+             WHERE (data -> 'user' -> 'names' ->> 0) = $1"
+        );
+
+        let (sql, _) = build(Dialect::Sqlite, &select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT json_quote(json_extract(data, '$.\"user\".\"tags\"')) AS tags FROM events \
+             WHERE json_extract(data, '$.\"user\".\"names\"[0]') = ?"
+        );
+
+        let (sql, _) = build(Dialect::MySQL, &select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT JSON_EXTRACT(data, '$.\"user\".\"tags\"') AS tags FROM events \
+             WHERE JSON_UNQUOTE(JSON_EXTRACT(data, '$.\"user\".\"names\"[0]')) = ?"
+        );
+
+        let select = "events"
+            .select("id")
+            .filter("data".json_contains(r#"{"kind":"login"}"#));
+        let (sql, _) = build(Dialect::Pg, &select).unwrap();
+        assert_eq!(sql, "SELECT id FROM events WHERE data @> $1::jsonb");
+        assert!(build(Dialect::Sqlite, &select).is_err());
+    }
+}
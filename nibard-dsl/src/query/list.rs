@@ -0,0 +1,107 @@
+use super::{Expression, IntoValue, ValueFor};
+use crate::{Context, Error};
+use nibard_shared::{Dialect, Value};
+
+/// A parenthesized list of values for use with `has`, written as
+/// `(?, ?, ?)`. Only Sqlite accepts an empty list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct List(Vec<Value>);
+
+impl List {
+    pub fn new<V, I>(values: I) -> List
+    where
+        V: Into<Value>,
+        I: IntoIterator<Item = V>,
+    {
+        List(values.into_iter().map(Into::into).collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Shorthand for `List::new`.
+pub fn list<V, I>(values: I) -> List
+where
+    V: Into<Value>,
+    I: IntoIterator<Item = V>,
+{
+    List::new(values)
+}
+
+impl<C: Context> Expression<C> for List {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        if self.0.is_empty() && dialect != Dialect::Sqlite {
+            return Err(Error::EmptyList {
+                dialect,
+                description: "IN () needs at least one value".to_owned(),
+            });
+        }
+
+        ctx.write_char('(')?;
+        for (idx, value) in self.0.iter().enumerate() {
+            if idx > 0 {
+                ctx.write_str(", ")?;
+            }
+            ctx.push(value.clone())?;
+        }
+        ctx.write_char(')')?;
+        Ok(())
+    }
+}
+
+impl<C: Context> IntoValue<C> for List {
+    type Expression = List;
+    fn into_expression(self) -> Self::Expression {
+        self
+    }
+}
+
+impl<T, C: Context> ValueFor<T, C> for List {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+    use crate::prelude::*;
+    use crate::query::Join;
+
+    #[test]
+    fn empty_list() {
+        let select = "users".select("id").filter("id".has(list([1, 2])));
+        let (sql, _) = build(Dialect::Pg, select).unwrap();
+        assert_eq!(sql, "SELECT id FROM users WHERE id IN ($1, $2)");
+
+        let select = "users"
+            .select("id")
+            .filter("id".has(list(Vec::<i32>::new())));
+        assert!(build(Dialect::Sqlite, &select).is_ok());
+        assert!(matches!(
+            build(Dialect::MySQL, &select),
+            Err(Error::EmptyList {
+                dialect: Dialect::MySQL,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn unsupported_join() {
+        let select = "users"
+            .select("users.id")
+            .join(Join::full("posts").on("posts.owner".eql("users.id".expr())));
+        assert!(matches!(
+            build(Dialect::MySQL, select),
+            Err(Error::Unsupported {
+                dialect: Dialect::MySQL,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use super::Select;
+use crate::{Context, Error, Statement};
+use nibard_shared::Dialect;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+pub trait LockSelect<C: Context>: Select<C> + Sized {
+    fn for_update<'a>(self) -> LockedSel<'a, Self, C> {
+        LockedSel::new(self, LockMode::Update)
+    }
+
+    fn for_share<'a>(self) -> LockedSel<'a, Self, C> {
+        LockedSel::new(self, LockMode::Share)
+    }
+}
+
+impl<S, C: Context> LockSelect<C> for S where S: Select<C> {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LockMode {
+    Update,
+    Share,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LockWait {
+    Wait,
+    NoWait,
+    SkipLocked,
+}
+
+/// A select with a row locking clause. Sqlite has no row locks, since a
+/// write transaction locks the whole database, so the clause is left out
+/// there unless the lock is `strict`, in which case building fails.
+#[derive(Clone, Debug)]
+pub struct LockedSel<'a, S, C> {
+    select: S,
+    mode: LockMode,
+    of: Vec<Cow<'a, str>>,
+    wait: LockWait,
+    strict: bool,
+    _c: PhantomData<C>,
+}
+
+impl<'a, S, C: Context> LockedSel<'a, S, C>
+where
+    S: Select<C>,
+{
+    pub fn new(select: S, mode: LockMode) -> LockedSel<'a, S, C> {
+        LockedSel {
+            select,
+            mode,
+            of: Vec::default(),
+            wait: LockWait::Wait,
+            strict: false,
+            _c: PhantomData,
+        }
+    }
+
+    /// Only locks rows of `table` in a joined select.
+    pub fn of(mut self, table: impl Into<Cow<'a, str>>) -> Self {
+        self.of.push(table.into());
+        self
+    }
+
+    pub fn nowait(mut self) -> Self {
+        self.wait = LockWait::NoWait;
+        self
+    }
+
+    pub fn skip_locked(mut self) -> Self {
+        self.wait = LockWait::SkipLocked;
+        self
+    }
+
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+}
+
+impl<'a, S, C: Context> Statement<C> for LockedSel<'a, S, C>
+where
+    S: Select<C>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        if dialect == Dialect::Sqlite && self.strict {
+            return Err(Error::Unsupported {
+                dialect,
+                feature: "row locking".to_owned(),
+            });
+        }
+
+        self.select.build(ctx)?;
+
+        match (dialect, self.mode) {
+            (Dialect::Sqlite, _) => return Ok(()),
+            (_, LockMode::Update) => ctx.write_str(" FOR UPDATE")?,
+            (Dialect::MySQL, LockMode::Share)
+                if self.of.is_empty() && self.wait == LockWait::Wait =>
+            {
+                ctx.write_str(" LOCK IN SHARE MODE")?;
+                return Ok(());
+            }
+            (_, LockMode::Share) => ctx.write_str(" FOR SHARE")?,
+        }
+
+        if !self.of.is_empty() {
+            write!(ctx, " OF {}", self.of.join(", "))?;
+        }
+
+        match self.wait {
+            LockWait::Wait => {}
+            LockWait::NoWait => ctx.write_str(" NOWAIT")?,
+            LockWait::SkipLocked => ctx.write_str(" SKIP LOCKED")?,
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+    use crate::prelude::*;
+
+    #[test]
+    fn work_queue() {
+        let select = "jobs"
+            .select("id")
+            .filter("state".eql("queued"))
+            .limit(1)
+            .for_update()
+            .of("jobs")
+            .skip_locked();
+        let (sql, _) = build(Dialect::Pg, &select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT id FROM jobs WHERE state = $1 LIMIT 1 FOR UPDATE OF jobs SKIP LOCKED"
+        );
+
+        let (sql, _) = build(Dialect::Sqlite, &select).unwrap();
+        assert_eq!(sql, "SELECT id FROM jobs WHERE state = ? LIMIT 1");
+        assert!(build(Dialect::Sqlite, select.strict()).is_err());
+
+        let (sql, _) = build(Dialect::MySQL, "jobs".select("id").for_share()).unwrap();
+        assert_eq!(sql, "SELECT id FROM jobs LOCK IN SHARE MODE");
+    }
+}
@@ -1,13 +1,25 @@
 mod column_ext;
 mod condition;
+mod field;
 mod func;
 mod impls;
 mod join;
+mod json;
+mod list;
+mod lock;
+mod param;
+mod raw;
+mod search;
 mod select;
+mod subquery;
 mod table_ext;
+mod typed;
 mod types;
 
-pub use self::{column_ext::*, condition::*, func::*, join::*, select::*, table_ext::*, types::*};
+pub use self::{
+    column_ext::*, condition::*, field::*, func::*, join::*, json::*, list::*, lock::*, param::*,
+    raw::*, search::*, select::*, subquery::*, table_ext::*, typed::*, types::*,
+};
 
 #[cfg(test)]
 mod test {
@@ -36,4 +48,36 @@ mod test {
 
         println!("Out: {:?}", out.build());
     }
+
+    fn soft_delete<S: crate::Statement<DefaultContext>>(
+        stmt: S,
+    ) -> (String, Vec<nibard_shared::Value>) {
+        let policy = crate::ast::SoftDelete::new().table("posts", "deleted_at");
+        let mut ctx = DefaultContext::new(Dialect::Pg).with_soft_delete(policy);
+        stmt.build(&mut ctx).unwrap();
+        ctx.build().unwrap()
+    }
+
+    #[test]
+    fn soft_deleted_rows() {
+        let (sql, values) = soft_delete("posts".select("id").filter("id".eql(1)).limit(1));
+        assert_eq!(
+            sql,
+            "SELECT id FROM (SELECT * FROM posts WHERE deleted_at IS NULL) AS posts \
+             WHERE id = $1 LIMIT 1"
+        );
+        assert_eq!(values.len(), 1);
+
+        let (sql, _) = soft_delete("users".select("name").join(
+            Join::inner("posts".table_alias("p")).on("p".col("user_id").eql("users.id".expr())),
+        ));
+        assert_eq!(
+            sql,
+            "SELECT name FROM users INNER JOIN \
+             (SELECT * FROM posts WHERE deleted_at IS NULL) AS p ON p.user_id = users.id"
+        );
+
+        let (sql, _) = soft_delete("users".select("id"));
+        assert_eq!(sql, "SELECT id FROM users");
+    }
 }
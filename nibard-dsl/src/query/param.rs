@@ -0,0 +1,36 @@
+use super::{Expression, IntoValue, ValueFor};
+use crate::{Context, Error};
+use std::borrow::Cow;
+
+/// A placeholder bound by name after the statement is built. See
+/// `prepare` and `Template`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param<'a> {
+    name: Cow<'a, str>,
+}
+
+impl<'a> Param<'a> {
+    pub fn named(name: impl Into<Cow<'a, str>>) -> Param<'a> {
+        Param { name: name.into() }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<'a, C: Context> Expression<C> for Param<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        ctx.push_param(&self.name)?;
+        Ok(())
+    }
+}
+
+impl<'a, C: Context> IntoValue<C> for Param<'a> {
+    type Expression = Param<'a>;
+    fn into_expression(self) -> Self::Expression {
+        self
+    }
+}
+
+impl<'a, T, C: Context> ValueFor<T, C> for Param<'a> {}
@@ -0,0 +1,140 @@
+use super::{Column, Expression, IntoValue, Selection, Target, ValueFor};
+use crate::{Context, Error};
+use nibard_shared::Value;
+use std::borrow::Cow;
+
+/// A raw SQL fragment with `?` placeholders, for constructs the builder does
+/// not cover. Each `?` is replaced with the dialect's placeholder, continuing
+/// the numbering of the surrounding statement. `??` writes a literal `?`,
+/// and question marks inside single-quoted strings are left alone.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sql<'a> {
+    sql: Cow<'a, str>,
+    values: Vec<Value>,
+}
+
+impl<'a> Sql<'a> {
+    pub fn new<V, I>(sql: impl Into<Cow<'a, str>>, values: I) -> Sql<'a>
+    where
+        V: Into<Value>,
+        I: IntoIterator<Item = V>,
+    {
+        Sql {
+            sql: sql.into(),
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    fn write<C: Context>(&self, ctx: &mut C) -> Result<(), Error> {
+        let mut values = self.values.iter();
+        let mut placeholders = 0;
+        let mut quoted = false;
+        let mut chars = self.sql.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' => {
+                    quoted = !quoted;
+                    ctx.write_char(c)?;
+                }
+                '?' if !quoted && chars.peek() == Some(&'?') => {
+                    chars.next();
+                    ctx.write_char('?')?;
+                }
+                '?' if !quoted => {
+                    placeholders += 1;
+                    if let Some(value) = values.next() {
+                        ctx.push(value.clone())?;
+                    }
+                }
+                c => ctx.write_char(c)?,
+            }
+        }
+
+        if placeholders != self.values.len() {
+            return Err(Error::InvalidStatement {
+                dialect: *ctx.dialect(),
+                description: format!(
+                    "sql fragment has {} placeholders but {} values",
+                    placeholders,
+                    self.values.len()
+                ),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Shorthand for `Sql::new`.
+pub fn sql<'a, V, I>(sql: impl Into<Cow<'a, str>>, values: I) -> Sql<'a>
+where
+    V: Into<Value>,
+    I: IntoIterator<Item = V>,
+{
+    Sql::new(sql, values)
+}
+
+impl<'a, C: Context> Expression<C> for Sql<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        self.write(ctx)
+    }
+}
+
+impl<'a, C: Context> Selection<C> for Sql<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        self.write(ctx)
+    }
+}
+
+impl<'a, C: Context> Column<C> for Sql<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        self.write(ctx)
+    }
+}
+
+impl<'a, C: Context> Target<C> for Sql<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        self.write(ctx)
+    }
+}
+
+impl<'a, C: Context> IntoValue<C> for Sql<'a> {
+    type Expression = Sql<'a>;
+    fn into_expression(self) -> Self::Expression {
+        self
+    }
+}
+
+impl<'a, T, C: Context> ValueFor<T, C> for Sql<'a> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+    use crate::prelude::*;
+    use nibard_shared::Dialect;
+
+    #[test]
+    fn continues_placeholders() {
+        let select = "docs".select("id").filter("kind".eql("note").and(sql(
+            "json_extract(data, ?) = ? AND title != '?'",
+            [Value::from("$.owner"), Value::from(3)],
+        )));
+        let (sql, values) = build(Dialect::Pg, select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT id FROM docs WHERE kind = $1 AND json_extract(data, $2) = $3 AND title != '?'"
+        );
+        assert_eq!(
+            values,
+            vec![Value::from("note"), Value::from("$.owner"), Value::from(3)]
+        );
+
+        let select = "docs"
+            .select("id")
+            .filter(super::sql("data ?? ?", ["a", "b"]));
+        assert!(build(Dialect::Pg, select).is_err());
+    }
+}
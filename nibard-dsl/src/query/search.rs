@@ -0,0 +1,252 @@
+use super::{Column, Expression, Selection};
+use crate::create::CreateFullText;
+use crate::{Context, Error};
+use nibard_shared::{Dialect, Value};
+use std::borrow::Cow;
+
+/// A full-text index over text columns of a table.
+///
+/// Sqlite searches an external-content FTS5 table named `{table}_fts`, kept
+/// in sync by triggers. Pg uses a GIN index over `to_tsvector` and MySQL a
+/// `FULLTEXT` index. `create()` returns the matching DDL, which has to exist
+/// before `matches` or `rank` are used.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FullText<'a> {
+    pub table: Cow<'a, str>,
+    pub columns: Vec<Cow<'a, str>>,
+    pub name: Option<Cow<'a, str>>,
+    pub key: Cow<'a, str>,
+    pub language: Cow<'a, str>,
+}
+
+impl<'a> FullText<'a> {
+    pub fn new<I, S>(table: impl Into<Cow<'a, str>>, columns: I) -> FullText<'a>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Cow<'a, str>>,
+    {
+        FullText {
+            table: table.into(),
+            columns: columns.into_iter().map(Into::into).collect(),
+            name: None,
+            key: Cow::Borrowed("id"),
+            language: Cow::Borrowed("english"),
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<Cow<'a, str>>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// The integer primary key of the table, used as the FTS5 rowid.
+    pub fn key(mut self, key: impl Into<Cow<'a, str>>) -> Self {
+        self.key = key.into();
+        self
+    }
+
+    /// The Pg text search configuration.
+    pub fn language(mut self, language: impl Into<Cow<'a, str>>) -> Self {
+        self.language = language.into();
+        self
+    }
+
+    pub fn index_name(&self) -> Cow<'_, str> {
+        match &self.name {
+            Some(name) => Cow::Borrowed(name),
+            None => Cow::Owned(format!("{}_fts", self.table)),
+        }
+    }
+
+    /// Rows where any of the columns match the plain text `query`.
+    pub fn matches(&self, query: impl Into<String>) -> Matches<'a> {
+        Matches {
+            index: self.clone(),
+            query: query.into(),
+        }
+    }
+
+    /// The relevance of a row for `query`, higher is better. Select it with
+    /// an alias and order by that alias.
+    pub fn rank(&self, query: impl Into<String>) -> Rank<'a> {
+        Rank {
+            index: self.clone(),
+            query: query.into(),
+        }
+    }
+
+    pub fn create(&self) -> CreateFullText<'a> {
+        CreateFullText::new(self.clone())
+    }
+
+    /// Writes the Pg `to_tsvector` document. The expression has to be
+    /// identical in the index and in queries for the index to be used.
+    pub(crate) fn write_tsvector<W: std::fmt::Write>(
+        &self,
+        out: &mut W,
+        qualify: bool,
+    ) -> Result<(), Error> {
+        out.write_str("to_tsvector(")?;
+        Value::Text(self.language.to_string()).write_sql(out, Dialect::Pg)?;
+        out.write_str(", ")?;
+        for (idx, column) in self.columns.iter().enumerate() {
+            if idx > 0 {
+                out.write_str(" || ' ' || ")?;
+            }
+            if qualify {
+                write!(out, "coalesce({}.{}, '')", self.table, column)?;
+            } else {
+                write!(out, "coalesce({}, '')", column)?;
+            }
+        }
+        out.write_char(')')?;
+        Ok(())
+    }
+
+    fn write_mysql_match<C: Context>(&self, ctx: &mut C, query: &str) -> Result<(), Error> {
+        ctx.write_str("MATCH (")?;
+        for (idx, column) in self.columns.iter().enumerate() {
+            if idx > 0 {
+                ctx.write_str(", ")?;
+            }
+            write!(ctx, "{}.{}", self.table, column)?;
+        }
+        ctx.write_str(") AGAINST (")?;
+        ctx.push(Value::Text(query.to_owned()))?;
+        ctx.write_str(" IN NATURAL LANGUAGE MODE)")?;
+        Ok(())
+    }
+}
+
+/// Turns plain text into an FTS5 query matching all of its terms, so user
+/// input never hits the FTS5 query syntax.
+fn fts5_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matches<'a> {
+    index: FullText<'a>,
+    query: String,
+}
+
+impl<'a, C: Context> Expression<C> for Matches<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let index = &self.index;
+        match *ctx.dialect() {
+            Dialect::Sqlite => {
+                let query = fts5_query(&self.query);
+                if query.is_empty() {
+                    ctx.write_str("0")?;
+                    return Ok(());
+                }
+                let name = index.index_name();
+                write!(
+                    ctx,
+                    "{}.{} IN (SELECT rowid FROM {} WHERE {} MATCH ",
+                    index.table, index.key, name, name
+                )?;
+                ctx.push(Value::Text(query))?;
+                ctx.write_char(')')?;
+            }
+            Dialect::Pg => {
+                index.write_tsvector(ctx, true)?;
+                ctx.write_str(" @@ plainto_tsquery(")?;
+                Value::Text(index.language.to_string()).write_sql(ctx, Dialect::Pg)?;
+                ctx.write_str(", ")?;
+                ctx.push(Value::Text(self.query.clone()))?;
+                ctx.write_char(')')?;
+            }
+            Dialect::MySQL => index.write_mysql_match(ctx, &self.query)?,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rank<'a> {
+    index: FullText<'a>,
+    query: String,
+}
+
+impl<'a, C: Context> Selection<C> for Rank<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let index = &self.index;
+        match *ctx.dialect() {
+            Dialect::Sqlite => {
+                // bm25 is only available in a query over the FTS5 table and
+                // is lower for better matches.
+                let name = index.index_name();
+                write!(
+                    ctx,
+                    "(SELECT -bm25({}) FROM {} WHERE {} MATCH ",
+                    name, name, name
+                )?;
+                ctx.push(Value::Text(fts5_query(&self.query)))?;
+                write!(ctx, " AND {}.rowid = {}.{})", name, index.table, index.key)?;
+            }
+            Dialect::Pg => {
+                ctx.write_str("ts_rank(")?;
+                index.write_tsvector(ctx, true)?;
+                ctx.write_str(", plainto_tsquery(")?;
+                Value::Text(index.language.to_string()).write_sql(ctx, Dialect::Pg)?;
+                ctx.write_str(", ")?;
+                ctx.push(Value::Text(self.query.clone()))?;
+                ctx.write_str("))")?;
+            }
+            Dialect::MySQL => index.write_mysql_match(ctx, &self.query)?,
+        }
+        Ok(())
+    }
+}
+
+impl<'a, C: Context> Column<C> for Rank<'a> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        <Self as Selection<C>>::build(self, ctx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+    use crate::prelude::*;
+
+    #[test]
+    fn search() {
+        let index = FullText::new("docs", ["title", "body"]);
+        let select = "docs"
+            .select(index.rank("rust orm").column_alias("score"))
+            .filter(index.matches("rust orm"))
+            .order_by_desc("score");
+
+        let (sql, values) = build(Dialect::Sqlite, &select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT (SELECT -bm25(docs_fts) FROM docs_fts WHERE docs_fts MATCH ? \
+             AND docs_fts.rowid = docs.id) AS score FROM docs \
+             WHERE docs.id IN (SELECT rowid FROM docs_fts WHERE docs_fts MATCH ?) \
+             ORDER BY score DESC"
+        );
+        assert_eq!(values[0], Value::from("\"rust\" \"orm\""));
+
+        let select = "docs".select("docs.id").filter(index.matches("rust"));
+        let (sql, _) = build(Dialect::Pg, &select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT docs.id FROM docs WHERE to_tsvector('english', coalesce(docs.title, '') || ' ' || coalesce(docs.body, '')) \
+             @@ plainto_tsquery('english', $1)"
+        );
+
+        let (sql, _) = build(Dialect::MySQL, &select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT docs.id FROM docs WHERE MATCH (docs.title, docs.body) AGAINST (? IN NATURAL LANGUAGE MODE)"
+        );
+    }
+}
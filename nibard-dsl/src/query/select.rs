@@ -2,6 +2,7 @@ use super::{
     BinaryExpression, BinaryOperator, Column, Either, Expression, IntoValue, Joinable, Selection,
     Target,
 };
+use crate::ast::Stmt;
 use crate::{Context, Error, Statement};
 use std::marker::PhantomData;
 
@@ -37,6 +38,8 @@ impl<'a, C: Context> JoinSelect<C> for Box<dyn Select<C> + 'a> {}
 
 impl<'a, C: Context> FilterSelect<C> for Box<dyn Select<C> + 'a> {}
 
+impl<'a, C: Context> OrderSelect<C> for Box<dyn Select<C> + 'a> {}
+
 impl<A, B, C: Context> Select<C> for Either<A, B>
 where
     A: Select<C>,
@@ -70,6 +73,13 @@ where
 {
 }
 
+impl<A, B, C: Context> OrderSelect<C> for Either<A, B>
+where
+    A: OrderSelect<C>,
+    B: OrderSelect<C>,
+{
+}
+
 impl<A, B, C: Context> Statement<C> for Either<A, B>
 where
     A: Statement<C>,
@@ -81,6 +91,13 @@ where
             Either::Right(b) => b.build(ctx),
         }
     }
+
+    fn to_ast(&self) -> Option<Stmt> {
+        match self {
+            Either::Left(a) => a.to_ast(),
+            Either::Right(b) => b.to_ast(),
+        }
+    }
 }
 
 mod private {
@@ -109,9 +126,21 @@ pub trait JoinSelect<C: Context>: Select<C> + Sized {
     }
 }
 
-// pub trait OrderSelect<C: Context>: Select<C> + Sized {
-//     fn order_by_ascendin<(self)
-// }
+pub trait OrderSelect<C: Context>: Select<C> + Sized {
+    fn order_by<'a, Col>(self, col: Col) -> OrderSel<'a, Self, C>
+    where
+        Col: Column<C> + 'a,
+    {
+        OrderSel::new(self).then_by(col)
+    }
+
+    fn order_by_desc<'a, Col>(self, col: Col) -> OrderSel<'a, Self, C>
+    where
+        Col: Column<C> + 'a,
+    {
+        OrderSel::new(self).then_by_desc(col)
+    }
+}
 
 // Sel
 
@@ -173,6 +202,13 @@ where
 {
 }
 
+impl<T, S, C: Context> OrderSelect<C> for Sel<T, S>
+where
+    T: Target<C>,
+    S: Selection<C>,
+{
+}
+
 // Selelect offset limit
 
 #[derive(Clone, Debug)]
@@ -303,6 +339,13 @@ where
 {
 }
 
+impl<S, J, C: Context> OrderSelect<C> for JoinSel<S, J, C>
+where
+    S: Select<C>,
+    J: Joinable<C>,
+{
+}
+
 #[derive(Clone, Debug)]
 pub struct FilterSel<S, E> {
     select: S,
@@ -366,6 +409,75 @@ where
 {
 }
 
+impl<S, E, C: Context> OrderSelect<C> for FilterSel<S, E>
+where
+    S: Select<C>,
+    E: Expression<C>,
+{
+}
+
+// Select order by
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+pub struct OrderSel<'a, S, C: Context> {
+    select: S,
+    columns: Vec<(Box<dyn Column<C> + 'a>, Order)>,
+}
+
+impl<'a, S, C: Context> OrderSel<'a, S, C>
+where
+    S: Select<C>,
+{
+    pub fn new(select: S) -> OrderSel<'a, S, C> {
+        OrderSel {
+            select,
+            columns: Vec::default(),
+        }
+    }
+
+    pub fn then_by<Col: Column<C> + 'a>(mut self, col: Col) -> Self {
+        self.columns.push((Box::new(col), Order::Asc));
+        self
+    }
+
+    pub fn then_by_desc<Col: Column<C> + 'a>(mut self, col: Col) -> Self {
+        self.columns.push((Box::new(col), Order::Desc));
+        self
+    }
+}
+
+impl<'a, S: Select<C>, C: Context> Select<C> for OrderSel<'a, S, C> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        self.select.build(ctx)?;
+
+        for (idx, (col, order)) in self.columns.iter().enumerate() {
+            ctx.write_str(if idx == 0 { " ORDER BY " } else { ", " })?;
+            Column::build(&**col, ctx)?;
+            match order {
+                Order::Asc => ctx.write_str(" ASC")?,
+                Order::Desc => ctx.write_str(" DESC")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, S: Select<C>, C: Context> Statement<C> for OrderSel<'a, S, C> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        <OrderSel<'a, S, C> as Select<C>>::build(self, ctx)?;
+        Ok(())
+    }
+}
+
+impl<'a, S: Select<C>, C: Context> LimitedSelect<C> for OrderSel<'a, S, C> {}
+
 pub struct SelectExpr<S, C: Context>
 where
     S: Select<C>,
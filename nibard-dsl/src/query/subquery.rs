@@ -0,0 +1,130 @@
+use super::{Expression, IntoValue, Select, ValueFor};
+use crate::{Context, Error};
+use nibard_shared::Dialect;
+use std::marker::PhantomData;
+
+/// `EXISTS (subquery)`, or `NOT EXISTS` when negated. The subquery can refer
+/// to tables of the outer query, usually through a `TableAlias`.
+#[derive(Clone, Debug)]
+pub struct Exists<S, C> {
+    select: S,
+    negated: bool,
+    _c: PhantomData<C>,
+}
+
+pub fn exists<S: Select<C>, C: Context>(select: S) -> Exists<S, C> {
+    Exists {
+        select,
+        negated: false,
+        _c: PhantomData,
+    }
+}
+
+pub fn not_exists<S: Select<C>, C: Context>(select: S) -> Exists<S, C> {
+    Exists {
+        select,
+        negated: true,
+        _c: PhantomData,
+    }
+}
+
+impl<S: Select<C>, C: Context> Expression<C> for Exists<S, C> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        if self.negated {
+            ctx.write_str("NOT ")?;
+        }
+        ctx.write_str("EXISTS (")?;
+        self.select.build(ctx)?;
+        ctx.write_char(')')?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Quantifier {
+    Any,
+    All,
+}
+
+/// A quantified subquery for the right-hand side of a comparison, as in
+/// `"price".gt(all(subquery))`. Sqlite has no quantified comparisons.
+#[derive(Clone, Debug)]
+pub struct Quantified<S, C> {
+    select: S,
+    quantifier: Quantifier,
+    _c: PhantomData<C>,
+}
+
+pub fn any<S: Select<C>, C: Context>(select: S) -> Quantified<S, C> {
+    Quantified {
+        select,
+        quantifier: Quantifier::Any,
+        _c: PhantomData,
+    }
+}
+
+pub fn all<S: Select<C>, C: Context>(select: S) -> Quantified<S, C> {
+    Quantified {
+        select,
+        quantifier: Quantifier::All,
+        _c: PhantomData,
+    }
+}
+
+impl<S: Select<C>, C: Context> Expression<C> for Quantified<S, C> {
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        let dialect = *ctx.dialect();
+        let quantifier = match self.quantifier {
+            Quantifier::Any => "ANY",
+            Quantifier::All => "ALL",
+        };
+        if dialect == Dialect::Sqlite {
+            return Err(Error::Unsupported {
+                dialect,
+                feature: format!("{} (subquery)", quantifier),
+            });
+        }
+
+        write!(ctx, "{} (", quantifier)?;
+        self.select.build(ctx)?;
+        ctx.write_char(')')?;
+        Ok(())
+    }
+}
+
+impl<S: Select<C>, C: Context> IntoValue<C> for Quantified<S, C> {
+    type Expression = Self;
+    fn into_expression(self) -> Self::Expression {
+        self
+    }
+}
+
+impl<S: Select<C>, T, C: Context> ValueFor<T, C> for Quantified<S, C> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+    use crate::prelude::*;
+
+    #[test]
+    fn correlated() {
+        let users = "users".table_alias("u");
+        let posts = "posts"
+            .select("posts.id")
+            .filter("posts.owner_id".eql((&users).col("id").expr()));
+        let select = (&users)
+            .select("u.name")
+            .filter(not_exists(posts))
+            .and("u.age".gte(all("limits".select("age"))));
+
+        let (sql, _) = build(Dialect::Pg, &select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT u.name FROM users AS u WHERE NOT EXISTS \
+             (SELECT posts.id FROM posts WHERE posts.owner_id = u.id) \
+             AND u.age >= ALL (SELECT age FROM limits)"
+        );
+        assert!(build(Dialect::Sqlite, &select).is_err());
+    }
+}
@@ -1,5 +1,7 @@
+use super::types::write_live_rows;
 use super::{
-    Alias, Column, FilterSelect, JoinSelect, LimitedSelect, Sel, Select, Selection, Target,
+    Alias, Column, FilterSelect, JoinSelect, LimitedSelect, OrderSelect, Sel, Select, Selection,
+    Target,
 };
 use crate::{Context, Error, Statement, Table};
 use std::marker::PhantomData;
@@ -75,6 +77,13 @@ where
 {
 }
 
+impl<T, S, C: Context> OrderSelect<C> for TargetSelect<T, S, C>
+where
+    T: Target<C>,
+    S: Selection<C>,
+{
+}
+
 impl<T, S, C: Context> Statement<C> for TargetSelect<T, S, C>
 where
     T: Target<C>,
@@ -135,20 +144,28 @@ where
     A: Alias<C>,
 {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
-        self.table.build(ctx)?;
+        let live_rows = match self.table.table_name() {
+            Some(table) => write_live_rows(ctx, table)?,
+            None => false,
+        };
+        if !live_rows {
+            self.table.build(ctx)?;
+        }
         write!(ctx, " AS ")?;
         self.alias.build(ctx)?;
         Ok(())
     }
 }
 
+/// Columns of an aliased table are qualified with the alias, which is also
+/// how correlated subqueries refer to it.
 impl<T, A, C: Context> Table<C> for TableAlias<T, A, C>
 where
     T: Table<C>,
     A: Alias<C>,
 {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
-        <T as Table<C>>::build(&self.table, ctx)?;
+        self.alias.build(ctx)?;
         Ok(())
     }
 }
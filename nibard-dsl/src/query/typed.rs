@@ -0,0 +1,281 @@
+use super::{
+    BinaryExpression, BinaryOperator, ColExpr, Column, IntoValue, Select, SelectExpr, Selection,
+};
+use crate::{Context, Error};
+use nibard_shared::Value;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A column carrying the Rust type `T` of the values it holds.
+///
+/// The comparison methods shadow the ones from `ColumnExt` and only accept
+/// values implementing `ValueFor<T>`, so comparing an integer column with a
+/// string fails to compile. Nullable columns are typed as `Option<T>` and
+/// accept both `T` and `Option<T>`.
+///
+/// ```ignore
+/// const AGE: TypedColumn<i32> = TypedColumn::new("age");
+/// "users".select("name").filter(AGE.gte(18));
+/// ```
+pub struct TypedColumn<T, Col = &'static str> {
+    col: Col,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T, Col> TypedColumn<T, Col> {
+    pub const fn new(col: Col) -> TypedColumn<T, Col> {
+        TypedColumn {
+            col,
+            _t: PhantomData,
+        }
+    }
+
+    pub fn column(&self) -> &Col {
+        &self.col
+    }
+
+    pub fn into_inner(self) -> Col {
+        self.col
+    }
+
+    pub fn eql<C: Context, E: ValueFor<T, C>>(
+        self,
+        e: E,
+    ) -> BinaryExpression<ColExpr<Self, C>, E::Expression, C>
+    where
+        Self: Column<C>,
+    {
+        BinaryExpression::new(ColExpr::new(self), e.into_expression(), BinaryOperator::Eq)
+    }
+
+    pub fn neq<C: Context, E: ValueFor<T, C>>(
+        self,
+        e: E,
+    ) -> BinaryExpression<ColExpr<Self, C>, E::Expression, C>
+    where
+        Self: Column<C>,
+    {
+        BinaryExpression::new(
+            ColExpr::new(self),
+            e.into_expression(),
+            BinaryOperator::NotEq,
+        )
+    }
+
+    pub fn lt<C: Context, E: ValueFor<T, C>>(
+        self,
+        e: E,
+    ) -> BinaryExpression<ColExpr<Self, C>, E::Expression, C>
+    where
+        Self: Column<C>,
+    {
+        BinaryExpression::new(ColExpr::new(self), e.into_expression(), BinaryOperator::Lt)
+    }
+
+    pub fn lte<C: Context, E: ValueFor<T, C>>(
+        self,
+        e: E,
+    ) -> BinaryExpression<ColExpr<Self, C>, E::Expression, C>
+    where
+        Self: Column<C>,
+    {
+        BinaryExpression::new(ColExpr::new(self), e.into_expression(), BinaryOperator::Lte)
+    }
+
+    pub fn gt<C: Context, E: ValueFor<T, C>>(
+        self,
+        e: E,
+    ) -> BinaryExpression<ColExpr<Self, C>, E::Expression, C>
+    where
+        Self: Column<C>,
+    {
+        BinaryExpression::new(ColExpr::new(self), e.into_expression(), BinaryOperator::Gt)
+    }
+
+    pub fn gte<C: Context, E: ValueFor<T, C>>(
+        self,
+        e: E,
+    ) -> BinaryExpression<ColExpr<Self, C>, E::Expression, C>
+    where
+        Self: Column<C>,
+    {
+        BinaryExpression::new(ColExpr::new(self), e.into_expression(), BinaryOperator::Gte)
+    }
+
+    pub fn like<C: Context, E: ValueFor<T, C>>(
+        self,
+        e: E,
+    ) -> BinaryExpression<ColExpr<Self, C>, E::Expression, C>
+    where
+        Self: Column<C>,
+    {
+        BinaryExpression::new(
+            ColExpr::new(self),
+            e.into_expression(),
+            BinaryOperator::Like,
+        )
+    }
+
+    pub fn has<C: Context, E: ValueFor<T, C>>(
+        self,
+        e: E,
+    ) -> BinaryExpression<ColExpr<Self, C>, E::Expression, C>
+    where
+        Self: Column<C>,
+    {
+        BinaryExpression::new(ColExpr::new(self), e.into_expression(), BinaryOperator::In)
+    }
+}
+
+impl<T, Col: Clone> Clone for TypedColumn<T, Col> {
+    fn clone(&self) -> Self {
+        TypedColumn::new(self.col.clone())
+    }
+}
+
+impl<T, Col: Copy> Copy for TypedColumn<T, Col> {}
+
+impl<T, Col: fmt::Debug> fmt::Debug for TypedColumn<T, Col> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedColumn")
+            .field(&self.col)
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T, Col, C: Context> Selection<C> for TypedColumn<T, Col>
+where
+    Col: Column<C>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        <Col as Selection<C>>::build(&self.col, ctx)
+    }
+}
+
+impl<T, Col, C: Context> Column<C> for TypedColumn<T, Col>
+where
+    Col: Column<C>,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        <Col as Column<C>>::build(&self.col, ctx)
+    }
+}
+
+/// Values that can be compared against a column holding `T`.
+pub trait ValueFor<T, C: Context>: IntoValue<C> {}
+
+macro_rules! value_for {
+    ($ty: ty => $($value: ty),*) => {
+        $(
+            impl<C: Context> ValueFor<$ty, C> for $value {}
+            impl<C: Context> ValueFor<Option<$ty>, C> for $value {}
+            impl<C: Context> ValueFor<Option<$ty>, C> for Option<$value> {}
+        )*
+    };
+}
+
+value_for!(i8 => i8);
+value_for!(u8 => u8);
+value_for!(i16 => i8, u8, i16);
+value_for!(u16 => u8, u16);
+value_for!(i32 => i8, u8, i16, u16, i32);
+value_for!(u32 => u8, u16, u32);
+value_for!(i64 => i8, u8, i16, u16, i32, u32, i64);
+value_for!(f32 => f32);
+value_for!(f64 => f32, f64);
+value_for!(bool => bool);
+value_for!(String => String);
+value_for!(Vec<u8> => Vec<u8>);
+#[cfg(feature = "time")]
+value_for!(chrono::NaiveDate => chrono::NaiveDate);
+#[cfg(feature = "time")]
+value_for!(chrono::NaiveDateTime => chrono::NaiveDateTime, chrono::DateTime<chrono::Utc>);
+#[cfg(feature = "time")]
+value_for!(chrono::DateTime<chrono::Utc> => chrono::NaiveDateTime, chrono::DateTime<chrono::Utc>);
+
+impl<C: Context> ValueFor<String, C> for &str {}
+impl<C: Context> ValueFor<Option<String>, C> for &str {}
+impl<C: Context> ValueFor<Option<String>, C> for Option<&str> {}
+
+// Explicit values and subqueries are not checked.
+impl<T, C: Context> ValueFor<T, C> for Value {}
+
+impl<T, C: Context> ValueFor<T, C> for &Value {}
+
+impl<S: Select<C>, T, C: Context> ValueFor<T, C> for SelectExpr<S, C> {}
+
+// Columns of the same type can be compared with each other.
+impl<T, Col, C: Context> ValueFor<T, C> for ColExpr<TypedColumn<T, Col>, C> where Col: Column<C> {}
+
+impl<T, Col, C: Context> ValueFor<Option<T>, C> for ColExpr<TypedColumn<T, Col>, C> where
+    Col: Column<C>
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::build;
+    use crate::prelude::*;
+    use nibard_shared::Dialect;
+
+    const ID: TypedColumn<i64> = TypedColumn::new("users.id");
+    const NAME: TypedColumn<Option<String>> = TypedColumn::new("users.name");
+    const OWNER: TypedColumn<i64> = TypedColumn::new("posts.owner_id");
+
+    #[test]
+    fn typed_comparisons() {
+        let select = "users".select(ID).filter(
+            ID.gt(10)
+                .and(NAME.eql(Some("bob")))
+                .and(OWNER.eql(ID.expr())),
+        );
+        let (sql, values) = build(Dialect::Pg, select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT users.id FROM users WHERE users.id > $1 AND users.name = $2 AND posts.owner_id = users.id"
+        );
+        assert_eq!(values, vec![Value::Int(10), Value::Text("bob".into())]);
+    }
+
+    #[test]
+    fn unsigned_columns() {
+        const AGE: TypedColumn<u8> = TypedColumn::new("age");
+        const SIZE: TypedColumn<Option<u32>> = TypedColumn::new("size");
+        const SCORE: TypedColumn<i64> = TypedColumn::new("score");
+
+        let select = "files"
+            .select(AGE)
+            .filter(AGE.gte(18u8).and(SIZE.lt(4096u32)).and(SCORE.eql(7u16)));
+        let (sql, values) = build(Dialect::Sqlite, select).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT age FROM files WHERE age >= ? AND size < ? AND score = ?"
+        );
+        assert_eq!(
+            values,
+            vec![Value::SmallInt(18), Value::BigInt(4096), Value::Int(7)]
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_columns() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        const BORN: TypedColumn<NaiveDate> = TypedColumn::new("born");
+        const SEEN: TypedColumn<Option<chrono::DateTime<Utc>>> = TypedColumn::new("seen");
+
+        let date = NaiveDate::from_ymd_opt(2020, 1, 2).unwrap();
+        let seen = Utc.from_utc_datetime(&date.and_hms_opt(5, 6, 7).unwrap());
+        let select = "users"
+            .select(BORN)
+            .filter(BORN.lt(date).and(SEEN.gt(seen)));
+        let (_, values) = build(Dialect::Pg, select).unwrap();
+        assert_eq!(
+            values,
+            vec![Value::Date(date), Value::DateTime(seen.naive_utc())]
+        );
+    }
+}
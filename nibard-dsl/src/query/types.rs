@@ -29,6 +29,12 @@ impl<C: Context> Alias<C> for String {
 
 pub trait Target<C: Context> {
     fn build(&self, ctx: &mut C) -> Result<(), Error>;
+
+    /// The name of the table, if the target is a single table, for the
+    /// soft-delete policy of the context.
+    fn table_name(&self) -> Option<&str> {
+        None
+    }
 }
 
 impl<'a, T, C: Context> Target<C> for &'a T
@@ -38,12 +44,40 @@ where
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
         <T as Target<C>>::build(&**self, ctx)
     }
+
+    fn table_name(&self) -> Option<&str> {
+        <T as Target<C>>::table_name(&**self)
+    }
+}
+
+/// Writes `table` as a subquery without the rows the soft-delete policy of
+/// the context marks deleted, returning `false` and writing nothing when
+/// the table has no such column. The caller writes the alias.
+pub(crate) fn write_live_rows<C: Context>(ctx: &mut C, table: &str) -> Result<bool, Error> {
+    let column = match ctx.soft_delete().and_then(|policy| policy.column(table)) {
+        Some(column) => column.to_owned(),
+        None => return Ok(false),
+    };
+    write!(ctx, "(SELECT * FROM {} WHERE {} IS NULL)", table, column)?;
+    Ok(true)
+}
+
+fn write_table<C: Context>(ctx: &mut C, table: &str) -> Result<(), Error> {
+    if write_live_rows(ctx, table)? {
+        write!(ctx, " AS {}", table)?;
+    } else {
+        ctx.write_str(table)?;
+    }
+    Ok(())
 }
 
 impl<'a, C: Context> Target<C> for &'a str {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
-        ctx.write_str(self)?;
-        Ok(())
+        write_table(ctx, self)
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        Some(self)
     }
 }
 
@@ -56,8 +90,11 @@ impl<'a, C: Context> Table<C> for &'a str {
 
 impl<C: Context> Target<C> for String {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
-        ctx.write_str(self)?;
-        Ok(())
+        write_table(ctx, self)
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        Some(self)
     }
 }
 
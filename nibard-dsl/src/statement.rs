@@ -1,14 +1,58 @@
-use super::{Context, Error};
+use super::{ast::Stmt, Context, Error, LiteralContext};
+use nibard_shared::Dialect;
 
 pub trait Statement<C: Context> {
     fn build(&self, ctx: &mut C) -> Result<(), Error>;
+
+    /// The statement as an owned tree, for statements that can be rewritten
+    /// before they are built. The typed builders return `None`; they apply
+    /// the soft-delete policy of the context while building instead.
+    fn to_ast(&self) -> Option<Stmt> {
+        None
+    }
+}
+
+impl<T, C: Context> Statement<C> for &T
+where
+    T: Statement<C> + ?Sized,
+{
+    fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        <T as Statement<C>>::build(&**self, ctx)
+    }
+
+    fn to_ast(&self) -> Option<Stmt> {
+        <T as Statement<C>>::to_ast(&**self)
+    }
+}
+
+pub trait StatementExt: Statement<LiteralContext> {
+    /// Renders the statement with every value written as an escaped literal
+    /// for `dialect`, for logging and `.sql` dumps. Not meant to be executed
+    /// with untrusted input; use bind parameters for that.
+    fn to_sql_literal(&self, dialect: Dialect) -> Result<String, Error> {
+        inline(dialect, |ctx| self.build(ctx))
+    }
+}
+
+impl<S: Statement<LiteralContext>> StatementExt for S {}
+
+/// Runs `build` against a fresh `LiteralContext` and returns the SQL. Every
+/// inlined fragment goes through here, `to_sql_literal` included.
+pub(crate) fn inline<F>(dialect: Dialect, build: F) -> Result<String, Error>
+where
+    F: FnOnce(&mut LiteralContext) -> Result<(), Error>,
+{
+    let mut ctx = LiteralContext::new(dialect);
+    build(&mut ctx)?;
+    let (sql, _) = ctx.build()?;
+    Ok(sql)
 }
 
 pub trait Table<C: Context> {
     fn build(&self, ctx: &mut C) -> Result<(), Error>;
 }
 
-impl<'a, T, C: Context> Table<C> for &'a T
+impl<T, C: Context> Table<C> for &T
 where
     T: Table<C>,
 {
@@ -16,3 +60,35 @@ where
         <T as Table<C>>::build(&**self, ctx)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insert::Insert;
+    use crate::prelude::*;
+    use nibard_shared::Value;
+
+    #[test]
+    fn literal_values() {
+        let select = "users"
+            .select("id")
+            .filter("name".eql("o'neil").and("active".eql(true)));
+        assert_eq!(
+            select.to_sql_literal(Dialect::Sqlite).unwrap(),
+            "SELECT id FROM users WHERE name = 'o''neil' AND active = 1"
+        );
+
+        let insert = Insert::new("files")
+            .set("data", vec![0xde_u8, 0xad])
+            .set("parent", Value::Null)
+            .set("public", false);
+        assert_eq!(
+            insert.to_sql_literal(Dialect::Pg).unwrap(),
+            "INSERT INTO files (data, parent, public) VALUES ('\\xdead'::bytea,NULL,FALSE)"
+        );
+        assert_eq!(
+            insert.to_sql_literal(Dialect::MySQL).unwrap(),
+            "INSERT INTO files (data, parent, public) VALUES (X'dead',NULL,FALSE)"
+        );
+    }
+}
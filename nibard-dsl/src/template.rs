@@ -0,0 +1,107 @@
+use super::Error;
+use nibard_shared::Value;
+use std::collections::{HashMap, HashSet};
+
+/// A built statement with named parameters left open, so the same SQL can be
+/// executed many times with different values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    sql: String,
+    values: Vec<Value>,
+    params: HashMap<String, Vec<usize>>,
+}
+
+impl Template {
+    pub(crate) fn new(sql: String, values: Vec<Value>, slots: Vec<(usize, String)>) -> Template {
+        let mut params: HashMap<String, Vec<usize>> = HashMap::default();
+        for (idx, name) in slots {
+            params.entry(name).or_default().push(idx);
+        }
+        Template {
+            sql,
+            values,
+            params,
+        }
+    }
+
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Names of the parameters which have to be bound.
+    pub fn params(&self) -> impl Iterator<Item = &str> {
+        self.params.keys().map(|name| name.as_str())
+    }
+
+    /// Returns the bind values with every named parameter filled in. All
+    /// parameters have to be bound and unknown names are rejected.
+    pub fn bind<K, V, I>(&self, params: I) -> Result<Vec<Value>, Error>
+    where
+        K: AsRef<str>,
+        V: Into<Value>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut values = self.values.clone();
+        let mut bound = HashSet::new();
+        for (name, value) in params {
+            let name = name.as_ref();
+            let (name, slots) = self
+                .params
+                .get_key_value(name)
+                .ok_or_else(|| Error::UnknownParam(name.to_owned()))?;
+            let value = value.into();
+            for &idx in slots {
+                values[idx] = value.clone();
+            }
+            bound.insert(name.as_str());
+        }
+
+        let missing = self
+            .params
+            .keys()
+            .filter(|name| !bound.contains(name.as_str()))
+            .min();
+        if let Some(name) = missing {
+            return Err(Error::MissingParam(name.clone()));
+        }
+
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+    use crate::query::Param;
+    use crate::{prepare, Error};
+    use nibard_shared::{Dialect, Value};
+
+    #[test]
+    fn bind_by_name() {
+        let select = "users".select("name").filter(
+            "id".eql(Param::named("user_id"))
+                .and("active".eql(true))
+                .or("owner".eql(Param::named("user_id"))),
+        );
+        let template = prepare(Dialect::Pg, select).unwrap();
+        assert_eq!(
+            template.sql(),
+            "SELECT name FROM users WHERE id = $1 AND active = $2 OR owner = $3"
+        );
+
+        let values = template.bind([("user_id", 7)]).unwrap();
+        assert_eq!(
+            values,
+            vec![Value::Int(7), Value::Bool(true), Value::Int(7)]
+        );
+
+        assert!(matches!(
+            template.bind(Vec::<(&str, i32)>::new()),
+            Err(Error::MissingParam(name)) if name == "user_id"
+        ));
+        assert!(matches!(
+            template.bind([("user_id", 7), ("other", 1)]),
+            Err(Error::UnknownParam(name)) if name == "other"
+        ));
+    }
+}
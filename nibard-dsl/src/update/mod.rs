@@ -35,6 +35,13 @@ impl<'a, C: Context> Update<'a, C> {
 
 impl<'a, C: Context> Statement<C> for Update<'a, C> {
     fn build(&self, ctx: &mut C) -> Result<(), Error> {
+        if self.values.is_empty() {
+            return Err(Error::InvalidStatement {
+                dialect: *ctx.dialect(),
+                description: format!("update of {} sets no columns", self.table),
+            });
+        }
+
         write!(ctx, "UPDATE {} SET ", self.table)?;
         for (idx, value) in self.values.iter().enumerate() {
             if idx > 0 {
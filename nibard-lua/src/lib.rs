@@ -4,7 +4,10 @@ use mlua::{
     serde::LuaSerdeExt, Error as LuaError, Lua, UserData, UserDataFields, UserDataMethods,
     Value as LuaValue,
 };
-use nibard::{connection::Error as NibardError, Database, DatabaseRow, Executor, RowExt};
+use nibard::{
+    connection::Error as NibardError, Database, DatabaseRow, Executor, RowExt, Untagged, Value,
+};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -63,7 +66,7 @@ impl UserData for LuaDatabase {
                 .map_err(LuaError::external)?
                 .to_map();
 
-            lua.to_value(&row)
+            lua.to_value(&untagged(&row))
         });
 
         methods.add_async_method(
@@ -90,7 +93,7 @@ impl UserData for LuaDatabase {
                             Some(Err(err)) => return Err(LuaError::external(err)),
                             None => return Ok(LuaValue::Nil),
                         };
-                        let value = lua.to_value(&row)?;
+                        let value = lua.to_value(&untagged(&row))?;
                         Ok(value)
                     }
                 })
@@ -98,3 +101,10 @@ impl UserData for LuaDatabase {
         );
     }
 }
+
+/// Rows reach Lua as plain tables of column values.
+fn untagged(row: &HashMap<String, Value>) -> HashMap<&str, Untagged<'_>> {
+    row.iter()
+        .map(|(column, value)| (column.as_str(), Untagged(value)))
+        .collect()
+}
@@ -19,12 +19,14 @@ pub enum Type {
     #[cfg(feature = "json")]
     JSON,
     Auto,
+    /// A 64 bit auto increment column, `BIGSERIAL` on Pg.
+    BigAuto,
 }
 
 impl Type {
     pub fn is_auto(&self) -> bool {
         match self {
-            Type::Auto => true,
+            Type::Auto | Type::BigAuto => true,
             _ => false,
         }
     }
@@ -32,6 +34,7 @@ impl Type {
     fn to_pg(&self, out: &mut dyn Write) -> fmt::Result {
         match self {
             Type::Auto => out.write_str("SERIAL"),
+            Type::BigAuto => out.write_str("BIGSERIAL"),
             Type::BigInt => out.write_str("BIGINT"),
             Type::Binary => out.write_str("BYTEA"),
             Type::Bool => out.write_str("BOOL"),
@@ -45,13 +48,13 @@ impl Type {
             Type::Text => out.write_str("TEXT"),
             Type::Int => out.write_str("INTEGER"),
             #[cfg(feature = "json")]
-            Type::JSON => out.write_str("JSON"),
+            Type::JSON => out.write_str("JSONB"),
         }
     }
 
     fn to_sqlite(&self, out: &mut dyn Write) -> fmt::Result {
         match self {
-            Type::Auto => out.write_str("INTEGER"),
+            Type::Auto | Type::BigAuto => out.write_str("INTEGER"),
             Type::BigInt => out.write_str("BIGINT"),
             Type::Binary => out.write_str("BYTEA"),
             Type::Bool => out.write_str("BOOL"),
@@ -72,8 +75,9 @@ impl Type {
     fn to_mysql(&self, out: &mut dyn Write) -> fmt::Result {
         match self {
             Type::Auto => out.write_str("INTEGER"),
+            Type::BigAuto => out.write_str("BIGINT"),
             Type::BigInt => out.write_str("BIGINT"),
-            Type::Binary => out.write_str("BYTEA"),
+            Type::Binary => out.write_str("BLOB"),
             Type::Bool => out.write_str("BOOL"),
             Type::Char(i) => write!(out, "CHAR({})", i),
             Type::VarChar(i) => write!(out, "VARCHAR({})", i),
@@ -89,6 +93,77 @@ impl Type {
         }
     }
 
+    /// Maps a column type as the database reports it, eg. `varchar(64)`,
+    /// `character varying(64)` or `int unsigned`, back to a `Type`. Sqlite
+    /// accepts any type name, so unknown names fall back to its affinity
+    /// rules there. Sqlite stores every integer in up to 8 bytes, so only
+    /// a column declared exactly `INT` maps to `Type::Int` there. Auto
+    /// increment is not part of the type name and has to be detected by the
+    /// caller.
+    pub fn from_sql(dialect: Dialect, name: &str) -> Option<Type> {
+        let name = name.trim().to_ascii_lowercase();
+        let (base, args) = match name.find('(') {
+            Some(idx) => (
+                name[..idx].trim(),
+                name[idx + 1..].split(')').next().unwrap_or(""),
+            ),
+            None => (name.as_str(), ""),
+        };
+        let base = base.trim_end_matches(" unsigned");
+        let size = args
+            .split(',')
+            .next()
+            .and_then(|arg| arg.trim().parse().ok());
+
+        let ty = match base {
+            "serial" | "serial4" => Type::Auto,
+            "bigserial" | "serial8" => Type::BigAuto,
+            "tinyint" if dialect == Dialect::MySQL && size == Some(1) => Type::Bool,
+            "smallint" | "int2" | "tinyint" => Type::SmallInt,
+            "integer" if dialect == Dialect::Sqlite => Type::BigInt,
+            "int" | "integer" | "int4" | "mediumint" => Type::Int,
+            "bigint" | "int8" => Type::BigInt,
+            "char" | "character" | "bpchar" => Type::Char(size.unwrap_or(1)),
+            "varchar" | "character varying" => match size {
+                Some(size) => Type::VarChar(size),
+                None => Type::Text,
+            },
+            "text" | "tinytext" | "mediumtext" | "longtext" | "clob" => Type::Text,
+            "float" => Type::Float(size.map(|size| size as u16).unwrap_or(53)),
+            "double" | "double precision" | "float8" => Type::Float(53),
+            "real" | "float4" => Type::Real,
+            "bool" | "boolean" => Type::Bool,
+            "bytea" | "blob" | "tinyblob" | "mediumblob" | "longblob" | "binary" | "varbinary" => {
+                Type::Binary
+            }
+            "date" => Type::Date,
+            "datetime"
+            | "timestamp"
+            | "timestamptz"
+            | "timestamp without time zone"
+            | "timestamp with time zone" => Type::DateTime,
+            #[cfg(feature = "json")]
+            "json" | "jsonb" => Type::JSON,
+            _ if dialect == Dialect::Sqlite => {
+                // https://www.sqlite.org/datatype3.html#determination_of_column_affinity
+                if base.contains("int") {
+                    Type::BigInt
+                } else if base.contains("char") || base.contains("clob") || base.contains("text") {
+                    Type::Text
+                } else if base.is_empty() || base.contains("blob") {
+                    Type::Binary
+                } else if base.contains("real") || base.contains("floa") || base.contains("doub") {
+                    Type::Real
+                } else {
+                    return None;
+                }
+            }
+            _ => return None,
+        };
+
+        Some(ty)
+    }
+
     pub fn write_sql(&self, out: &mut dyn fmt::Write, dialect: Dialect) -> fmt::Result {
         match dialect {
             Dialect::Pg => self.to_pg(out),
@@ -97,3 +172,55 @@ impl Type {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_sql() {
+        let cases = [
+            (Dialect::Pg, "integer", Some(Type::Int)),
+            (Dialect::Pg, "bigint", Some(Type::BigInt)),
+            (Dialect::Pg, "serial", Some(Type::Auto)),
+            (Dialect::Pg, "bigserial", Some(Type::BigAuto)),
+            (
+                Dialect::Pg,
+                "character varying(64)",
+                Some(Type::VarChar(64)),
+            ),
+            (Dialect::Pg, "character varying", Some(Type::Text)),
+            (Dialect::Pg, "character(2)", Some(Type::Char(2))),
+            (Dialect::Pg, "double precision", Some(Type::Float(53))),
+            (Dialect::Pg, "real", Some(Type::Real)),
+            (
+                Dialect::Pg,
+                "timestamp with time zone",
+                Some(Type::DateTime),
+            ),
+            (Dialect::Pg, "bytea", Some(Type::Binary)),
+            (Dialect::Pg, "uuid", None),
+            (Dialect::MySQL, "tinyint(1)", Some(Type::Bool)),
+            (Dialect::MySQL, "tinyint(4)", Some(Type::SmallInt)),
+            (Dialect::MySQL, "int unsigned", Some(Type::Int)),
+            (Dialect::MySQL, "varchar(255)", Some(Type::VarChar(255))),
+            (Dialect::MySQL, "float(24)", Some(Type::Float(24))),
+            (Dialect::MySQL, "datetime", Some(Type::DateTime)),
+            (Dialect::MySQL, "geometry", None),
+            (Dialect::Sqlite, "INT", Some(Type::Int)),
+            (Dialect::Sqlite, "INTEGER", Some(Type::BigInt)),
+            (Dialect::Sqlite, "UNSIGNED BIG INT", Some(Type::BigInt)),
+            (Dialect::Sqlite, "VARCHAR(10)", Some(Type::VarChar(10))),
+            (Dialect::Sqlite, "NVARCHAR", Some(Type::Text)),
+            (Dialect::Sqlite, "", Some(Type::Binary)),
+            (Dialect::Sqlite, "DOUBLE", Some(Type::Float(53))),
+            (Dialect::Sqlite, "FLOATING POINT", Some(Type::BigInt)),
+            (Dialect::Sqlite, "LONG DOUBLE", Some(Type::Real)),
+            (Dialect::Sqlite, "NUMERIC", None),
+        ];
+
+        for (dialect, name, ty) in cases {
+            assert_eq!(Type::from_sql(dialect, name), ty, "{:?} {}", dialect, name);
+        }
+    }
+}
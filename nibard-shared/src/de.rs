@@ -19,6 +19,11 @@ impl<'de> de::Deserializer<'de> for Value {
             Value::Bool(b) => visitor.visit_bool(b),
             Value::Float(f) => visitor.visit_f64(f),
             Value::Text(s) => visitor.visit_string(s),
+            Value::Binary(b) => visitor.visit_byte_buf(b),
+            #[cfg(feature = "json")]
+            Value::Json(json) => json
+                .deserialize_any(visitor)
+                .map_err(|err| Error::Message(err.to_string())),
             _ => {
                 unimplemented!("Type {:?}", self)
             }
@@ -1,9 +1,10 @@
-use super::Error;
+use super::{Dialect, Error, Type};
 #[cfg(feature = "time")]
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 #[cfg(feature = "json")]
 use serde_json::Value as JsonValue;
 use std::borrow::Cow;
+use std::fmt;
 
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -22,12 +23,13 @@ pub enum ValueRef<'a> {
     #[cfg(feature = "time")]
     DateTime(NaiveDateTime),
     #[cfg(feature = "json")]
-    Json(#[cfg_attr(feature = "serde", serde(borrow))] &'a JsonValue),
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+    Json(&'a JsonValue),
     Null,
 }
 
 #[derive(Debug, PartialEq, Clone)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum Value {
     Text(String),
@@ -68,6 +70,45 @@ impl Value {
         }
     }
 
+    /// Writes the value as an escaped SQL literal for the given dialect.
+    /// Used where bind parameters are not allowed, such as column defaults.
+    pub fn write_sql(&self, out: &mut dyn fmt::Write, dialect: Dialect) -> fmt::Result {
+        match self {
+            Value::Null => out.write_str("NULL"),
+            Value::SmallInt(i) => write!(out, "{}", i),
+            Value::Int(i) => write!(out, "{}", i),
+            Value::BigInt(i) => write!(out, "{}", i),
+            Value::Float(f) => write_float(out, *f, dialect),
+            Value::Real(f) => write_float(out, *f as f64, dialect),
+            Value::Bool(b) => match (dialect, b) {
+                (Dialect::Sqlite, true) => out.write_str("1"),
+                (Dialect::Sqlite, false) => out.write_str("0"),
+                (_, true) => out.write_str("TRUE"),
+                (_, false) => out.write_str("FALSE"),
+            },
+            Value::Text(s) => write_str_literal(out, s, dialect),
+            Value::Binary(b) => {
+                match dialect {
+                    Dialect::Pg => out.write_str("'\\x")?,
+                    Dialect::Sqlite | Dialect::MySQL => out.write_str("X'")?,
+                }
+                for byte in b {
+                    write!(out, "{:02x}", byte)?;
+                }
+                match dialect {
+                    Dialect::Pg => out.write_str("'::bytea"),
+                    Dialect::Sqlite | Dialect::MySQL => out.write_str("'"),
+                }
+            }
+            #[cfg(feature = "time")]
+            Value::Date(date) => write!(out, "'{}'", date.format("%Y-%m-%d")),
+            #[cfg(feature = "time")]
+            Value::DateTime(date) => write!(out, "'{}'", date.format("%Y-%m-%d %H:%M:%S%.f")),
+            #[cfg(feature = "json")]
+            Value::Json(json) => write_str_literal(out, &json.to_string(), dialect),
+        }
+    }
+
     pub fn as_str(&self) -> Option<&str> {
         match self {
             Value::Text(s) => Some(s.as_str()),
@@ -131,28 +172,72 @@ impl Value {
             _ => unimplemented!(""),
         }
     }
+
+    /// Parses the textual form of a value of type `ty`, as found in
+    /// fixture and CSV files. Binary is taken as the raw bytes of `text`.
+    pub fn parse(ty: Type, text: &str) -> Result<Value, Error> {
+        let invalid = || Error::Message(format!("invalid {:?} value: {}", ty, text));
+        let trimmed = text.trim();
+        let value = match ty {
+            Type::Char(_) | Type::VarChar(_) | Type::Text => Value::Text(text.to_owned()),
+            Type::SmallInt => Value::SmallInt(trimmed.parse().map_err(|_| invalid())?),
+            Type::Int | Type::Auto => Value::Int(trimmed.parse().map_err(|_| invalid())?),
+            Type::BigInt | Type::BigAuto => Value::BigInt(trimmed.parse().map_err(|_| invalid())?),
+            Type::Float(_) => Value::Float(trimmed.parse().map_err(|_| invalid())?),
+            Type::Real => Value::Real(trimmed.parse().map_err(|_| invalid())?),
+            Type::Bool => match trimmed.to_ascii_lowercase().as_str() {
+                "true" | "t" | "yes" | "1" => Value::Bool(true),
+                "false" | "f" | "no" | "0" => Value::Bool(false),
+                _ => return Err(invalid()),
+            },
+            Type::Binary => Value::Binary(text.as_bytes().to_vec()),
+            #[cfg(feature = "time")]
+            Type::Date => {
+                Value::Date(NaiveDate::parse_from_str(trimmed, "%Y-%m-%d").map_err(|_| invalid())?)
+            }
+            #[cfg(feature = "time")]
+            Type::DateTime => Value::DateTime(
+                NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S%.f")
+                    .or_else(|_| NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%dT%H:%M:%S%.f"))
+                    .map_err(|_| invalid())?,
+            ),
+            #[cfg(not(feature = "time"))]
+            Type::Date | Type::DateTime => Value::Text(text.to_owned()),
+            #[cfg(feature = "json")]
+            Type::JSON => Value::Json(serde_json::from_str(text).map_err(|_| invalid())?),
+        };
+        Ok(value)
+    }
 }
 
+/// Serializes a value as the bare number, string, etc. it holds, without
+/// the type tag `Value` itself is serialized with, eg. to hand rows to a
+/// scripting language. It cannot be deserialized back into a `Value`.
+#[cfg(feature = "serde")]
+pub struct Untagged<'a>(pub &'a Value);
+
 #[cfg(feature = "serde")]
-impl serde::Serialize for Value {
+impl<'a> serde::Serialize for Untagged<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        match self {
-            Value::Text(s) => serializer.serialize_str(&s),
+        match self.0 {
+            Value::Text(s) => serializer.serialize_str(s),
             Value::BigInt(i) => serializer.serialize_i64(*i),
             Value::Int(i) => serializer.serialize_i32(*i),
             Value::SmallInt(i) => serializer.serialize_i16(*i),
+            Value::Float(f) => serializer.serialize_f64(*f),
+            Value::Real(f) => serializer.serialize_f32(*f),
+            Value::Bool(b) => serializer.serialize_bool(*b),
             #[cfg(feature = "time")]
-            Value::Date(date) => date.serialize(serializer),
+            Value::Date(date) => serde::Serialize::serialize(date, serializer),
             #[cfg(feature = "time")]
-            Value::DateTime(date) => date.serialize(serializer),
+            Value::DateTime(date) => serde::Serialize::serialize(date, serializer),
+            #[cfg(feature = "json")]
+            Value::Json(json) => serde::Serialize::serialize(json, serializer),
             Value::Null => serializer.serialize_unit(),
-            Value::Binary(b) => serializer.serialize_bytes(&b),
-            _ => {
-                unimplemented!("not implemented {:?}", self)
-            }
+            Value::Binary(b) => serializer.serialize_bytes(b),
         }
     }
 }
@@ -215,6 +300,30 @@ impl From<Vec<u8>> for Value {
     }
 }
 
+impl From<i8> for Value {
+    fn from(value: i8) -> Value {
+        Value::SmallInt(value.into())
+    }
+}
+
+impl From<u8> for Value {
+    fn from(value: u8) -> Value {
+        Value::SmallInt(value.into())
+    }
+}
+
+impl From<u16> for Value {
+    fn from(value: u16) -> Value {
+        Value::Int(value.into())
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Value {
+        Value::BigInt(value.into())
+    }
+}
+
 impl From<i16> for Value {
     fn from(value: i16) -> Value {
         Value::SmallInt(value)
@@ -244,3 +353,123 @@ impl From<f64> for Value {
         Value::Float(value)
     }
 }
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Value {
+        Value::Bool(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Value {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<NaiveDate> for Value {
+    fn from(value: NaiveDate) -> Value {
+        Value::Date(value)
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<NaiveDateTime> for Value {
+    fn from(value: NaiveDateTime) -> Value {
+        Value::DateTime(value)
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<DateTime<Utc>> for Value {
+    fn from(value: DateTime<Utc>) -> Value {
+        Value::DateTime(value.naive_utc())
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<JsonValue> for Value {
+    fn from(value: JsonValue) -> Value {
+        Value::Json(value)
+    }
+}
+
+fn write_str_literal(out: &mut dyn fmt::Write, s: &str, dialect: Dialect) -> fmt::Result {
+    out.write_char('\'')?;
+    for c in s.chars() {
+        match c {
+            '\'' => out.write_str("''")?,
+            '\\' if dialect == Dialect::MySQL => out.write_str("\\\\")?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('\'')
+}
+
+fn write_float(out: &mut dyn fmt::Write, f: f64, dialect: Dialect) -> fmt::Result {
+    if f.is_finite() {
+        write!(out, "{:?}", f)
+    } else if dialect == Dialect::Pg {
+        if f.is_nan() {
+            out.write_str("'NaN'::float8")
+        } else if f > 0.0 {
+            out.write_str("'Infinity'::float8")
+        } else {
+            out.write_str("'-Infinity'::float8")
+        }
+    } else {
+        out.write_str("NULL")
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn serde_round_trip() {
+        let values = vec![
+            Value::Text("a".into()),
+            Value::SmallInt(-2),
+            Value::Int(3),
+            Value::BigInt(i64::MAX),
+            Value::Float(0.25),
+            Value::Real(1.5),
+            Value::Bool(true),
+            Value::Binary(vec![0, 255]),
+            Value::Json(serde_json::json!({ "a": [1, null] })),
+            Value::Null,
+        ];
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Value>>(&json).unwrap(), values);
+        assert_eq!(
+            serde_json::to_value(&Value::Float(0.25)).unwrap(),
+            serde_json::json!({ "type": "Float", "value": 0.25 })
+        );
+        assert_eq!(
+            serde_json::to_value(&Value::Null).unwrap(),
+            serde_json::json!({ "type": "Null" })
+        );
+    }
+
+    #[test]
+    fn untagged() {
+        let json = |value: Value| serde_json::to_value(&Untagged(&value)).unwrap();
+        assert_eq!(json(Value::Bool(false)), serde_json::json!(false));
+        assert_eq!(json(Value::Real(0.5)), serde_json::json!(0.5));
+        assert_eq!(json(Value::Text("a".into())), serde_json::json!("a"));
+        assert_eq!(json(Value::Null), serde_json::Value::Null);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn serde_round_trip_dates() {
+        let date = NaiveDate::from_ymd_opt(2021, 3, 4).unwrap();
+        let values = vec![
+            Value::Date(date),
+            Value::DateTime(date.and_hms_opt(5, 6, 7).unwrap()),
+        ];
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Value>>(&json).unwrap(), values);
+    }
+}
@@ -0,0 +1,37 @@
+use super::query::StatementQuery;
+use nibard_connection::{DatabaseTransaction, Error};
+use nibard_dsl::ast::{Expr, InsertStmt};
+use nibard_shared::{Dialect, Value};
+
+/// Sqlite's default limit on bind parameters per statement.
+const MAX_PARAMS: usize = 999;
+
+/// How many rows of `columns` values fit in one `INSERT`, at most
+/// `batch_size`.
+pub(crate) fn rows_per_batch(batch_size: usize, columns: usize) -> usize {
+    batch_size.min(MAX_PARAMS / columns.max(1)).max(1)
+}
+
+/// Inserts the rows of `batch` with a single multi-row `INSERT`, leaving
+/// `batch` empty.
+pub(crate) async fn insert(
+    tx: &mut DatabaseTransaction<'_>,
+    dialect: Dialect,
+    table: &str,
+    columns: &[String],
+    batch: &mut Vec<Vec<Value>>,
+) -> Result<u64, Error> {
+    if batch.is_empty() {
+        return Ok(0);
+    }
+    let mut insert = InsertStmt::new(table).columns(columns);
+    for values in batch.drain(..) {
+        insert = insert.values(values.into_iter().map(Expr::value));
+    }
+    let result = insert
+        .to_query(dialect)
+        .map_err(|err| Error::Build(Box::new(err)))?
+        .execute(&mut *tx)
+        .await?;
+    Ok(result.rows_affected)
+}
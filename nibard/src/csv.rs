@@ -0,0 +1,368 @@
+use super::batch;
+use super::introspect::introspect;
+use super::Error;
+use futures::{Stream, StreamExt};
+use nibard_connection::{Database, DatabaseRow, Error as ConnectionError, Row};
+use nibard_shared::{Type, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+
+/// How values are written to and read from CSV.
+///
+/// `NULL` is written as `null`, an empty field by default, and fields equal
+/// to it are read back as `NULL`. Binary is base64, dates and timestamps use
+/// the ISO 8601 forms `Value::parse` reads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    null: String,
+    delimiter: u8,
+    batch_size: usize,
+    types: HashMap<String, Type>,
+    skip_invalid: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions::new()
+    }
+}
+
+impl CsvOptions {
+    pub fn new() -> CsvOptions {
+        CsvOptions {
+            null: String::new(),
+            delimiter: b',',
+            batch_size: 100,
+            types: HashMap::new(),
+            skip_invalid: false,
+        }
+    }
+
+    pub fn null(mut self, null: impl Into<String>) -> Self {
+        self.null = null.into();
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Most rows per `INSERT` on import, 100 by default.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Type of an imported column. Columns without one take the type of
+    /// the table's column.
+    pub fn column_type(mut self, column: impl Into<String>, ty: Type) -> Self {
+        self.types.insert(column.into(), ty);
+        self
+    }
+
+    /// Imports the valid lines even if others fail. By default nothing is
+    /// imported when a line fails.
+    pub fn skip_invalid(mut self) -> Self {
+        self.skip_invalid = true;
+        self
+    }
+
+    pub fn format<'a>(&'a self, value: &'a Value) -> Cow<'a, str> {
+        match value {
+            Value::Null => Cow::Borrowed(&self.null),
+            Value::Text(text) => Cow::Borrowed(text),
+            Value::SmallInt(i) => Cow::Owned(i.to_string()),
+            Value::Int(i) => Cow::Owned(i.to_string()),
+            Value::BigInt(i) => Cow::Owned(i.to_string()),
+            Value::Float(f) => Cow::Owned(f.to_string()),
+            Value::Real(f) => Cow::Owned(f.to_string()),
+            Value::Bool(b) => Cow::Borrowed(if *b { "true" } else { "false" }),
+            Value::Binary(bytes) => Cow::Owned(base64::encode(bytes)),
+            #[cfg(feature = "time")]
+            Value::Date(date) => Cow::Owned(date.format("%Y-%m-%d").to_string()),
+            #[cfg(feature = "time")]
+            Value::DateTime(date) => Cow::Owned(date.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+            #[cfg(feature = "json")]
+            Value::Json(json) => Cow::Owned(json.to_string()),
+        }
+    }
+
+    fn parse(&self, ty: Type, field: &str) -> Result<Value, String> {
+        if field == self.null {
+            return Ok(Value::Null);
+        }
+        match ty {
+            Type::Binary => base64::decode(field)
+                .map(Value::Binary)
+                .map_err(|err| err.to_string()),
+            ty => Value::parse(ty, field).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+/// Writes `rows`, eg. from `ExecutorExt::query`, as CSV with a header line
+/// and returns the number of rows written. Without rows nothing, not even
+/// the header, is written.
+pub async fn export<S, W>(rows: S, writer: W, options: &CsvOptions) -> Result<u64, Error>
+where
+    S: Stream<Item = Result<DatabaseRow, ConnectionError>>,
+    W: io::Write,
+{
+    let mut writer = ::csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .from_writer(writer);
+    let mut columns: Option<Vec<String>> = None;
+    let mut count = 0;
+
+    futures::pin_mut!(rows);
+    while let Some(row) = rows.next().await {
+        let row = row?;
+        let columns = match &columns {
+            Some(columns) => columns,
+            None => {
+                let names = row
+                    .columns()
+                    .iter()
+                    .map(|column| column.name.to_owned())
+                    .collect::<Vec<_>>();
+                writer
+                    .write_record(&names)
+                    .map_err(|err| Error::Csv(err.to_string()))?;
+                columns.get_or_insert(names)
+            }
+        };
+
+        let mut record = Vec::with_capacity(columns.len());
+        for column in columns {
+            record.push(row.try_get(column, None)?);
+        }
+        writer
+            .write_record(
+                record
+                    .iter()
+                    .map(|value| options.format(value).into_owned()),
+            )
+            .map_err(|err| Error::Csv(err.to_string()))?;
+        count += 1;
+    }
+
+    writer.flush()?;
+    Ok(count)
+}
+
+/// A line of a CSV import which could not be read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineError {
+    pub line: u64,
+    pub message: String,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImportReport {
+    pub inserted: u64,
+    pub errors: Vec<LineError>,
+}
+
+/// Inserts CSV with a header line naming the columns into `table`, in
+/// batches within one transaction. Lines which cannot be read are reported,
+/// and unless `CsvOptions::skip_invalid` is set they roll the import back.
+pub async fn import<R: io::Read>(
+    db: &Database,
+    table: &str,
+    reader: R,
+    options: &CsvOptions,
+) -> Result<ImportReport, Error> {
+    let dialect = db.dialect();
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .from_reader(reader);
+    let columns = reader
+        .headers()
+        .map_err(|err| Error::Csv(err.to_string()))?
+        .iter()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    let types = column_types(db, table, &columns, options).await?;
+
+    let per_batch = batch::rows_per_batch(options.batch_size, columns.len());
+    let mut report = ImportReport::default();
+    let mut rows = Vec::new();
+    let mut tx = db.begin().await?;
+
+    for (idx, record) in reader.records().enumerate() {
+        let values = record
+            .map_err(|err| (err.position().cloned(), err.to_string()))
+            .and_then(|record| {
+                let position = record.position().cloned();
+                types
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(ty, field)| options.parse(*ty, field))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| (position, err))
+            });
+
+        match values {
+            Ok(values) => {
+                if !report.errors.is_empty() && !options.skip_invalid {
+                    continue;
+                }
+                rows.push(values);
+                if rows.len() >= per_batch {
+                    report.inserted +=
+                        batch::insert(&mut tx, dialect, table, &columns, &mut rows).await?;
+                }
+            }
+            Err((position, message)) => report.errors.push(LineError {
+                line: position.map_or(idx as u64 + 2, |position| position.line()),
+                message,
+            }),
+        }
+    }
+
+    if !report.errors.is_empty() && !options.skip_invalid {
+        report.inserted = 0;
+        return Ok(report);
+    }
+    report.inserted += batch::insert(&mut tx, dialect, table, &columns, &mut rows).await?;
+    tx.commit().await?;
+
+    Ok(report)
+}
+
+async fn column_types(
+    db: &Database,
+    table: &str,
+    columns: &[String],
+    options: &CsvOptions,
+) -> Result<Vec<Type>, Error> {
+    let schema = if columns
+        .iter()
+        .all(|column| options.types.contains_key(column))
+    {
+        None
+    } else {
+        Some(introspect(db).await?)
+    };
+    let fields = schema
+        .as_ref()
+        .and_then(|schema| schema.tables.iter().find(|t| t.name == table))
+        .map(|table| table.fields.as_slice())
+        .unwrap_or_default();
+
+    columns
+        .iter()
+        .map(|column| {
+            options
+                .types
+                .get(column)
+                .copied()
+                .or_else(|| {
+                    fields
+                        .iter()
+                        .find(|field| field.name == column.as_str())
+                        .map(|field| field.ty)
+                })
+                .ok_or_else(|| Error::Csv(format!("unknown column {}.{}", table, column)))
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use super::*;
+    use crate::query::Query;
+    use nibard_connection::Executor;
+
+    async fn open() -> Database {
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        db.execute(
+            "CREATE TABLE readings(id INTEGER PRIMARY KEY, sensor TEXT, value REAL, mean DOUBLE)",
+        )
+        .await
+        .unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn import_floats() {
+        let db = open().await;
+        let csv = "id,sensor,value,mean\n1,a,0.5,1.25\n2,b,-3,\n3,c,2.75,1e3\n";
+        let report = import(&db, "readings", csv.as_bytes(), &CsvOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                inserted: 3,
+                errors: Vec::new()
+            }
+        );
+
+        let rows = Query::new(
+            "SELECT value, mean FROM readings ORDER BY id".to_owned(),
+            Vec::new(),
+        )
+        .fetch(&db)
+        .map(|row| {
+            let row = row.unwrap();
+            (
+                row.try_get("value", None).unwrap(),
+                row.try_get("mean", None).unwrap(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(
+            rows,
+            [
+                (Value::Float(0.5), Value::Float(1.25)),
+                (Value::Float(-3.0), Value::Null),
+                (Value::Float(2.75), Value::Float(1000.0)),
+            ]
+        );
+
+        let mut out = Vec::new();
+        let rows = Query::new(
+            "SELECT id, value FROM readings ORDER BY id".to_owned(),
+            Vec::new(),
+        )
+        .fetch(&db);
+        assert_eq!(export(rows, &mut out, &CsvOptions::new()).await.unwrap(), 3);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,value\n1,0.5\n2,-3\n3,2.75\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_float() {
+        let db = open().await;
+        let csv = "id,value\n1,0.5\n2,high\n";
+        let report = import(&db, "readings", csv.as_bytes(), &CsvOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(report.inserted, 0);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].line, 3);
+
+        let report = import(
+            &db,
+            "readings",
+            csv.as_bytes(),
+            &CsvOptions::new().skip_invalid(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.inserted, 1);
+    }
+}
@@ -0,0 +1,387 @@
+use super::batch;
+use super::introspect::introspect;
+use super::query::Query;
+use super::Error;
+use futures::TryStreamExt;
+use nibard_connection::{Database, DatabaseRow, Error as ConnectionError, Executor, Row};
+use nibard_dsl::ast::{Expr, InsertStmt};
+use nibard_dsl::create::{CreateTable, Schema};
+use nibard_dsl::{escape_identifier, StatementExt};
+use nibard_shared::{Dialect, Type, Value};
+use std::io;
+
+/// Rows per `INSERT` in a dump.
+const DUMP_BATCH: usize = 100;
+
+/// Writes the tables of `db`, their rows and indexes as SQL for `dialect`:
+/// tables in foreign key order, one multi-row `INSERT` per batch of rows,
+/// then the indexes and, on Pg, the sequences set past the copied keys.
+///
+/// Column types go through `Type`, so only what introspection can describe
+/// is dumped. Dates need the `time` feature to be written as dates.
+pub async fn dump<W: io::Write>(
+    db: &Database,
+    mut writer: W,
+    dialect: Dialect,
+) -> Result<(), Error> {
+    let schema = introspect(db).await?;
+    let (tables, indexes) = statements(&schema, dialect)?;
+    for sql in tables {
+        writeln!(writer, "{};", sql)?;
+    }
+
+    for table in ordered_tables(&schema)? {
+        let columns = column_names(table);
+        let rows = Query::new(select(table, db.dialect()), Vec::new()).fetch(db);
+        futures::pin_mut!(rows);
+        let mut batch = Vec::with_capacity(DUMP_BATCH);
+        while let Some(row) = rows.try_next().await? {
+            batch.push(decode(&row, table)?);
+            if batch.len() >= DUMP_BATCH {
+                write_insert(&mut writer, dialect, table, &columns, &mut batch)?;
+            }
+        }
+        write_insert(&mut writer, dialect, table, &columns, &mut batch)?;
+    }
+
+    for sql in indexes {
+        writeln!(writer, "{};", sql)?;
+    }
+    for table in &schema.tables {
+        for sql in reset_sequences(table, dialect) {
+            writeln!(writer, "{};", sql)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Copies the tables of `src`, with their rows and indexes, to `dst`, eg.
+/// from Sqlite to Pg, and returns the number of rows copied per table.
+///
+/// Tables are created unless they exist and filled in foreign key order
+/// within one transaction, rows ordered by primary key. Afterwards the
+/// sequences of auto increment columns continue after the copied keys.
+pub async fn copy_database(src: &Database, dst: &Database) -> Result<Vec<(String, u64)>, Error> {
+    let dialect = dst.dialect();
+    let schema = introspect(src).await?;
+    let (tables, indexes) = statements(&schema, dialect)?;
+
+    // MySQL commits DDL implicitly, so it runs ahead of the transaction.
+    for sql in &tables {
+        dst.execute(sql.as_str()).await?;
+    }
+
+    let mut copied = Vec::with_capacity(schema.tables.len());
+    let mut tx = dst.begin().await?;
+    for table in ordered_tables(&schema)? {
+        let columns = column_names(table);
+        let per_batch = batch::rows_per_batch(DUMP_BATCH, columns.len());
+        let rows = Query::new(select(table, src.dialect()), Vec::new()).fetch(src);
+        futures::pin_mut!(rows);
+        let mut batch = Vec::with_capacity(per_batch);
+        let mut count = 0;
+        while let Some(row) = rows.try_next().await? {
+            batch.push(decode(&row, table)?);
+            if batch.len() >= per_batch {
+                count += batch::insert(&mut tx, dialect, &table.name, &columns, &mut batch).await?;
+            }
+        }
+        count += batch::insert(&mut tx, dialect, &table.name, &columns, &mut batch).await?;
+        copied.push((table.name.to_string(), count));
+    }
+    for table in &schema.tables {
+        for sql in reset_sequences(table, dialect) {
+            (&mut tx).execute(sql.as_str()).await?;
+        }
+    }
+    tx.commit().await?;
+
+    for sql in &indexes {
+        dst.execute(sql.as_str()).await?;
+    }
+    Ok(copied)
+}
+
+fn ordered_tables<'a>(schema: &'a Schema<'static>) -> Result<Vec<&'a CreateTable<'static>>, Error> {
+    schema
+        .ordered_tables()
+        .map_err(|err| ConnectionError::Build(Box::new(err)).into())
+}
+
+/// The DDL for the tables and for the indexes of `schema`.
+fn statements(
+    schema: &Schema<'static>,
+    dialect: Dialect,
+) -> Result<(Vec<String>, Vec<String>), Error> {
+    let mut tables = schema
+        .statements(dialect)
+        .map_err(|err| ConnectionError::Build(Box::new(err)))?;
+    // `Schema::statements` lists the tables before the indexes.
+    let indexes = tables.split_off(schema.tables.len());
+    Ok((tables, indexes))
+}
+
+fn column_names(table: &CreateTable<'_>) -> Vec<String> {
+    table
+        .fields
+        .iter()
+        .map(|field| field.name.to_string())
+        .collect()
+}
+
+/// Selects the rows of `table`, every column but binary ones as text since
+/// the drivers decode few types natively.
+fn select(table: &CreateTable<'_>, dialect: Dialect) -> String {
+    let text = if dialect == Dialect::MySQL {
+        "CHAR"
+    } else {
+        "TEXT"
+    };
+    let columns = table
+        .fields
+        .iter()
+        .map(|field| {
+            let name = escape_identifier(&field.name, &dialect);
+            match field.ty {
+                Type::Binary => name,
+                _ => format!("CAST({name} AS {text}) AS {name}", name = name, text = text),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut sql = format!(
+        "SELECT {} FROM {}",
+        columns.join(", "),
+        escape_identifier(&table.name, &dialect)
+    );
+    let primary_key = table
+        .fields
+        .iter()
+        .filter(|field| field.primary_key)
+        .map(|field| escape_identifier(&field.name, &dialect))
+        .collect::<Vec<_>>();
+    if !primary_key.is_empty() {
+        sql.push_str(" ORDER BY ");
+        sql.push_str(&primary_key.join(", "));
+    }
+    sql
+}
+
+fn decode(row: &DatabaseRow, table: &CreateTable<'_>) -> Result<Vec<Value>, ConnectionError> {
+    table
+        .fields
+        .iter()
+        .map(|field| match row.try_get(&field.name, None)? {
+            Value::Text(text) if field.ty != Type::Binary => {
+                Value::parse(field.ty, &text).map_err(|err| {
+                    ConnectionError::Decode(format!("{}.{}: {}", table.name, field.name, err))
+                })
+            }
+            value => Ok(value),
+        })
+        .collect()
+}
+
+fn write_insert<W: io::Write>(
+    writer: &mut W,
+    dialect: Dialect,
+    table: &CreateTable<'_>,
+    columns: &[String],
+    batch: &mut Vec<Vec<Value>>,
+) -> Result<(), Error> {
+    if batch.is_empty() {
+        return Ok(());
+    }
+    let mut insert = InsertStmt::new(table.name.as_ref()).columns(columns);
+    for values in batch.drain(..) {
+        insert = insert.values(values.into_iter().map(Expr::value));
+    }
+    let sql = insert
+        .to_sql_literal(dialect)
+        .map_err(|err| ConnectionError::Build(Box::new(err)))?;
+    writeln!(writer, "{};", sql)?;
+    Ok(())
+}
+
+/// Moves the sequences of the auto increment columns of `table` past the
+/// largest key. Sqlite and MySQL keep track of explicitly inserted keys
+/// themselves.
+fn reset_sequences(table: &CreateTable<'_>, dialect: Dialect) -> Vec<String> {
+    if dialect != Dialect::Pg {
+        return Vec::new();
+    }
+    table
+        .fields
+        .iter()
+        .filter(|field| field.ty.is_auto())
+        .map(|field| {
+            let table = escape_identifier(&table.name, &dialect);
+            // pg_get_serial_sequence parses the table name as an identifier
+            // but takes the column name as is
+            format!(
+                "SELECT setval(pg_get_serial_sequence('{table_literal}', '{column_literal}'), \
+                 COALESCE(MAX({column}), 0) + 1, false) FROM {table}",
+                table_literal = table.replace('\'', "''"),
+                column_literal = field.name.replace('\'', "''"),
+                column = escape_identifier(&field.name, &dialect),
+                table = table
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nibard_dsl::create::{Column, CreateIndex, ForeignKey};
+
+    fn schema() -> Schema<'static> {
+        Schema::new()
+            .table(
+                CreateTable::new("items")
+                    .column(Column::new("id", Type::BigAuto).primary_key())
+                    .column(
+                        Column::new("product_id", Type::Int)
+                            .foreign_key(ForeignKey::new("products", "id")),
+                    ),
+            )
+            .table(
+                CreateTable::new("products")
+                    .column(Column::new("id", Type::Auto).primary_key())
+                    .column(Column::new("price", Type::Real))
+                    .column(Column::new("weight", Type::Float(53))),
+            )
+            .index(CreateIndex::new(
+                "items",
+                "items_product_idx",
+                vec!["product_id".into()],
+            ))
+    }
+
+    #[test]
+    fn table_and_index_statements() {
+        let (tables, indexes) = statements(&schema(), Dialect::Pg).unwrap();
+        assert_eq!(
+            tables,
+            [
+                "CREATE TABLE IF NOT EXISTS products(id SERIAL PRIMARY KEY, \
+                 price REAL DEFAULT NULL, weight FLOAT(53) DEFAULT NULL)",
+                "CREATE TABLE IF NOT EXISTS items(id BIGSERIAL PRIMARY KEY, \
+                 product_id INTEGER DEFAULT NULL, FOREIGN KEY (product_id) REFERENCES products(id))",
+            ]
+        );
+        assert_eq!(
+            indexes,
+            ["CREATE INDEX IF NOT EXISTS items_product_idx ON items (product_id)"]
+        );
+    }
+
+    #[test]
+    fn inserts() {
+        let schema = schema();
+        let products = &schema.tables[1];
+        let mut batch = vec![
+            vec![Value::Int(1), Value::Real(9.5), Value::Float(0.25)],
+            vec![Value::Int(2), Value::Null, Value::Float(-3.0)],
+        ];
+        let mut out = Vec::new();
+        write_insert(
+            &mut out,
+            Dialect::Sqlite,
+            products,
+            &column_names(products),
+            &mut batch,
+        )
+        .unwrap();
+        assert!(batch.is_empty());
+        // an empty batch writes nothing
+        write_insert(&mut out, Dialect::Sqlite, products, &[], &mut batch).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "INSERT INTO products (id, price, weight) VALUES (1, 9.5, 0.25), (2, NULL, -3.0);\n"
+        );
+    }
+
+    #[test]
+    fn selects() {
+        let schema = schema();
+        assert_eq!(
+            select(&schema.tables[1], Dialect::Pg),
+            "SELECT CAST(\"id\" AS TEXT) AS \"id\", CAST(\"price\" AS TEXT) AS \"price\", \
+             CAST(\"weight\" AS TEXT) AS \"weight\" FROM \"products\" ORDER BY \"id\""
+        );
+        let blobs = CreateTable::new("blobs").column(Column::new("data", Type::Binary));
+        assert_eq!(select(&blobs, Dialect::MySQL), "SELECT `data` FROM `blobs`");
+    }
+
+    #[test]
+    fn sequences() {
+        let schema = schema();
+        assert_eq!(
+            reset_sequences(&schema.tables[0], Dialect::Pg),
+            ["SELECT setval(pg_get_serial_sequence('\"items\"', 'id'), \
+              COALESCE(MAX(\"id\"), 0) + 1, false) FROM \"items\""]
+        );
+        assert_eq!(
+            reset_sequences(&schema.tables[1], Dialect::Pg),
+            [
+                "SELECT setval(pg_get_serial_sequence('\"products\"', 'id'), \
+              COALESCE(MAX(\"id\"), 0) + 1, false) FROM \"products\""
+            ]
+        );
+        assert!(reset_sequences(&schema.tables[0], Dialect::Sqlite).is_empty());
+        assert!(reset_sequences(&schema.tables[0], Dialect::MySQL).is_empty());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn copy_floats() {
+        use futures::StreamExt;
+
+        let src = Database::open("sqlite::memory:").await.unwrap();
+        for sql in [
+            "CREATE TABLE products(id INTEGER PRIMARY KEY, name TEXT, price REAL, weight DOUBLE)",
+            "INSERT INTO products (id, name, price, weight) \
+             VALUES (1, 'lamp', 9.5, 0.25), (2, 'chair', 20, NULL)",
+        ] {
+            src.execute(sql).await.unwrap();
+        }
+        let dst = Database::open("sqlite::memory:").await.unwrap();
+
+        let copied = copy_database(&src, &dst).await.unwrap();
+        assert_eq!(copied, [("products".to_owned(), 2)]);
+
+        let rows = Query::new(
+            "SELECT id, name, price, weight FROM products ORDER BY id".to_owned(),
+            Vec::new(),
+        )
+        .fetch(&dst)
+        .map(|row| {
+            let row = row.unwrap();
+            ["id", "name", "price", "weight"]
+                .iter()
+                .map(|column| row.try_get(column, None).unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(
+            rows,
+            [
+                vec![
+                    Value::BigInt(1),
+                    Value::Text("lamp".into()),
+                    Value::Float(9.5),
+                    Value::Float(0.25)
+                ],
+                vec![
+                    Value::BigInt(2),
+                    Value::Text("chair".into()),
+                    Value::Float(20.0),
+                    Value::Null
+                ],
+            ]
+        );
+    }
+}
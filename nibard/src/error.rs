@@ -0,0 +1,19 @@
+use nibard_connection::Error as ConnectionError;
+use thiserror::Error as ThisError;
+
+/// Errors of the tools built on top of a connection: migrations, fixtures,
+/// CSV and dumps. Failures of the connection itself are wrapped as
+/// `Connection`.
+#[derive(Debug, ThisError)]
+pub enum Error {
+    #[error(transparent)]
+    Connection(#[from] ConnectionError),
+    #[error("migration error: {0}")]
+    Migration(String),
+    #[error("fixture error: {0}")]
+    Fixture(String),
+    #[error("csv error: {0}")]
+    Csv(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
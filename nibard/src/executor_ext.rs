@@ -1,9 +1,16 @@
-use super::query::StatementQuery;
+#[cfg(feature = "json")]
+use super::explain::QueryPlan;
+use super::{introspect, soft_delete};
+#[cfg(feature = "json")]
+use futures::TryStreamExt;
 use futures::{
-    future::{BoxFuture, FutureExt},
-    stream::{BoxStream, StreamExt},
+    future::{self, BoxFuture, FutureExt},
+    stream::{self, BoxStream, StreamExt},
 };
 use nibard_connection::{DatabaseRow, Error, Executor, QueryResult};
+use nibard_dsl::create::Schema;
+#[cfg(feature = "json")]
+use nibard_dsl::explain::Explain;
 use nibard_dsl::{DefaultContext, Statement};
 
 pub trait ExecutorExt<'c>: Executor<'c> + Send {
@@ -14,7 +21,10 @@ pub trait ExecutorExt<'c>: Executor<'c> + Send {
     where
         Self: Sized + 'c,
     {
-        stmt.to_query(self.dialect()).execute(self).boxed()
+        match soft_delete::to_query(stmt, self.dialect(), self.soft_delete()) {
+            Ok(query) => query.execute(self).boxed(),
+            Err(err) => future::ready(Err(Error::Build(Box::new(err)))).boxed(),
+        }
     }
 
     fn query<S: Statement<DefaultContext>>(
@@ -24,7 +34,10 @@ pub trait ExecutorExt<'c>: Executor<'c> + Send {
     where
         Self: Sized + 'c,
     {
-        stmt.to_query(self.dialect()).fetch(self).boxed()
+        match soft_delete::to_query(stmt, self.dialect(), self.soft_delete()) {
+            Ok(query) => query.fetch(self).boxed(),
+            Err(err) => stream::once(future::ready(Err(Error::Build(Box::new(err))))).boxed(),
+        }
     }
 
     fn query_one<S: Statement<DefaultContext>>(
@@ -34,8 +47,67 @@ pub trait ExecutorExt<'c>: Executor<'c> + Send {
     where
         Self: Sized + 'c,
     {
-        stmt.to_query(self.dialect()).fetch_one(self).boxed()
+        match soft_delete::to_query(stmt, self.dialect(), self.soft_delete()) {
+            Ok(query) => query.fetch_one(self).boxed(),
+            Err(err) => future::ready(Err(Error::Build(Box::new(err)))).boxed(),
+        }
     }
+
+    /// Fetches the plan the database would use for `stmt`.
+    #[cfg(feature = "json")]
+    fn explain<S: Statement<DefaultContext>>(
+        self,
+        stmt: S,
+    ) -> BoxFuture<'c, Result<QueryPlan, Error>>
+    where
+        Self: Sized + 'c,
+    {
+        explain_plan(self, Explain::new(stmt))
+    }
+
+    /// Runs `stmt` and fetches the plan annotated with actual row counts.
+    /// Not supported by Sqlite.
+    #[cfg(feature = "json")]
+    fn explain_analyze<S: Statement<DefaultContext>>(
+        self,
+        stmt: S,
+    ) -> BoxFuture<'c, Result<QueryPlan, Error>>
+    where
+        Self: Sized + 'c,
+    {
+        explain_plan(self, Explain::new(stmt).analyze())
+    }
+
+    /// Reads the tables, columns, keys and indexes of the database, see
+    /// [`introspect::introspect`].
+    fn introspect(self) -> BoxFuture<'c, Result<Schema<'static>, Error>>
+    where
+        Self: Sized + Copy + 'c,
+    {
+        introspect::introspect(self).boxed()
+    }
+}
+
+#[cfg(feature = "json")]
+fn explain_plan<'c, E, S>(
+    executor: E,
+    explain: Explain<S>,
+) -> BoxFuture<'c, Result<QueryPlan, Error>>
+where
+    E: Executor<'c> + Send + 'c,
+    S: Statement<DefaultContext>,
+{
+    let dialect = executor.dialect();
+    let query = match soft_delete::to_query(explain, dialect, executor.soft_delete()) {
+        Ok(query) => query,
+        Err(err) => return future::ready(Err(Error::Build(Box::new(err)))).boxed(),
+    };
+
+    async move {
+        let rows = query.fetch(executor).try_collect::<Vec<_>>().await?;
+        QueryPlan::from_rows(dialect, &rows)
+    }
+    .boxed()
 }
 
 impl<'c, E> ExecutorExt<'c> for E where E: Executor<'c> + Send {}
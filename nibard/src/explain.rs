@@ -0,0 +1,615 @@
+use nibard_connection::{DatabaseRow, Error, Row};
+use nibard_shared::{Dialect, Value};
+use serde_json::Value as JsonValue;
+
+/// A query plan as reported by the database, normalized into a tree of
+/// nodes so the same assertions work against every dialect.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct QueryPlan {
+    pub nodes: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlanNode {
+    /// The operation as the database names it, eg. `Seq Scan` for Pg, the
+    /// access type for MySQL or the full detail line for Sqlite.
+    pub operation: String,
+    pub table: Option<String>,
+    pub index: Option<String>,
+    /// Set when every row of `table` is read without the help of an index.
+    pub full_scan: bool,
+    pub estimated_rows: Option<f64>,
+    /// Only reported by analyzed plans.
+    pub actual_rows: Option<f64>,
+    pub children: Vec<PlanNode>,
+}
+
+impl QueryPlan {
+    /// Decodes the rows returned by an EXPLAIN statement built with
+    /// `nibard_dsl::explain::Explain` for `dialect`.
+    pub fn from_rows(dialect: Dialect, rows: &[DatabaseRow]) -> Result<QueryPlan, Error> {
+        match dialect {
+            Dialect::Sqlite => {
+                let mut lines = Vec::with_capacity(rows.len());
+                for row in rows {
+                    let id = int_value(row.try_get("id", None)?)?;
+                    let parent = int_value(row.try_get("parent", None)?)?;
+                    let detail = match row.try_get("detail", None)? {
+                        Value::Text(detail) => detail,
+                        other => return Err(unexpected("detail", &other)),
+                    };
+                    lines.push((id, parent, detail));
+                }
+                Ok(QueryPlan::parse_sqlite(lines.iter().map(
+                    |(id, parent, detail)| (*id, *parent, detail.as_str()),
+                )))
+            }
+            Dialect::Pg => {
+                let row = rows
+                    .first()
+                    .ok_or_else(|| Error::Decode("empty query plan".to_owned()))?;
+                QueryPlan::parse_pg(&json_value(row)?)
+            }
+            Dialect::MySQL => {
+                let row = rows
+                    .first()
+                    .ok_or_else(|| Error::Decode("empty query plan".to_owned()))?;
+                match first_column(row)? {
+                    // EXPLAIN ANALYZE only has a text format
+                    Value::Text(text) if !text.trim_start().starts_with('{') => {
+                        Ok(QueryPlan::parse_mysql_tree(&text))
+                    }
+                    _ => QueryPlan::parse_mysql(&json_value(row)?),
+                }
+            }
+        }
+    }
+
+    /// Builds the plan from the `(id, parent, detail)` rows of
+    /// `EXPLAIN QUERY PLAN`.
+    pub fn parse_sqlite<'a, I>(rows: I) -> QueryPlan
+    where
+        I: IntoIterator<Item = (i64, i64, &'a str)>,
+    {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+
+        fn children(rows: &[(i64, i64, &str)], parent: i64) -> Vec<PlanNode> {
+            rows.iter()
+                .filter(|(_, p, _)| *p == parent)
+                .map(|(id, _, detail)| {
+                    let mut node = sqlite_node(detail);
+                    node.children = children(rows, *id);
+                    node
+                })
+                .collect()
+        }
+
+        QueryPlan {
+            nodes: children(&rows, 0),
+        }
+    }
+
+    /// Builds the plan from the document returned by
+    /// `EXPLAIN (FORMAT JSON)`.
+    pub fn parse_pg(json: &JsonValue) -> Result<QueryPlan, Error> {
+        let plans = match json {
+            JsonValue::Array(plans) => plans.as_slice(),
+            _ => std::slice::from_ref(json),
+        };
+
+        let nodes = plans
+            .iter()
+            .map(|plan| match plan.get("Plan") {
+                Some(plan) => pg_node(plan),
+                None => Err(Error::Decode("missing Plan in query plan".to_owned())),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(QueryPlan { nodes })
+    }
+
+    /// Builds the plan from the document returned by `EXPLAIN FORMAT=JSON`.
+    pub fn parse_mysql(json: &JsonValue) -> Result<QueryPlan, Error> {
+        if json.get("query_block").is_none() {
+            return Err(Error::Decode(
+                "missing query_block in query plan".to_owned(),
+            ));
+        }
+        Ok(QueryPlan {
+            nodes: mysql_nodes(json),
+        })
+    }
+
+    /// Builds the plan from the indented tree returned by MySQL's
+    /// `EXPLAIN ANALYZE`.
+    pub fn parse_mysql_tree(text: &str) -> QueryPlan {
+        let mut stack: Vec<(usize, PlanNode)> = Vec::new();
+        let mut nodes = Vec::new();
+
+        fn pop(stack: &mut Vec<(usize, PlanNode)>, nodes: &mut Vec<PlanNode>) {
+            if let Some((_, node)) = stack.pop() {
+                match stack.last_mut() {
+                    Some((_, parent)) => parent.children.push(node),
+                    None => nodes.push(node),
+                }
+            }
+        }
+
+        for line in text.lines() {
+            let depth = match line.find("-> ") {
+                Some(depth) => depth,
+                None => continue,
+            };
+            while matches!(stack.last(), Some((d, _)) if *d >= depth) {
+                pop(&mut stack, &mut nodes);
+            }
+            stack.push((depth, mysql_tree_node(&line[depth + 3..])));
+        }
+        while !stack.is_empty() {
+            pop(&mut stack, &mut nodes);
+        }
+
+        QueryPlan { nodes }
+    }
+
+    /// Iterates every node of the plan, depth first.
+    pub fn iter(&self) -> Nodes<'_> {
+        Nodes {
+            stack: self.nodes.iter().rev().collect(),
+        }
+    }
+
+    pub fn full_scans(&self) -> impl Iterator<Item = &PlanNode> {
+        self.iter().filter(|node| node.full_scan)
+    }
+
+    pub fn has_full_scan(&self) -> bool {
+        self.full_scans().next().is_some()
+    }
+
+    pub fn uses_index(&self, index: &str) -> bool {
+        self.iter().any(|node| node.index.as_deref() == Some(index))
+    }
+}
+
+pub struct Nodes<'a> {
+    stack: Vec<&'a PlanNode>,
+}
+
+impl<'a> Iterator for Nodes<'a> {
+    type Item = &'a PlanNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter().rev());
+        Some(node)
+    }
+}
+
+fn unexpected(column: &str, value: &Value) -> Error {
+    Error::Decode(format!("unexpected value for {}: {:?}", column, value))
+}
+
+fn int_value(value: Value) -> Result<i64, Error> {
+    match value {
+        Value::SmallInt(i) => Ok(i as i64),
+        Value::Int(i) => Ok(i as i64),
+        Value::BigInt(i) => Ok(i),
+        other => Err(unexpected("id", &other)),
+    }
+}
+
+fn first_column(row: &DatabaseRow) -> Result<Value, Error> {
+    let name = match row.columns().first() {
+        Some(column) => column.name.to_owned(),
+        None => return Err(Error::Decode("empty query plan".to_owned())),
+    };
+    row.try_get(&name, None)
+}
+
+fn json_value(row: &DatabaseRow) -> Result<JsonValue, Error> {
+    match first_column(row)? {
+        Value::Json(json) => Ok(json),
+        Value::Text(text) => {
+            serde_json::from_str(&text).map_err(|err| Error::Decode(err.to_string()))
+        }
+        other => Err(unexpected("query plan", &other)),
+    }
+}
+
+fn sqlite_node(detail: &str) -> PlanNode {
+    let mut node = PlanNode {
+        operation: detail.to_owned(),
+        ..Default::default()
+    };
+
+    let (scan, rest) = if let Some(rest) = detail.strip_prefix("SCAN ") {
+        (true, rest)
+    } else if let Some(rest) = detail.strip_prefix("SEARCH ") {
+        (false, rest)
+    } else {
+        return node;
+    };
+    // Sqlite before 3.36 writes `SCAN TABLE users`
+    let rest = rest.strip_prefix("TABLE ").unwrap_or(rest);
+    if rest.starts_with("CONSTANT ROW") || rest.starts_with("SUBQUERY") || rest.starts_with('(') {
+        return node;
+    }
+
+    let mut words = rest.split_whitespace();
+    node.table = words.next().map(str::to_owned);
+    while let Some(word) = words.next() {
+        if word == "INDEX" {
+            node.index = words.next().map(str::to_owned);
+        } else if word == "KEY" {
+            node.index = Some("PRIMARY KEY".to_owned());
+        }
+    }
+    node.full_scan = scan && node.index.is_none();
+    node
+}
+
+fn pg_node(plan: &JsonValue) -> Result<PlanNode, Error> {
+    let operation = plan
+        .get("Node Type")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| Error::Decode("missing Node Type in query plan".to_owned()))?;
+    let string = |key: &str| plan.get(key).and_then(JsonValue::as_str).map(str::to_owned);
+
+    let children = match plan.get("Plans") {
+        Some(JsonValue::Array(plans)) => plans.iter().map(pg_node).collect::<Result<_, _>>()?,
+        _ => Vec::new(),
+    };
+
+    Ok(PlanNode {
+        operation: operation.to_owned(),
+        table: string("Relation Name"),
+        index: string("Index Name"),
+        full_scan: operation == "Seq Scan",
+        estimated_rows: plan.get("Plan Rows").and_then(JsonValue::as_f64),
+        actual_rows: plan.get("Actual Rows").and_then(JsonValue::as_f64),
+        children,
+    })
+}
+
+/// MySQL nests tables in operation objects (`nested_loop`,
+/// `ordering_operation`, ...) whose names are only partly documented, so
+/// every object on the way to a `table` becomes a node and branches without
+/// tables are dropped.
+fn mysql_nodes(json: &JsonValue) -> Vec<PlanNode> {
+    match json {
+        JsonValue::Array(items) => items.iter().flat_map(mysql_nodes).collect(),
+        JsonValue::Object(map) => map
+            .iter()
+            .filter_map(|(key, value)| {
+                if key == "table" {
+                    return Some(mysql_table(value));
+                }
+                let children = mysql_nodes(value);
+                match value {
+                    // arrays such as `nested_loop` are a single operation
+                    JsonValue::Array(_) | JsonValue::Object(_) if !children.is_empty() => {
+                        Some(PlanNode {
+                            operation: key.clone(),
+                            children,
+                            ..Default::default()
+                        })
+                    }
+                    _ => None,
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn mysql_table(table: &JsonValue) -> PlanNode {
+    let string = |key: &str| {
+        table
+            .get(key)
+            .and_then(JsonValue::as_str)
+            .map(str::to_owned)
+    };
+    let operation = string("access_type").unwrap_or_default();
+
+    PlanNode {
+        full_scan: operation == "ALL",
+        operation,
+        table: string("table_name"),
+        index: string("key"),
+        estimated_rows: table
+            .get("rows_examined_per_scan")
+            .and_then(JsonValue::as_f64),
+        actual_rows: None,
+        children: mysql_nodes(table),
+    }
+}
+
+fn mysql_tree_node(line: &str) -> PlanNode {
+    // -> Index lookup on u using idx_email (email='a')  (cost=0.35 rows=1) (actual time=0.01..0.01 rows=1 loops=1)
+    let (operation, stats) = match line.find("  (") {
+        Some(idx) => (&line[..idx], &line[idx..]),
+        None => (line, ""),
+    };
+
+    let word_after = |needle: &str| {
+        operation
+            .find(needle)
+            .map(|idx| &operation[idx + needle.len()..])
+            .and_then(|rest| rest.split_whitespace().next())
+            .map(|word| word.trim_end_matches(':').to_owned())
+    };
+
+    let rows = |section: &str| {
+        stats
+            .find(section)
+            .map(|idx| &stats[idx..])
+            .and_then(|rest| rest.find("rows=").map(|idx| &rest[idx + 5..]))
+            .and_then(|rest| rest.split([' ', ')']).next())
+            .and_then(|rows| rows.parse::<f64>().ok())
+    };
+
+    PlanNode {
+        operation: operation.to_owned(),
+        table: word_after(" on "),
+        index: word_after(" using "),
+        full_scan: operation.starts_with("Table scan on "),
+        estimated_rows: rows("(cost="),
+        actual_rows: rows("(actual "),
+        children: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn operations(plan: &QueryPlan) -> Vec<&str> {
+        plan.iter().map(|node| node.operation.as_str()).collect()
+    }
+
+    #[test]
+    fn sqlite() {
+        let plan = QueryPlan::parse_sqlite(vec![
+            (3, 0, "SEARCH users USING INDEX users_email_idx (email=?)"),
+            (8, 0, "SCAN TABLE posts"),
+            (12, 0, "CORRELATED SCALAR SUBQUERY 1"),
+            (16, 12, "SEARCH teams USING INTEGER PRIMARY KEY (rowid=?)"),
+            (30, 0, "USE TEMP B-TREE FOR ORDER BY"),
+        ]);
+        assert_eq!(plan.nodes.len(), 4);
+        assert_eq!(
+            operations(&plan),
+            vec![
+                "SEARCH users USING INDEX users_email_idx (email=?)",
+                "SCAN TABLE posts",
+                "CORRELATED SCALAR SUBQUERY 1",
+                "SEARCH teams USING INTEGER PRIMARY KEY (rowid=?)",
+                "USE TEMP B-TREE FOR ORDER BY",
+            ]
+        );
+        assert_eq!(plan.nodes[0].table.as_deref(), Some("users"));
+        assert!(plan.uses_index("users_email_idx"));
+        assert!(plan.uses_index("PRIMARY KEY"));
+        assert_eq!(
+            plan.full_scans()
+                .map(|node| node.table.as_deref())
+                .collect::<Vec<_>>(),
+            vec![Some("posts")]
+        );
+        assert_eq!(plan.nodes[2].children[0].table.as_deref(), Some("teams"));
+        assert_eq!(plan.nodes[3].table, None);
+
+        assert_eq!(
+            QueryPlan::parse_sqlite(Vec::new()),
+            QueryPlan { nodes: Vec::new() }
+        );
+        // rows pointing at a missing parent are not part of the tree
+        let plan = QueryPlan::parse_sqlite(vec![(4, 2, "SCAN users")]);
+        assert!(plan.nodes.is_empty());
+        let plan = QueryPlan::parse_sqlite(vec![(2, 0, "SCAN CONSTANT ROW")]);
+        assert!(!plan.has_full_scan());
+    }
+
+    #[test]
+    fn pg() {
+        let json = json!([{
+            "Plan": {
+                "Node Type": "Hash Join",
+                "Parallel Aware": false,
+                "Join Type": "Inner",
+                "Startup Cost": 1.04,
+                "Total Cost": 2.1,
+                "Plan Rows": 3,
+                "Plan Width": 36,
+                "Actual Rows": 2,
+                "Hash Cond": "(p.owner_id = u.id)",
+                "Plans": [
+                    {
+                        "Node Type": "Seq Scan",
+                        "Parent Relationship": "Outer",
+                        "Relation Name": "posts",
+                        "Alias": "p",
+                        "Plan Rows": 3,
+                        "Actual Rows": 3
+                    },
+                    {
+                        "Node Type": "Hash",
+                        "Parent Relationship": "Inner",
+                        "Plan Rows": 2,
+                        "Plans": [{
+                            "Node Type": "Index Scan",
+                            "Parent Relationship": "Outer",
+                            "Scan Direction": "Forward",
+                            "Index Name": "users_pkey",
+                            "Relation Name": "users",
+                            "Alias": "u",
+                            "Plan Rows": 2
+                        }]
+                    }
+                ]
+            },
+            "Planning Time": 0.1,
+            "Execution Time": 0.05
+        }]);
+        let plan = QueryPlan::parse_pg(&json).unwrap();
+        assert_eq!(
+            operations(&plan),
+            vec!["Hash Join", "Seq Scan", "Hash", "Index Scan"]
+        );
+        assert_eq!(plan.nodes[0].estimated_rows, Some(3.0));
+        assert_eq!(plan.nodes[0].actual_rows, Some(2.0));
+        assert_eq!(plan.nodes[0].children[1].actual_rows, None);
+        assert!(plan.uses_index("users_pkey"));
+        assert_eq!(
+            plan.full_scans()
+                .map(|node| node.table.as_deref())
+                .collect::<Vec<_>>(),
+            vec![Some("posts")]
+        );
+
+        // a single plan without the surrounding array
+        let plan = QueryPlan::parse_pg(&json!({ "Plan": { "Node Type": "Result" } })).unwrap();
+        assert_eq!(operations(&plan), vec!["Result"]);
+
+        assert!(QueryPlan::parse_pg(&json!([])).unwrap().nodes.is_empty());
+        assert!(matches!(
+            QueryPlan::parse_pg(&json!([{ "Query": "SELECT 1" }])),
+            Err(Error::Decode(_))
+        ));
+        assert!(matches!(
+            QueryPlan::parse_pg(&json!([{ "Plan": { "Plans": [] } }])),
+            Err(Error::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn mysql() {
+        let json = json!({
+            "query_block": {
+                "select_id": 1,
+                "cost_info": { "query_cost": "1.45" },
+                "nested_loop": [
+                    {
+                        "table": {
+                            "table_name": "u",
+                            "access_type": "ALL",
+                            "rows_examined_per_scan": 3,
+                            "rows_produced_per_join": 3,
+                            "filtered": "100.00",
+                            "cost_info": { "read_cost": "0.25", "eval_cost": "0.30" },
+                            "used_columns": ["id", "email"]
+                        }
+                    },
+                    {
+                        "table": {
+                            "table_name": "p",
+                            "access_type": "ref",
+                            "possible_keys": ["posts_owner_idx"],
+                            "key": "posts_owner_idx",
+                            "used_key_parts": ["owner_id"],
+                            "key_length": "5",
+                            "ref": ["test.u.id"],
+                            "rows_examined_per_scan": 1,
+                            "filtered": "100.00"
+                        }
+                    }
+                ]
+            }
+        });
+        let plan = QueryPlan::parse_mysql(&json).unwrap();
+        assert_eq!(
+            operations(&plan),
+            vec!["query_block", "nested_loop", "ALL", "ref"]
+        );
+        let tables = &plan.nodes[0].children[0].children;
+        assert_eq!(tables[0].table.as_deref(), Some("u"));
+        assert!(tables[0].full_scan);
+        assert_eq!(tables[0].estimated_rows, Some(3.0));
+        assert_eq!(tables[1].index.as_deref(), Some("posts_owner_idx"));
+        assert!(!tables[1].full_scan);
+
+        // no tables, eg. `EXPLAIN SELECT 1`
+        let plan = QueryPlan::parse_mysql(&json!({
+            "query_block": { "select_id": 1, "message": "No tables used" }
+        }))
+        .unwrap();
+        assert!(plan.nodes.is_empty());
+
+        assert!(matches!(
+            QueryPlan::parse_mysql(&json!({ "select_id": 1 })),
+            Err(Error::Decode(_))
+        ));
+        assert!(matches!(
+            QueryPlan::parse_mysql(&json!([])),
+            Err(Error::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn mysql_tree() {
+        let text = "\
+-> Nested loop inner join  (cost=1.45 rows=3) (actual time=0.040..0.052 rows=3 loops=1)
+    -> Filter: (u.email is not null)  (cost=0.55 rows=3) (actual time=0.025..0.030 rows=3 loops=1)
+        -> Table scan on u  (cost=0.55 rows=3) (actual time=0.024..0.028 rows=3 loops=1)
+    -> Index lookup on p using posts_owner_idx (owner_id=u.id)  (cost=0.30 rows=1) (actual time=0.005..0.006 rows=1 loops=3)
+";
+        let plan = QueryPlan::parse_mysql_tree(text);
+        assert_eq!(plan.nodes.len(), 1);
+        assert_eq!(
+            operations(&plan),
+            vec![
+                "Nested loop inner join",
+                "Filter: (u.email is not null)",
+                "Table scan on u",
+                "Index lookup on p using posts_owner_idx (owner_id=u.id)",
+            ]
+        );
+        let root = &plan.nodes[0];
+        assert_eq!(root.estimated_rows, Some(3.0));
+        assert_eq!(root.actual_rows, Some(3.0));
+        assert_eq!(root.children.len(), 2);
+
+        let scan = &root.children[0].children[0];
+        assert_eq!(scan.table.as_deref(), Some("u"));
+        assert!(scan.full_scan);
+
+        let lookup = &root.children[1];
+        assert_eq!(lookup.table.as_deref(), Some("p"));
+        assert_eq!(lookup.index.as_deref(), Some("posts_owner_idx"));
+        assert_eq!(lookup.estimated_rows, Some(1.0));
+        assert_eq!(lookup.actual_rows, Some(1.0));
+
+        assert!(QueryPlan::parse_mysql_tree("").nodes.is_empty());
+        assert!(QueryPlan::parse_mysql_tree("EXPLAIN\nno plan here")
+            .nodes
+            .is_empty());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn sqlite_rows() {
+        use crate::prelude::*;
+        use nibard_connection::Database;
+        use nibard_dsl::prelude::*;
+
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        db.execute("CREATE TABLE users(id INTEGER PRIMARY KEY, email TEXT)")
+            .await
+            .unwrap();
+        db.execute("CREATE INDEX users_email_idx ON users (email)")
+            .await
+            .unwrap();
+
+        let plan = db
+            .explain("users".select("id").filter("email".eql("a")))
+            .await
+            .unwrap();
+        assert!(plan.uses_index("users_email_idx"));
+        assert!(!plan.has_full_scan());
+
+        let plan = db.explain("users".select("id")).await.unwrap();
+        assert!(plan.has_full_scan());
+    }
+}
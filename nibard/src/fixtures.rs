@@ -0,0 +1,638 @@
+use super::batch;
+use super::introspect::introspect;
+use super::query::{Query, StatementQuery};
+use super::Error;
+use nibard_connection::{Database, DatabaseTransaction, Error as ConnectionError, Row};
+use nibard_dsl::ast::{DeleteStmt, Expr, InsertStmt};
+use nibard_dsl::create::{CreateTable, Schema};
+use nibard_shared::{Dialect, Type, Value};
+use std::collections::HashMap;
+#[cfg(any(
+    feature = "fixtures-json",
+    feature = "fixtures-yaml",
+    feature = "fixtures-csv"
+))]
+use std::path::Path;
+
+/// Field naming a fixture row, so other rows can refer to its key.
+pub const KEY_FIELD: &str = "@key";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FixtureValue {
+    Value(Value),
+    /// The primary key of the row named by `@key`.
+    Ref(String),
+}
+
+impl FixtureValue {
+    /// Reads a field from a fixture file. `@alice` refers to the row named
+    /// `alice` and `@@` escapes a leading `@`.
+    pub fn from_text(text: &str) -> FixtureValue {
+        match text.strip_prefix('@') {
+            Some(rest) if rest.starts_with('@') => {
+                FixtureValue::Value(Value::Text(rest.to_owned()))
+            }
+            Some(key) => FixtureValue::Ref(key.to_owned()),
+            None => FixtureValue::Value(Value::Text(text.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FixtureRow {
+    key: Option<String>,
+    values: Vec<(String, FixtureValue)>,
+}
+
+impl FixtureRow {
+    pub fn new() -> FixtureRow {
+        FixtureRow::default()
+    }
+
+    /// Names the row, making its primary key available to `set_ref`.
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    pub fn set(mut self, column: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.values
+            .push((column.into(), FixtureValue::Value(value.into())));
+        self
+    }
+
+    pub fn set_ref(mut self, column: impl Into<String>, key: impl Into<String>) -> Self {
+        self.values
+            .push((column.into(), FixtureValue::Ref(key.into())));
+        self
+    }
+
+    #[cfg(any(
+        feature = "fixtures-json",
+        feature = "fixtures-yaml",
+        feature = "fixtures-csv"
+    ))]
+    fn field(mut self, column: String, value: FixtureValue) -> Result<Self, Error> {
+        if column == KEY_FIELD {
+            match value {
+                FixtureValue::Value(Value::Text(key)) | FixtureValue::Ref(key) => {
+                    self.key = Some(key)
+                }
+                other => {
+                    return Err(Error::Fixture(format!(
+                        "{} has to be a string, got {:?}",
+                        KEY_FIELD, other
+                    )))
+                }
+            }
+        } else {
+            self.values.push((column, value));
+        }
+        Ok(self)
+    }
+}
+
+/// Rows to seed tables with, eg. for tests and demos.
+///
+/// Tables are filled in foreign key order, read from the database, with
+/// batched inserts. A row can be named with a `@key` field and other rows
+/// refer to its primary key with `@name` values; those references are
+/// resolved to the generated key, so they have to point at rows in tables
+/// filled before. Everything is inserted in one transaction.
+///
+/// Fixture files map table names to lists of rows in JSON or YAML, or hold
+/// the rows of the table named by the file in CSV, where empty fields are
+/// `NULL`. Text is converted to the type of its column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixtures {
+    tables: Vec<(String, Vec<FixtureRow>)>,
+    batch_size: usize,
+}
+
+impl Default for Fixtures {
+    fn default() -> Self {
+        Fixtures::new()
+    }
+}
+
+impl Fixtures {
+    pub fn new() -> Fixtures {
+        Fixtures {
+            tables: Vec::new(),
+            batch_size: 100,
+        }
+    }
+
+    /// Most rows per `INSERT`, 100 by default.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn row(mut self, table: impl Into<String>, row: FixtureRow) -> Self {
+        self.rows(table.into()).push(row);
+        self
+    }
+
+    /// Adds the rows of `other`, eg. from another file.
+    pub fn extend(&mut self, other: Fixtures) {
+        for (table, rows) in other.tables {
+            self.rows(table).extend(rows);
+        }
+    }
+
+    fn rows(&mut self, table: String) -> &mut Vec<FixtureRow> {
+        let idx = match self.tables.iter().position(|(name, _)| *name == table) {
+            Some(idx) => idx,
+            None => {
+                self.tables.push((table, Vec::new()));
+                self.tables.len() - 1
+            }
+        };
+        &mut self.tables[idx].1
+    }
+
+    #[cfg(feature = "fixtures-json")]
+    pub fn from_json(json: &str) -> Result<Fixtures, Error> {
+        let json = serde_json::from_str(json).map_err(|err| Error::Fixture(err.to_string()))?;
+        Fixtures::from_json_value(json)
+    }
+
+    #[cfg(feature = "fixtures-yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Fixtures, Error> {
+        let json = serde_yaml::from_str(yaml).map_err(|err| Error::Fixture(err.to_string()))?;
+        Fixtures::from_json_value(json)
+    }
+
+    #[cfg(any(feature = "fixtures-json", feature = "fixtures-yaml"))]
+    fn from_json_value(json: serde_json::Value) -> Result<Fixtures, Error> {
+        use serde_json::Value as Json;
+
+        let tables = match json {
+            Json::Object(tables) => tables,
+            _ => {
+                return Err(Error::Fixture(
+                    "expected a map of table names to rows".to_owned(),
+                ))
+            }
+        };
+
+        let mut fixtures = Fixtures::new();
+        for (table, rows) in tables {
+            let rows = match rows {
+                Json::Array(rows) => rows,
+                _ => {
+                    return Err(Error::Fixture(format!(
+                        "{}: expected a list of rows",
+                        table
+                    )))
+                }
+            };
+            for row in rows {
+                let fields = match row {
+                    Json::Object(fields) => fields,
+                    _ => return Err(Error::Fixture(format!("{}: expected a row", table))),
+                };
+                let mut row = FixtureRow::new();
+                for (column, value) in fields {
+                    let value = json_value(value)
+                        .map_err(|err| Error::Fixture(format!("{}.{}: {}", table, column, err)))?;
+                    row = row.field(column, value)?;
+                }
+                fixtures = fixtures.row(table.clone(), row);
+            }
+        }
+        Ok(fixtures)
+    }
+
+    /// Reads the rows of `table` from CSV with a header line.
+    #[cfg(feature = "fixtures-csv")]
+    pub fn from_csv(table: impl Into<String>, csv: &str) -> Result<Fixtures, Error> {
+        let table = table.into();
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let columns = reader
+            .headers()
+            .map_err(|err| Error::Fixture(format!("{}: {}", table, err)))?
+            .iter()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+
+        let mut fixtures = Fixtures::new();
+        for record in reader.records() {
+            let record = record.map_err(|err| Error::Fixture(format!("{}: {}", table, err)))?;
+            let mut row = FixtureRow::new();
+            for (column, field) in columns.iter().zip(record.iter()) {
+                let value = if field.is_empty() {
+                    FixtureValue::Value(Value::Null)
+                } else {
+                    FixtureValue::from_text(field)
+                };
+                row = row.field(column.clone(), value)?;
+            }
+            fixtures = fixtures.row(table.clone(), row);
+        }
+        Ok(fixtures)
+    }
+
+    /// Reads a fixture file, picking the format from its extension among
+    /// the enabled `fixtures-*` features.
+    #[cfg(any(
+        feature = "fixtures-json",
+        feature = "fixtures-yaml",
+        feature = "fixtures-csv"
+    ))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Fixtures, Error> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let parse: fn(&Path, &str) -> Result<Fixtures, Error> = match extension {
+            #[cfg(feature = "fixtures-json")]
+            "json" => |_, content| Fixtures::from_json(content),
+            #[cfg(feature = "fixtures-yaml")]
+            "yaml" | "yml" => |_, content| Fixtures::from_yaml(content),
+            #[cfg(feature = "fixtures-csv")]
+            "csv" => |path, content| {
+                let table = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or("");
+                Fixtures::from_csv(table, content)
+            },
+            _ => {
+                return Err(Error::Fixture(format!(
+                    "{}: unsupported fixture format",
+                    path.display()
+                )))
+            }
+        };
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| Error::Fixture(format!("{}: {}", path.display(), err)))?;
+        parse(path, &content)
+    }
+
+    /// Reads every fixture file in `path`, in file name order.
+    #[cfg(any(
+        feature = "fixtures-json",
+        feature = "fixtures-yaml",
+        feature = "fixtures-csv"
+    ))]
+    pub fn load_dir(path: impl AsRef<Path>) -> Result<Fixtures, Error> {
+        let path = path.as_ref();
+        let io_error = |err: std::io::Error| Error::Fixture(format!("{}: {}", path.display(), err));
+
+        let mut files = std::fs::read_dir(path)
+            .map_err(io_error)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io_error)?;
+        files.sort();
+
+        let mut fixtures = Fixtures::new();
+        for file in files.into_iter().filter(|file| file.is_file()) {
+            fixtures.extend(Fixtures::load(file)?);
+        }
+        Ok(fixtures)
+    }
+
+    /// Inserts the rows, returning the primary keys of the named rows.
+    pub async fn insert(&self, db: &Database) -> Result<HashMap<String, Value>, Error> {
+        self.apply(db, false).await
+    }
+
+    /// Deletes every row of the fixture tables, in reverse foreign key
+    /// order, before inserting the rows. Tables outside the fixtures that
+    /// refer to them have to be emptied first.
+    pub async fn reset(&self, db: &Database) -> Result<HashMap<String, Value>, Error> {
+        self.apply(db, true).await
+    }
+
+    async fn apply(&self, db: &Database, reset: bool) -> Result<HashMap<String, Value>, Error> {
+        let dialect = db.dialect();
+        let tables = self.ordered_tables(introspect(db).await?)?;
+
+        let mut tx = db.begin().await?;
+        if reset {
+            for table in tables.iter().rev() {
+                DeleteStmt::new(table.name.as_ref())
+                    .with_deleted()
+                    .to_query(dialect)
+                    .map_err(|err| ConnectionError::Build(Box::new(err)))?
+                    .execute(&mut tx)
+                    .await?;
+            }
+        }
+
+        let mut keys = HashMap::new();
+        for table in &tables {
+            let rows = self
+                .tables
+                .iter()
+                .filter(|(name, _)| *name == table.name)
+                .flat_map(|(_, rows)| rows);
+            self.insert_rows(&mut tx, dialect, table, rows, &mut keys)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(keys)
+    }
+
+    fn ordered_tables(&self, schema: Schema<'static>) -> Result<Vec<CreateTable<'static>>, Error> {
+        if let Some((missing, _)) = self
+            .tables
+            .iter()
+            .find(|(name, _)| !schema.tables.iter().any(|table| table.name == *name))
+        {
+            return Err(Error::Fixture(format!("unknown table {}", missing)));
+        }
+
+        let schema = Schema {
+            tables: schema
+                .tables
+                .into_iter()
+                .filter(|table| self.tables.iter().any(|(name, _)| table.name == *name))
+                .collect(),
+            indexes: Vec::new(),
+        };
+        let ordered = schema
+            .ordered_tables()
+            .map_err(|err| Error::Fixture(err.to_string()))?;
+        Ok(ordered.into_iter().cloned().collect())
+    }
+
+    async fn insert_rows<'a, I>(
+        &self,
+        tx: &mut DatabaseTransaction<'_>,
+        dialect: Dialect,
+        table: &CreateTable<'static>,
+        rows: I,
+        keys: &mut HashMap<String, Value>,
+    ) -> Result<(), Error>
+    where
+        I: Iterator<Item = &'a FixtureRow>,
+    {
+        let mut columns: Vec<String> = Vec::new();
+        let mut batch: Vec<Vec<Value>> = Vec::new();
+
+        for row in rows {
+            let mut values = Vec::with_capacity(row.values.len());
+            for (column, value) in &row.values {
+                let ty = table
+                    .fields
+                    .iter()
+                    .find(|field| field.name == column.as_str())
+                    .map(|field| field.ty)
+                    .ok_or_else(|| {
+                        Error::Fixture(format!("unknown column {}.{}", table.name, column))
+                    })?;
+                let value = match value {
+                    FixtureValue::Value(value) => value.clone(),
+                    FixtureValue::Ref(key) => keys.get(key).cloned().ok_or_else(|| {
+                        Error::Fixture(format!(
+                            "{}.{} refers to unknown row {}",
+                            table.name, column, key
+                        ))
+                    })?,
+                };
+                let value = coerce(value, ty)
+                    .map_err(|err| Error::Fixture(format!("{}.{}: {}", table.name, column, err)))?;
+                values.push(value);
+            }
+            let row_columns = row.values.iter().map(|(column, _)| column);
+
+            if let Some(key) = &row.key {
+                if keys.contains_key(key) {
+                    return Err(Error::Fixture(format!("duplicate row key {}", key)));
+                }
+                batch::insert(tx, dialect, &table.name, &columns, &mut batch).await?;
+                let columns = row_columns.cloned().collect::<Vec<_>>();
+                let id = insert_keyed(tx, dialect, table, &columns, values).await?;
+                keys.insert(key.clone(), id);
+                continue;
+            }
+
+            let per_batch = batch::rows_per_batch(self.batch_size, row.values.len());
+            if !columns.iter().eq(row_columns.clone()) || batch.len() >= per_batch {
+                batch::insert(tx, dialect, &table.name, &columns, &mut batch).await?;
+                columns = row_columns.cloned().collect();
+            }
+            batch.push(values);
+        }
+
+        batch::insert(tx, dialect, &table.name, &columns, &mut batch).await?;
+        Ok(())
+    }
+}
+
+/// Inserts a single row and returns its primary key: the given one if the
+/// row sets it, otherwise the generated one.
+async fn insert_keyed(
+    tx: &mut DatabaseTransaction<'_>,
+    dialect: Dialect,
+    table: &CreateTable<'static>,
+    columns: &[String],
+    values: Vec<Value>,
+) -> Result<Value, Error> {
+    let primary_key = match table.fields.iter().find(|field| field.primary_key) {
+        Some(primary_key) => primary_key,
+        None => {
+            return Err(Error::Fixture(format!(
+                "{} has no primary key to refer to",
+                table.name
+            )))
+        }
+    };
+    if let Some(idx) = columns.iter().position(|c| *c == primary_key.name) {
+        return Ok(values[idx].clone());
+    }
+
+    let (sql, values) = nibard_dsl::build(
+        dialect,
+        InsertStmt::new(table.name.as_ref())
+            .columns(columns)
+            .values(values.into_iter().map(Expr::value)),
+    )
+    .map_err(|err| ConnectionError::Build(Box::new(err)))?;
+
+    let id = if dialect == Dialect::Pg {
+        let sql = format!("{} RETURNING CAST({} AS TEXT) AS id", sql, primary_key.name);
+        let row = Query::new(sql, values).fetch_one(&mut *tx).await?;
+        match row.try_get("id", None)? {
+            Value::Text(id) => Value::parse(primary_key.ty, &id)
+                .map_err(|err| ConnectionError::Decode(err.to_string()))?,
+            other => {
+                return Err(ConnectionError::Decode(format!("unexpected key {:?}", other)).into())
+            }
+        }
+    } else {
+        let result = Query::new(sql, values).execute(&mut *tx).await?;
+        let id = result
+            .last_insert_id
+            .ok_or_else(|| Error::Fixture(format!("no key was generated for {}", table.name)))?;
+        integer(id, primary_key.ty)
+    };
+    Ok(id)
+}
+
+/// Converts a value read from a fixture to the type of its column.
+fn coerce(value: Value, ty: Type) -> Result<Value, nibard_shared::Error> {
+    let value = match value {
+        Value::Text(text) => Value::parse(ty, &text)?,
+        Value::SmallInt(i) => integer(i as i64, ty),
+        Value::Int(i) => integer(i as i64, ty),
+        Value::BigInt(i) => integer(i, ty),
+        Value::Float(f) => match ty {
+            Type::Real => Value::Real(f as f32),
+            Type::Char(_) | Type::VarChar(_) | Type::Text => Value::Text(f.to_string()),
+            _ => Value::Float(f),
+        },
+        Value::Bool(b) => match ty {
+            Type::Char(_) | Type::VarChar(_) | Type::Text => Value::Text(b.to_string()),
+            _ => Value::Bool(b),
+        },
+        value => value,
+    };
+    Ok(value)
+}
+
+fn integer(i: i64, ty: Type) -> Value {
+    use std::convert::TryFrom;
+
+    match ty {
+        Type::SmallInt => i16::try_from(i).map_or(Value::BigInt(i), Value::SmallInt),
+        Type::Int | Type::Auto => i32::try_from(i).map_or(Value::BigInt(i), Value::Int),
+        Type::Float(_) => Value::Float(i as f64),
+        Type::Real => Value::Real(i as f32),
+        Type::Bool => Value::Bool(i != 0),
+        Type::Char(_) | Type::VarChar(_) | Type::Text => Value::Text(i.to_string()),
+        _ => Value::BigInt(i),
+    }
+}
+
+#[cfg(any(feature = "fixtures-json", feature = "fixtures-yaml"))]
+fn json_value(json: serde_json::Value) -> Result<FixtureValue, String> {
+    use serde_json::Value as Json;
+
+    let value = match json {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Bool(b),
+        Json::Number(n) => match n.as_i64() {
+            Some(i) => Value::BigInt(i),
+            None => Value::Float(n.as_f64().unwrap_or_default()),
+        },
+        Json::String(text) => return Ok(FixtureValue::from_text(&text)),
+        #[cfg(feature = "json")]
+        json => Value::Json(json),
+        #[cfg(not(feature = "json"))]
+        _ => return Err("nested values need the json feature".to_owned()),
+    };
+    Ok(FixtureValue::Value(value))
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use super::*;
+    use futures::TryStreamExt;
+    use nibard_connection::Executor;
+
+    async fn open() -> Database {
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        for sql in [
+            "CREATE TABLE products(id INTEGER PRIMARY KEY, name TEXT NOT NULL, \
+             price REAL, weight FLOAT)",
+            "CREATE TABLE items(id INTEGER PRIMARY KEY, \
+             product_id INTEGER REFERENCES products(id), discount DOUBLE)",
+        ] {
+            db.execute(sql).await.unwrap();
+        }
+        db
+    }
+
+    async fn rows(db: &Database, sql: &str) -> Vec<Vec<Value>> {
+        Query::new(sql.to_owned(), Vec::new())
+            .fetch(db)
+            .map_ok(|row| {
+                row.columns()
+                    .iter()
+                    .map(|column| row.try_get(column.name, None).unwrap())
+                    .collect()
+            })
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn float_fields() {
+        let db = open().await;
+        let keys = Fixtures::new()
+            .row(
+                "items",
+                FixtureRow::new()
+                    .set_ref("product_id", "lamp")
+                    .set("discount", 0.5),
+            )
+            .row(
+                "products",
+                FixtureRow::new()
+                    .key("lamp")
+                    .set("name", "lamp")
+                    .set("price", 9.5)
+                    .set("weight", 1.25),
+            )
+            .row(
+                "products",
+                FixtureRow::new()
+                    .set("name", "chair")
+                    .set("price", 20)
+                    .set("weight", Value::Null),
+            )
+            .insert(&db)
+            .await
+            .unwrap();
+        assert_eq!(keys["lamp"], Value::BigInt(1));
+
+        assert_eq!(
+            rows(&db, "SELECT name, price, weight FROM products ORDER BY id").await,
+            [
+                vec![
+                    Value::Text("lamp".into()),
+                    Value::Float(9.5),
+                    Value::Float(1.25)
+                ],
+                vec![Value::Text("chair".into()), Value::Float(20.0), Value::Null],
+            ]
+        );
+        assert_eq!(
+            rows(&db, "SELECT product_id, discount FROM items").await,
+            [vec![Value::BigInt(1), Value::Float(0.5)]]
+        );
+    }
+
+    #[cfg(feature = "fixtures-json")]
+    #[tokio::test]
+    async fn float_fields_from_json() {
+        let db = open().await;
+        Fixtures::from_json(
+            r#"{
+                "products": [
+                    { "@key": "lamp", "name": "lamp", "price": 9.5, "weight": "0.75" }
+                ],
+                "items": [{ "product_id": "@lamp", "discount": 2 }]
+            }"#,
+        )
+        .unwrap()
+        .insert(&db)
+        .await
+        .unwrap();
+
+        assert_eq!(
+            rows(&db, "SELECT price, weight FROM products").await,
+            [vec![Value::Float(9.5), Value::Float(0.75)]]
+        );
+        assert_eq!(
+            rows(&db, "SELECT product_id, discount FROM items").await,
+            [vec![Value::BigInt(1), Value::Float(2.0)]]
+        );
+    }
+}
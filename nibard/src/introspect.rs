@@ -0,0 +1,664 @@
+use super::query::Query;
+use futures::TryStreamExt;
+use nibard_connection::{DatabaseRow, Error, Executor, Row};
+use nibard_dsl::create::{
+    Column, CreateIndex, CreateTable, ForeignKey, IndexColumn, IndexMethod, IndexPredicate,
+    ReferentialAction, Schema,
+};
+use nibard_shared::{Dialect, Type, Value};
+
+const SQLITE_TABLES: &str = "SELECT name FROM sqlite_master \
+     WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name";
+
+const SQLITE_COLUMNS: &str = "SELECT name AS column_name, type AS column_type, \
+     \"notnull\" AS not_null, dflt_value AS column_default, pk AS primary_key \
+     FROM pragma_table_info(?) ORDER BY cid";
+
+const SQLITE_FOREIGN_KEYS: &str = "SELECT \"from\" AS column_name, \"table\" AS foreign_table, \
+     \"to\" AS foreign_column, on_update, on_delete FROM pragma_foreign_key_list(?) \
+     ORDER BY id, seq";
+
+const SQLITE_INDEXES: &str = "SELECT il.name AS index_name, il.\"unique\" AS is_unique, \
+     il.partial AS partial, ii.name AS column_name, m.sql AS sql \
+     FROM pragma_index_list(?) AS il \
+     JOIN pragma_index_info(il.name) AS ii \
+     JOIN sqlite_master AS m ON m.type = 'index' AND m.name = il.name \
+     WHERE il.origin = 'c' ORDER BY il.seq, ii.seqno";
+
+const PG_COLUMNS: &str = "SELECT c.relname::text AS table_name, a.attname::text AS column_name, \
+     format_type(a.atttypid, a.atttypmod) AS column_type, a.attnotnull::text AS not_null, \
+     pg_get_expr(d.adbin, d.adrelid) AS column_default, \
+     EXISTS (SELECT 1 FROM pg_index ix WHERE ix.indrelid = c.oid AND ix.indisprimary \
+         AND a.attnum = ANY(ix.indkey))::text AS primary_key \
+     FROM pg_attribute a \
+     JOIN pg_class c ON c.oid = a.attrelid \
+     JOIN pg_namespace n ON n.oid = c.relnamespace \
+     LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum \
+     WHERE n.nspname = current_schema() AND c.relkind = 'r' \
+         AND a.attnum > 0 AND NOT a.attisdropped \
+     ORDER BY c.relname, a.attnum";
+
+const PG_FOREIGN_KEYS: &str = "SELECT t.relname::text AS table_name, \
+     a.attname::text AS column_name, ft.relname::text AS foreign_table, \
+     fa.attname::text AS foreign_column, con.confupdtype::text AS on_update, \
+     con.confdeltype::text AS on_delete \
+     FROM pg_constraint con \
+     JOIN pg_class t ON t.oid = con.conrelid \
+     JOIN pg_namespace n ON n.oid = t.relnamespace \
+     JOIN pg_class ft ON ft.oid = con.confrelid \
+     CROSS JOIN LATERAL unnest(con.conkey, con.confkey) AS k(attnum, fattnum) \
+     JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum \
+     JOIN pg_attribute fa ON fa.attrelid = con.confrelid AND fa.attnum = k.fattnum \
+     WHERE con.contype = 'f' AND n.nspname = current_schema() \
+     ORDER BY t.relname, con.conname";
+
+const PG_INDEXES: &str = "SELECT t.relname::text AS table_name, i.relname::text AS index_name, \
+     ix.indisunique::text AS is_unique, am.amname::text AS method, \
+     pg_get_indexdef(ix.indexrelid, k.ord, true) AS column_name, \
+     pg_get_expr(ix.indpred, ix.indrelid, true) AS predicate \
+     FROM pg_index ix \
+     JOIN pg_class i ON i.oid = ix.indexrelid \
+     JOIN pg_class t ON t.oid = ix.indrelid \
+     JOIN pg_namespace n ON n.oid = t.relnamespace \
+     JOIN pg_am am ON am.oid = i.relam \
+     CROSS JOIN LATERAL generate_series(1, ix.indnkeyatts::int) AS k(ord) \
+     WHERE n.nspname = current_schema() AND t.relkind = 'r' AND NOT ix.indisprimary \
+     ORDER BY t.relname, i.relname, k.ord";
+
+const MYSQL_COLUMNS: &str = "SELECT CAST(c.table_name AS CHAR) AS table_name, \
+     CAST(c.column_name AS CHAR) AS column_name, CAST(c.column_type AS CHAR) AS column_type, \
+     CAST(c.is_nullable AS CHAR) AS is_nullable, CAST(c.column_default AS CHAR) AS column_default, \
+     CAST(c.column_key AS CHAR) AS column_key, CAST(c.extra AS CHAR) AS extra \
+     FROM information_schema.columns c \
+     JOIN information_schema.tables t \
+         ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+     WHERE c.table_schema = DATABASE() AND t.table_type = 'BASE TABLE' \
+     ORDER BY c.table_name, c.ordinal_position";
+
+const MYSQL_FOREIGN_KEYS: &str = "SELECT CAST(k.table_name AS CHAR) AS table_name, \
+     CAST(k.column_name AS CHAR) AS column_name, \
+     CAST(k.referenced_table_name AS CHAR) AS foreign_table, \
+     CAST(k.referenced_column_name AS CHAR) AS foreign_column, \
+     CAST(r.update_rule AS CHAR) AS on_update, CAST(r.delete_rule AS CHAR) AS on_delete \
+     FROM information_schema.key_column_usage k \
+     JOIN information_schema.referential_constraints r \
+         ON r.constraint_schema = k.constraint_schema \
+         AND r.constraint_name = k.constraint_name AND r.table_name = k.table_name \
+     WHERE k.table_schema = DATABASE() AND k.referenced_table_name IS NOT NULL \
+     ORDER BY k.table_name, k.constraint_name, k.ordinal_position";
+
+const MYSQL_INDEXES: &str = "SELECT CAST(table_name AS CHAR) AS table_name, \
+     CAST(index_name AS CHAR) AS index_name, CAST(non_unique AS CHAR) AS non_unique, \
+     CAST(column_name AS CHAR) AS column_name, CAST(index_type AS CHAR) AS index_type \
+     FROM information_schema.statistics \
+     WHERE table_schema = DATABASE() AND index_name <> 'PRIMARY' \
+         AND index_type IN ('BTREE', 'HASH') \
+     ORDER BY table_name, index_name, seq_in_index";
+
+/// Reads the tables of the connected database, with their columns, primary
+/// and foreign keys and indexes, as the `nibard_dsl::create` types used to
+/// create them.
+///
+/// Only what those types can describe is returned: column defaults that are
+/// expressions, such as `CURRENT_TIMESTAMP`, are left out, as are indexes
+/// on expressions for Sqlite and MySQL and the indexes backing unique
+/// constraints on Sqlite. Columns of a type `Type` has no equivalent for
+/// are reported as `Error::Decode`.
+///
+/// This is also available as `introspect()` on a `&Database` or a
+/// transaction through [`ExecutorExt`](crate::ExecutorExt), so
+/// `db.introspect().await` works without importing this module.
+pub async fn introspect<'c, E>(executor: E) -> Result<Schema<'static>, Error>
+where
+    E: Executor<'c> + Copy + Send + 'c,
+{
+    match executor.dialect() {
+        Dialect::Sqlite => sqlite(executor).await,
+        Dialect::Pg => pg(executor).await,
+        Dialect::MySQL => mysql(executor).await,
+    }
+}
+
+async fn sqlite<'c, E>(executor: E) -> Result<Schema<'static>, Error>
+where
+    E: Executor<'c> + Copy + Send + 'c,
+{
+    let mut schema = Schema::new();
+
+    for row in rows(executor, SQLITE_TABLES, Vec::new()).await? {
+        let name = required_text(&row, "name")?;
+        let table_param = || vec![Value::Text(name.clone())];
+
+        let mut table = CreateTable::new(name.clone());
+        let mut rowid_alias = None;
+        for row in rows(executor, SQLITE_COLUMNS, table_param()).await? {
+            let mut column = column(Dialect::Sqlite, &name, &row)?;
+            column.primary_key = text(&row, "primary_key")?.as_deref() != Some("0");
+            column.required = text(&row, "not_null")?.as_deref() == Some("1");
+            let integer = text(&row, "column_type")?
+                .is_some_and(|ty| ty.trim().eq_ignore_ascii_case("integer"));
+            if column.primary_key && integer {
+                rowid_alias = Some(table.fields.len());
+            }
+            table.fields.push(column);
+        }
+        // a single `INTEGER PRIMARY KEY` column is an alias for the 64 bit
+        // rowid, which Sqlite assigns with or without AUTOINCREMENT
+        let primary_keys = table.fields.iter().filter(|column| column.primary_key);
+        if let (Some(idx), 1) = (rowid_alias, primary_keys.count()) {
+            table.fields[idx].ty = Type::BigAuto;
+        }
+
+        for row in rows(executor, SQLITE_FOREIGN_KEYS, table_param()).await? {
+            foreign_key(&mut table, &row, referential_action)?;
+        }
+
+        // an index on an expression has no column name, so it is dropped
+        let mut skip = None;
+        for row in rows(executor, SQLITE_INDEXES, table_param()).await? {
+            let index_name = required_text(&row, "index_name")?;
+            if skip.as_ref() == Some(&index_name) {
+                continue;
+            }
+            let column_name = match text(&row, "column_name")? {
+                Some(column_name) => column_name,
+                None => {
+                    if matches!(schema.indexes.last(), Some(index) if index.name == index_name) {
+                        schema.indexes.pop();
+                    }
+                    skip = Some(index_name);
+                    continue;
+                }
+            };
+
+            let index = index(&mut schema.indexes, &name, &index_name);
+            index.unique = text(&row, "is_unique")?.as_deref() == Some("1");
+            index.columns.push(IndexColumn::new(column_name));
+            if text(&row, "partial")?.as_deref() == Some("1") {
+                index.predicate = text(&row, "sql")?
+                    .and_then(|sql| sqlite_predicate(&sql))
+                    .map(|predicate| IndexPredicate::Sql(predicate.into()));
+            }
+        }
+
+        schema.tables.push(table);
+    }
+
+    // Sqlite allows `REFERENCES users` without a column, which means the
+    // primary key of `users`
+    let primary_keys = schema
+        .tables
+        .iter()
+        .filter_map(|table| {
+            let pk = table.fields.iter().find(|column| column.primary_key)?;
+            Some((table.name.to_string(), pk.name.to_string()))
+        })
+        .collect::<Vec<_>>();
+    for column in schema.tables.iter_mut().flat_map(|table| &mut table.fields) {
+        if let Some(fk) = column
+            .foreign_key
+            .as_mut()
+            .filter(|fk| fk.column.is_empty())
+        {
+            if let Some((_, pk)) = primary_keys.iter().find(|(table, _)| *table == fk.table) {
+                fk.column = pk.clone().into();
+            }
+        }
+    }
+
+    Ok(schema)
+}
+
+async fn pg<'c, E>(executor: E) -> Result<Schema<'static>, Error>
+where
+    E: Executor<'c> + Copy + Send + 'c,
+{
+    let mut schema = Schema::new();
+
+    for row in rows(executor, PG_COLUMNS, Vec::new()).await? {
+        let table_name = required_text(&row, "table_name")?;
+        let mut column = column(Dialect::Pg, &table_name, &row)?;
+        column.primary_key = text(&row, "primary_key")?.as_deref() == Some("true");
+        column.required = text(&row, "not_null")?.as_deref() == Some("true");
+        let serial = text(&row, "column_default")?.is_some_and(|d| d.starts_with("nextval("));
+        if serial {
+            column.ty = auto(column.ty);
+        }
+        table(&mut schema.tables, &table_name).fields.push(column);
+    }
+
+    for row in rows(executor, PG_FOREIGN_KEYS, Vec::new()).await? {
+        let table_name = required_text(&row, "table_name")?;
+        foreign_key(table(&mut schema.tables, &table_name), &row, pg_action)?;
+    }
+
+    for row in rows(executor, PG_INDEXES, Vec::new()).await? {
+        let table_name = required_text(&row, "table_name")?;
+        let index_name = required_text(&row, "index_name")?;
+        let part = required_text(&row, "column_name")?;
+
+        let index = index(&mut schema.indexes, &table_name, &index_name);
+        index.unique = text(&row, "is_unique")?.as_deref() == Some("true");
+        index.method = match text(&row, "method")?.as_deref() {
+            Some("hash") => Some(IndexMethod::Hash),
+            Some("gin") => Some(IndexMethod::Gin),
+            Some("gist") => Some(IndexMethod::Gist),
+            Some("brin") => Some(IndexMethod::Brin),
+            _ => None,
+        };
+        index.predicate =
+            text(&row, "predicate")?.map(|predicate| IndexPredicate::Sql(predicate.into()));
+        if is_identifier(&part) {
+            index.columns.push(IndexColumn::new(part));
+        } else {
+            index.columns.push(IndexColumn::expr(part));
+        }
+    }
+
+    Ok(schema)
+}
+
+async fn mysql<'c, E>(executor: E) -> Result<Schema<'static>, Error>
+where
+    E: Executor<'c> + Copy + Send + 'c,
+{
+    let mut schema = Schema::new();
+
+    for row in rows(executor, MYSQL_COLUMNS, Vec::new()).await? {
+        let table_name = required_text(&row, "table_name")?;
+        let extra = text(&row, "extra")?
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let mut column = column(Dialect::MySQL, &table_name, &row)?;
+        column.primary_key = text(&row, "column_key")?.as_deref() == Some("PRI");
+        column.required = text(&row, "is_nullable")?.as_deref() == Some("NO");
+        if extra.contains("default_generated") {
+            column.default = None;
+        }
+        if extra.contains("auto_increment") {
+            column.ty = auto(column.ty);
+        }
+        table(&mut schema.tables, &table_name).fields.push(column);
+    }
+
+    for row in rows(executor, MYSQL_FOREIGN_KEYS, Vec::new()).await? {
+        let table_name = required_text(&row, "table_name")?;
+        foreign_key(
+            table(&mut schema.tables, &table_name),
+            &row,
+            referential_action,
+        )?;
+    }
+
+    for row in rows(executor, MYSQL_INDEXES, Vec::new()).await? {
+        let table_name = required_text(&row, "table_name")?;
+        let index_name = required_text(&row, "index_name")?;
+        // functional indexes have no column name
+        let column_name = match text(&row, "column_name")? {
+            Some(column_name) => column_name,
+            None => continue,
+        };
+
+        let index = index(&mut schema.indexes, &table_name, &index_name);
+        index.unique = text(&row, "non_unique")?.as_deref() == Some("0");
+        if text(&row, "index_type")?.as_deref() == Some("HASH") {
+            index.method = Some(IndexMethod::Hash);
+        }
+        index.columns.push(IndexColumn::new(column_name));
+    }
+
+    Ok(schema)
+}
+
+async fn rows<'c, E>(executor: E, sql: &str, values: Vec<Value>) -> Result<Vec<DatabaseRow>, Error>
+where
+    E: Executor<'c> + 'c,
+{
+    Query::new(sql.to_owned(), values)
+        .fetch(executor)
+        .try_collect()
+        .await
+}
+
+fn text(row: &DatabaseRow, column: &str) -> Result<Option<String>, Error> {
+    match row.try_get(column, None)? {
+        Value::Text(text) => Ok(Some(text)),
+        Value::SmallInt(i) => Ok(Some(i.to_string())),
+        Value::Int(i) => Ok(Some(i.to_string())),
+        Value::BigInt(i) => Ok(Some(i.to_string())),
+        Value::Null => Ok(None),
+        other => Err(Error::Decode(format!(
+            "unexpected value for {}: {:?}",
+            column, other
+        ))),
+    }
+}
+
+fn required_text(row: &DatabaseRow, column: &str) -> Result<String, Error> {
+    text(row, column)?.ok_or_else(|| Error::Decode(format!("missing value for {}", column)))
+}
+
+/// The table named `name`, which is the last one pushed when rows come
+/// ordered by table.
+fn table<'t>(
+    tables: &'t mut Vec<CreateTable<'static>>,
+    name: &str,
+) -> &'t mut CreateTable<'static> {
+    match tables.iter().position(|table| table.name == name) {
+        Some(idx) => &mut tables[idx],
+        None => {
+            tables.push(CreateTable::new(name.to_owned()));
+            tables.last_mut().unwrap()
+        }
+    }
+}
+
+fn index<'i>(
+    indexes: &'i mut Vec<CreateIndex<'static>>,
+    table: &str,
+    name: &str,
+) -> &'i mut CreateIndex<'static> {
+    match indexes
+        .iter()
+        .position(|index| index.table == table && index.name == name)
+    {
+        Some(idx) => &mut indexes[idx],
+        None => {
+            indexes.push(CreateIndex::new(
+                table.to_owned(),
+                name.to_owned(),
+                Vec::new(),
+            ));
+            indexes.last_mut().unwrap()
+        }
+    }
+}
+
+fn column(dialect: Dialect, table: &str, row: &DatabaseRow) -> Result<Column<'static>, Error> {
+    let name = required_text(row, "column_name")?;
+    let type_name = text(row, "column_type")?.unwrap_or_default();
+    let ty = Type::from_sql(dialect, &type_name).ok_or_else(|| {
+        Error::Decode(format!(
+            "unsupported type {} for column {}.{}",
+            type_name, table, name
+        ))
+    })?;
+
+    let mut column = Column::new(name, ty);
+    column.default =
+        text(row, "column_default")?.and_then(|default| default_value(dialect, ty, &default));
+    Ok(column)
+}
+
+fn foreign_key(
+    table: &mut CreateTable<'static>,
+    row: &DatabaseRow,
+    action: fn(&str) -> ReferentialAction,
+) -> Result<(), Error> {
+    let column_name = required_text(row, "column_name")?;
+    let on_update = text(row, "on_update")?.unwrap_or_default();
+    let on_delete = text(row, "on_delete")?.unwrap_or_default();
+    let fk = ForeignKey::new(
+        required_text(row, "foreign_table")?,
+        text(row, "foreign_column")?.unwrap_or_default(),
+    )
+    .on_update(action(&on_update))
+    .on_delete(action(&on_delete));
+
+    match table
+        .fields
+        .iter_mut()
+        .find(|column| column.name == column_name)
+    {
+        Some(column) => column.foreign_key = Some(fk),
+        None => {
+            return Err(Error::Decode(format!(
+                "foreign key on unknown column {}.{}",
+                table.name, column_name
+            )))
+        }
+    }
+    Ok(())
+}
+
+fn referential_action(rule: &str) -> ReferentialAction {
+    match rule.to_ascii_uppercase().as_str() {
+        "CASCADE" => ReferentialAction::Cascade,
+        "RESTRICT" => ReferentialAction::Restrict,
+        "SET NULL" => ReferentialAction::SetNull,
+        "SET DEFAULT" => ReferentialAction::SetDefault,
+        _ => ReferentialAction::NoAction,
+    }
+}
+
+/// `pg_constraint` stores referential actions as single letters.
+fn pg_action(code: &str) -> ReferentialAction {
+    match code {
+        "c" => ReferentialAction::Cascade,
+        "r" => ReferentialAction::Restrict,
+        "n" => ReferentialAction::SetNull,
+        "d" => ReferentialAction::SetDefault,
+        _ => ReferentialAction::NoAction,
+    }
+}
+
+/// Turns a column default as the database reports it into a value. Only
+/// literals have one; expressions such as `now()` give `None`.
+fn default_value(dialect: Dialect, ty: Type, default: &str) -> Option<Value> {
+    let mut default = default.trim();
+    // Pg adds casts, eg. 'draft'::character varying
+    if let (Dialect::Pg, Some(idx)) = (dialect, default.rfind("::")) {
+        if !default[idx..].contains('\'') {
+            default = &default[..idx];
+        }
+    }
+    while default.starts_with('(') && default.ends_with(')') {
+        default = default[1..default.len() - 1].trim();
+    }
+    if default.eq_ignore_ascii_case("null") {
+        return None;
+    }
+
+    let quoted = default.len() >= 2 && default.starts_with('\'') && default.ends_with('\'');
+    let literal = if quoted {
+        default[1..default.len() - 1].replace("''", "'")
+    } else {
+        default.to_owned()
+    };
+
+    match ty {
+        Type::SmallInt => literal.parse().ok().map(Value::SmallInt),
+        Type::Int | Type::Auto => literal.parse().ok().map(Value::Int),
+        Type::BigInt | Type::BigAuto => literal.parse().ok().map(Value::BigInt),
+        Type::Float(_) => literal.parse().ok().map(Value::Float),
+        Type::Real => literal.parse().ok().map(Value::Real),
+        Type::Bool => match literal.to_ascii_lowercase().as_str() {
+            "true" | "t" | "1" => Some(Value::Bool(true)),
+            "false" | "f" | "0" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        // MySQL reports string defaults without quotes
+        Type::Char(_) | Type::VarChar(_) | Type::Text if quoted || dialect == Dialect::MySQL => {
+            Some(Value::Text(literal))
+        }
+        _ => None,
+    }
+}
+
+/// The auto increment counterpart of an integer column type.
+fn auto(ty: Type) -> Type {
+    match ty {
+        Type::Int => Type::Auto,
+        Type::BigInt => Type::BigAuto,
+        ty => ty,
+    }
+}
+
+fn sqlite_predicate(sql: &str) -> Option<String> {
+    let idx = sql.to_ascii_uppercase().rfind(" WHERE ")?;
+    Some(sql[idx + 7..].trim().to_owned())
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_values() {
+        let cases = [
+            (Dialect::Pg, Type::Int, "42", Some(Value::Int(42))),
+            (
+                Dialect::Pg,
+                Type::BigInt,
+                "'-7'::bigint",
+                Some(Value::BigInt(-7)),
+            ),
+            (
+                Dialect::Pg,
+                Type::VarChar(16),
+                "'draft'::character varying",
+                Some(Value::Text("draft".into())),
+            ),
+            (
+                Dialect::Pg,
+                Type::Text,
+                "'it''s'::text",
+                Some(Value::Text("it's".into())),
+            ),
+            (Dialect::Pg, Type::Bool, "true", Some(Value::Bool(true))),
+            (Dialect::Pg, Type::Real, "0.5", Some(Value::Real(0.5))),
+            (Dialect::Pg, Type::DateTime, "now()", None),
+            (Dialect::Pg, Type::Text, "NULL::text", None),
+            (Dialect::Sqlite, Type::Int, "(1)", Some(Value::Int(1))),
+            (
+                Dialect::Sqlite,
+                Type::Float(53),
+                "1.5",
+                Some(Value::Float(1.5)),
+            ),
+            (Dialect::Sqlite, Type::Bool, "0", Some(Value::Bool(false))),
+            (
+                Dialect::Sqlite,
+                Type::Text,
+                "'a'",
+                Some(Value::Text("a".into())),
+            ),
+            (Dialect::Sqlite, Type::Text, "CURRENT_TIMESTAMP", None),
+            (
+                Dialect::MySQL,
+                Type::Text,
+                "draft",
+                Some(Value::Text("draft".into())),
+            ),
+            (
+                Dialect::MySQL,
+                Type::SmallInt,
+                "3",
+                Some(Value::SmallInt(3)),
+            ),
+            (Dialect::MySQL, Type::Int, "abc", None),
+        ];
+
+        for (dialect, ty, default, value) in cases {
+            assert_eq!(
+                default_value(dialect, ty, default),
+                value,
+                "{:?} {:?} {}",
+                dialect,
+                ty,
+                default
+            );
+        }
+    }
+
+    #[test]
+    fn sqlite_predicates() {
+        assert_eq!(
+            sqlite_predicate("CREATE INDEX active_users ON users(name) WHERE deleted_at IS NULL")
+                .as_deref(),
+            Some("deleted_at IS NULL")
+        );
+        assert_eq!(
+            sqlite_predicate("create index i on t(a) where a > 1 ").as_deref(),
+            Some("a > 1")
+        );
+        assert_eq!(sqlite_predicate("CREATE INDEX i ON t(a)"), None);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn sqlite_schema() {
+        use crate::prelude::*;
+        use nibard_connection::Database;
+
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        for sql in [
+            "CREATE TABLE users(id INTEGER PRIMARY KEY, name TEXT NOT NULL DEFAULT 'anon', age INT)",
+            "CREATE TABLE posts(id INTEGER PRIMARY KEY AUTOINCREMENT, \
+             user_id BIGINT REFERENCES users ON DELETE CASCADE, score REAL)",
+            "CREATE TABLE tags(post_id INTEGER, name TEXT, PRIMARY KEY (post_id, name))",
+            "CREATE INDEX named_users ON users(name) WHERE name <> 'anon'",
+        ] {
+            db.execute(sql).await.unwrap();
+        }
+
+        let schema = db.introspect().await.unwrap();
+        let tables = schema
+            .tables
+            .iter()
+            .map(|table| table.name.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(tables, ["posts", "tags", "users"]);
+
+        let types = |table: usize| {
+            schema.tables[table]
+                .fields
+                .iter()
+                .map(|column| (column.name.to_string(), column.ty))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            types(0),
+            [
+                ("id".to_owned(), Type::BigAuto),
+                ("user_id".to_owned(), Type::BigInt),
+                ("score".to_owned(), Type::Real),
+            ]
+        );
+        // composite keys are not rowid aliases
+        assert_eq!(
+            types(1),
+            [
+                ("post_id".to_owned(), Type::BigInt),
+                ("name".to_owned(), Type::Text),
+            ]
+        );
+        assert_eq!(
+            types(2),
+            [
+                ("id".to_owned(), Type::BigAuto),
+                ("name".to_owned(), Type::Text),
+                ("age".to_owned(), Type::Int),
+            ]
+        );
+
+        let name = &schema.tables[2].fields[1];
+        assert!(name.required);
+        assert_eq!(name.default, Some(Value::Text("anon".into())));
+        let fk = schema.tables[0].fields[1].foreign_key.as_ref().unwrap();
+        assert_eq!((fk.table.as_ref(), fk.column.as_ref()), ("users", "id"));
+
+        assert_eq!(schema.indexes.len(), 1);
+        assert_eq!(schema.indexes[0].name, "named_users");
+        assert!(matches!(
+            &schema.indexes[0].predicate,
+            Some(IndexPredicate::Sql(sql)) if sql == "name <> 'anon'"
+        ));
+    }
+}
@@ -1,6 +1,18 @@
+mod batch;
+#[cfg(feature = "csv")]
+pub mod csv;
+pub mod dump;
+mod error;
 mod executor_ext;
+#[cfg(feature = "json")]
+pub mod explain;
+pub mod fixtures;
+pub mod introspect;
+pub mod migrate;
 pub mod query;
+mod soft_delete;
 
+pub use error::Error;
 pub use nibard_connection as connection;
 pub use nibard_connection::*;
 pub use nibard_shared::*;
@@ -13,6 +25,9 @@ pub mod prelude {
 
 pub use nibard_dsl as dsl;
 
+#[cfg(feature = "derive")]
+pub use nibard_derive::Table;
+
 // pub mod dsl {
 //     pub use nibard_dsl::*;
 //     pub mod prelude {
@@ -0,0 +1,774 @@
+use super::query::{Query, StatementQuery};
+use super::Error;
+use futures::TryStreamExt;
+use nibard_connection::{
+    Database, DatabaseRow, DatabaseTransaction, Error as ConnectionError, Executor, Row,
+};
+use nibard_dsl::ast::{DeleteStmt, Expr};
+use nibard_dsl::create::{Column, CreateTable};
+use nibard_dsl::insert::Insert;
+use nibard_dsl::prelude::*;
+use nibard_dsl::{LiteralContext, Statement};
+use nibard_shared::{Dialect, Type, Value};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::Path;
+
+const MIGRATIONS_TABLE: &str = "_nibard_migrations";
+
+enum Step {
+    Sql(String),
+    Statement(Box<dyn Statement<LiteralContext> + Send + Sync>),
+}
+
+impl Step {
+    fn render(&self, dialect: Dialect) -> Result<String, Error> {
+        match self {
+            Step::Sql(sql) => Ok(sql.clone()),
+            Step::Statement(stmt) => (&**stmt)
+                .to_sql_literal(dialect)
+                .map_err(|err| ConnectionError::Build(Box::new(err)).into()),
+        }
+    }
+}
+
+/// A numbered schema change. The up steps apply it and the optional down
+/// steps revert it; each step is either SQL, which may hold several
+/// statements, or a DSL statement rendered for the database's dialect.
+pub struct Migration {
+    pub version: i64,
+    pub name: String,
+    up: Vec<Step>,
+    down: Option<Vec<Step>>,
+}
+
+impl Migration {
+    pub fn new(version: i64, name: impl Into<String>) -> Migration {
+        Migration {
+            version,
+            name: name.into(),
+            up: Vec::new(),
+            down: None,
+        }
+    }
+
+    pub fn up_sql(mut self, sql: impl Into<String>) -> Self {
+        self.up.push(Step::Sql(sql.into()));
+        self
+    }
+
+    pub fn up<S>(mut self, stmt: S) -> Self
+    where
+        S: Statement<LiteralContext> + Send + Sync + 'static,
+    {
+        self.up.push(Step::Statement(Box::new(stmt)));
+        self
+    }
+
+    pub fn down_sql(mut self, sql: impl Into<String>) -> Self {
+        self.down
+            .get_or_insert_with(Vec::new)
+            .push(Step::Sql(sql.into()));
+        self
+    }
+
+    pub fn down<S>(mut self, stmt: S) -> Self
+    where
+        S: Statement<LiteralContext> + Send + Sync + 'static,
+    {
+        self.down
+            .get_or_insert_with(Vec::new)
+            .push(Step::Statement(Box::new(stmt)));
+        self
+    }
+
+    pub fn is_reversible(&self) -> bool {
+        self.down.is_some()
+    }
+
+    /// Hash of the up steps as run on `dialect`, recorded when the
+    /// migration is applied to notice later edits.
+    pub fn checksum(&self, dialect: Dialect) -> Result<String, Error> {
+        let mut hasher = Sha256::new();
+        for sql in render(&self.up, dialect)? {
+            hasher.update(sql.as_bytes());
+            hasher.update(b"\n");
+        }
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect())
+    }
+}
+
+impl fmt::Debug for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migration")
+            .field("version", &self.version)
+            .field("name", &self.name)
+            .field("reversible", &self.is_reversible())
+            .finish()
+    }
+}
+
+fn render(steps: &[Step], dialect: Dialect) -> Result<Vec<String>, Error> {
+    steps.iter().map(|step| step.render(dialect)).collect()
+}
+
+/// A migration recorded in the bookkeeping table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedMigration {
+    pub version: i64,
+    pub name: String,
+    pub direction: Direction,
+    pub statements: Vec<String>,
+    checksum: String,
+}
+
+/// The migrations `Migrator::run` or `Migrator::rollback` would apply, in
+/// order. Printing it gives the SQL, for dry runs.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Plan {
+    pub migrations: Vec<PlannedMigration>,
+}
+
+impl Plan {
+    pub fn is_empty(&self) -> bool {
+        self.migrations.is_empty()
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for migration in &self.migrations {
+            let direction = match migration.direction {
+                Direction::Up => "up",
+                Direction::Down => "down",
+            };
+            writeln!(
+                f,
+                "-- {} {} ({})",
+                migration.version, migration.name, direction
+            )?;
+            for sql in &migration.statements {
+                writeln!(f, "{};", sql.trim().trim_end_matches(';'))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Applies numbered migrations in order and records them, with a checksum,
+/// in a bookkeeping table.
+///
+/// Before anything runs the recorded migrations are checked against the
+/// known ones: a recorded migration that is unknown or was edited since,
+/// and a pending migration numbered below the latest applied one, are
+/// errors. Each migration runs in its own transaction on Sqlite and Pg.
+/// On Sqlite foreign keys are off while it runs and checked before it is
+/// committed, so tables can be rebuilt without touching referencing rows.
+/// MySQL commits DDL implicitly, so a failing migration can be left half
+/// applied there.
+pub struct Migrator {
+    migrations: Vec<Migration>,
+    table: String,
+}
+
+impl Default for Migrator {
+    fn default() -> Self {
+        Migrator::new()
+    }
+}
+
+impl Migrator {
+    pub fn new() -> Migrator {
+        Migrator {
+            migrations: Vec::new(),
+            table: MIGRATIONS_TABLE.to_owned(),
+        }
+    }
+
+    /// Reads `<version>_<name>.up.sql` and `<version>_<name>.down.sql`
+    /// files from `path`. A `<version>_<name>.sql` file is an up migration
+    /// without down.
+    pub fn from_dir(path: impl AsRef<Path>) -> Result<Migrator, Error> {
+        let path = path.as_ref();
+        let io_error = |err: std::io::Error| {
+            Error::Migration(format!("could not read {}: {}", path.display(), err))
+        };
+
+        let mut files = std::fs::read_dir(path)
+            .map_err(io_error)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io_error)?;
+        files.sort();
+
+        let mut migrator = Migrator::new();
+        for file in files {
+            let file_name = match file.file_name().and_then(|name| name.to_str()) {
+                Some(name) if name.ends_with(".sql") => name.trim_end_matches(".sql"),
+                _ => continue,
+            };
+            let (stem, down) = match file_name.strip_suffix(".down") {
+                Some(stem) => (stem, true),
+                None => (file_name.trim_end_matches(".up"), false),
+            };
+            let (version, name) = stem.split_once('_').unwrap_or((stem, ""));
+            let version = version.parse::<i64>().map_err(|_| {
+                Error::Migration(format!(
+                    "{} does not start with a version number",
+                    file.display()
+                ))
+            })?;
+            let sql = std::fs::read_to_string(&file).map_err(io_error)?;
+
+            let idx = match migrator
+                .migrations
+                .iter()
+                .position(|m| m.version == version)
+            {
+                Some(idx) => idx,
+                None => {
+                    migrator.migrations.push(Migration::new(version, name));
+                    migrator.migrations.len() - 1
+                }
+            };
+            let migration = &mut migrator.migrations[idx];
+            if down {
+                migration
+                    .down
+                    .get_or_insert_with(Vec::new)
+                    .push(Step::Sql(sql));
+            } else {
+                migration.up.push(Step::Sql(sql));
+            }
+        }
+
+        Ok(migrator)
+    }
+
+    /// Name of the bookkeeping table, `_nibard_migrations` by default.
+    pub fn table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    pub fn migration(mut self, migration: Migration) -> Self {
+        self.migrations.push(migration);
+        self
+    }
+
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// The migrations recorded as applied, oldest first.
+    pub async fn applied(&self, db: &Database) -> Result<Vec<AppliedMigration>, Error> {
+        let dialect = db.dialect();
+        if !self.has_table(db).await? {
+            return Ok(Vec::new());
+        }
+
+        let text = if dialect == Dialect::MySQL {
+            "CHAR"
+        } else {
+            "TEXT"
+        };
+        let sql = format!(
+            "SELECT CAST(version AS {text}) AS version, CAST(name AS {text}) AS name, \
+             CAST(checksum AS {text}) AS checksum FROM {} ORDER BY version",
+            self.table,
+            text = text
+        );
+
+        let rows = Query::new(sql, Vec::new())
+            .fetch(db)
+            .try_collect::<Vec<_>>()
+            .await?;
+        rows.iter()
+            .map(|row| {
+                let version = text_value(row, "version")?;
+                Ok(AppliedMigration {
+                    version: version.parse().map_err(|_| {
+                        Error::Migration(format!("invalid migration version {}", version))
+                    })?,
+                    name: text_value(row, "name")?,
+                    checksum: text_value(row, "checksum")?,
+                })
+            })
+            .collect()
+    }
+
+    /// The pending migrations `run` would apply. Nothing is changed, so
+    /// this is the dry run.
+    pub async fn plan(&self, db: &Database) -> Result<Plan, Error> {
+        let dialect = db.dialect();
+        let applied = self.applied(db).await?;
+        self.validate(dialect, &applied)?;
+
+        let mut pending = self
+            .migrations
+            .iter()
+            .filter(|m| !applied.iter().any(|a| a.version == m.version))
+            .collect::<Vec<_>>();
+        pending.sort_by_key(|m| m.version);
+
+        let migrations = pending
+            .into_iter()
+            .map(|migration| {
+                Ok(PlannedMigration {
+                    version: migration.version,
+                    name: migration.name.clone(),
+                    direction: Direction::Up,
+                    statements: render(&migration.up, dialect)?,
+                    checksum: migration.checksum(dialect)?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Plan { migrations })
+    }
+
+    /// The applied migrations above `version` that `rollback` would revert,
+    /// newest first.
+    pub async fn plan_rollback(&self, db: &Database, version: i64) -> Result<Plan, Error> {
+        let dialect = db.dialect();
+        let applied = self.applied(db).await?;
+        self.validate(dialect, &applied)?;
+
+        let migrations = applied
+            .iter()
+            .rev()
+            .filter(|applied| applied.version > version)
+            .map(|applied| {
+                let migration = self.find(applied.version).ok_or_else(|| unknown(applied))?;
+                let down = migration.down.as_ref().ok_or_else(|| {
+                    Error::Migration(format!(
+                        "migration {} {} cannot be reverted",
+                        migration.version, migration.name
+                    ))
+                })?;
+                Ok(PlannedMigration {
+                    version: migration.version,
+                    name: migration.name.clone(),
+                    direction: Direction::Down,
+                    statements: render(down, dialect)?,
+                    checksum: applied.checksum.clone(),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Plan { migrations })
+    }
+
+    /// Applies the pending migrations, returning what was applied.
+    pub async fn run(&self, db: &Database) -> Result<Plan, Error> {
+        let plan = self.plan(db).await?;
+        self.apply(db, &plan).await?;
+        Ok(plan)
+    }
+
+    /// Reverts the applied migrations above `version`, returning what was
+    /// reverted.
+    pub async fn rollback(&self, db: &Database, version: i64) -> Result<Plan, Error> {
+        let plan = self.plan_rollback(db, version).await?;
+        self.apply(db, &plan).await?;
+        Ok(plan)
+    }
+
+    async fn apply(&self, db: &Database, plan: &Plan) -> Result<(), Error> {
+        if plan.is_empty() {
+            return Ok(());
+        }
+        let dialect = db.dialect();
+
+        let create = CreateTable::new(self.table.clone())
+            .column(
+                Column::new("version", Type::BigInt)
+                    .primary_key()
+                    .not_null(),
+            )
+            .column(Column::new("name", Type::VarChar(255)).not_null())
+            .column(Column::new("checksum", Type::VarChar(64)).not_null());
+        let create = create
+            .to_sql_literal(dialect)
+            .map_err(|err| ConnectionError::Build(Box::new(err)))?;
+        db.execute(create.as_str()).await?;
+
+        for migration in &plan.migrations {
+            let record = match migration.direction {
+                Direction::Up => Insert::new(self.table.as_str())
+                    .set("version", migration.version)
+                    .set("name", migration.name.clone())
+                    .set("checksum", migration.checksum.clone())
+                    .to_query(dialect),
+                Direction::Down => DeleteStmt::new(self.table.as_str())
+                    .filter(Expr::col("version").eql(Expr::value(migration.version)))
+                    .to_query(dialect),
+            }
+            .map_err(|err| ConnectionError::Build(Box::new(err)))?;
+
+            match dialect {
+                Dialect::MySQL => {
+                    for sql in &migration.statements {
+                        execute_many(db, sql).await?;
+                    }
+                    record.execute(db).await?;
+                }
+                Dialect::Sqlite => {
+                    // `PRAGMA foreign_keys` is a no-op inside a transaction,
+                    // so table rebuilds would delete or reject the rows
+                    // referencing the rebuilt table. Turn it off first.
+                    let mut conn = db.acquire().await?;
+                    execute_many(&mut conn, "PRAGMA foreign_keys = OFF").await?;
+                    let applied = async {
+                        let mut tx = conn.begin().await?;
+                        for sql in &migration.statements {
+                            execute_many(&mut tx, sql).await?;
+                        }
+                        check_foreign_keys(&mut tx, migration).await?;
+                        record.execute(&mut tx).await?;
+                        tx.commit().await?;
+                        Ok::<_, Error>(())
+                    }
+                    .await;
+                    execute_many(&mut conn, "PRAGMA foreign_keys = ON").await?;
+                    applied?;
+                }
+                Dialect::Pg => {
+                    let mut tx = db.begin().await?;
+                    for sql in &migration.statements {
+                        execute_many(&mut tx, sql).await?;
+                    }
+                    record.execute(&mut tx).await?;
+                    tx.commit().await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn has_table(&self, db: &Database) -> Result<bool, Error> {
+        let sql = match db.dialect() {
+            Dialect::Sqlite => "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?",
+            Dialect::Pg => {
+                "SELECT table_name::text AS name FROM information_schema.tables \
+                 WHERE table_schema = current_schema() AND table_name = $1"
+            }
+            Dialect::MySQL => {
+                "SELECT CAST(table_name AS CHAR) AS name FROM information_schema.tables \
+                 WHERE table_schema = DATABASE() AND table_name = ?"
+            }
+        };
+        let rows = Query::new(sql.to_owned(), vec![Value::Text(self.table.clone())])
+            .fetch(db)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(!rows.is_empty())
+    }
+
+    fn find(&self, version: i64) -> Option<&Migration> {
+        self.migrations.iter().find(|m| m.version == version)
+    }
+
+    fn validate(&self, dialect: Dialect, applied: &[AppliedMigration]) -> Result<(), Error> {
+        for (idx, migration) in self.migrations.iter().enumerate() {
+            if self.migrations[..idx]
+                .iter()
+                .any(|m| m.version == migration.version)
+            {
+                return Err(Error::Migration(format!(
+                    "duplicate migration version {}",
+                    migration.version
+                )));
+            }
+        }
+
+        for applied in applied {
+            let migration = self.find(applied.version).ok_or_else(|| unknown(applied))?;
+            if migration.checksum(dialect)? != applied.checksum {
+                return Err(Error::Migration(format!(
+                    "migration {} {} was edited after it was applied",
+                    migration.version, migration.name
+                )));
+            }
+        }
+
+        if let Some(latest) = applied.iter().map(|applied| applied.version).max() {
+            let out_of_order = self
+                .migrations
+                .iter()
+                .filter(|m| m.version < latest)
+                .find(|m| !applied.iter().any(|a| a.version == m.version));
+            if let Some(migration) = out_of_order {
+                return Err(Error::Migration(format!(
+                    "migration {} {} is older than the latest applied migration {}",
+                    migration.version, migration.name, latest
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn unknown(applied: &AppliedMigration) -> Error {
+    Error::Migration(format!(
+        "applied migration {} {} is unknown",
+        applied.version, applied.name
+    ))
+}
+
+async fn execute_many<'c, E: Executor<'c>>(executor: E, sql: &'c str) -> Result<(), Error> {
+    executor
+        .execute_many(sql)
+        .await
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(())
+}
+
+/// With foreign keys off Sqlite does not reject rows violating them, and
+/// `PRAGMA foreign_key_check` reports them as rows instead of failing.
+async fn check_foreign_keys(
+    tx: &mut DatabaseTransaction<'_>,
+    migration: &PlannedMigration,
+) -> Result<(), Error> {
+    let violations = Query::new("PRAGMA foreign_key_check".to_owned(), Vec::new())
+        .fetch(tx)
+        .try_collect::<Vec<_>>()
+        .await?;
+    match violations.first() {
+        Some(row) => Err(Error::Migration(format!(
+            "migration {} {} leaves rows in {} violating their foreign keys",
+            migration.version,
+            migration.name,
+            text_value(row, "table")?
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn text_value(row: &DatabaseRow, column: &str) -> Result<String, ConnectionError> {
+    match row.try_get(column, None)? {
+        Value::Text(text) => Ok(text),
+        other => Err(ConnectionError::Decode(format!(
+            "unexpected value for {}: {:?}",
+            column, other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    fn applied(migration: &Migration) -> AppliedMigration {
+        AppliedMigration {
+            version: migration.version,
+            name: migration.name.clone(),
+            checksum: migration.checksum(Dialect::Sqlite).unwrap(),
+        }
+    }
+
+    fn message(result: Result<(), Error>) -> String {
+        match result {
+            Err(Error::Migration(message)) => message,
+            other => panic!("expected a migration error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn from_dir() {
+        let dir = std::env::temp_dir().join(format!("nibard-migrate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (file, sql) in [
+            ("2_add_email.up.sql", "ALTER TABLE users ADD email TEXT"),
+            ("2_add_email.down.sql", "ALTER TABLE users DROP email"),
+            ("1_create_users.sql", "CREATE TABLE users(id INTEGER)"),
+            ("README.md", "not a migration"),
+        ] {
+            fs::write(dir.join(file), sql).unwrap();
+        }
+
+        let migrator = Migrator::from_dir(&dir).unwrap();
+        let migrations = migrator
+            .migrations()
+            .iter()
+            .map(|m| (m.version, m.name.as_str(), m.is_reversible()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            migrations,
+            [(1, "create_users", false), (2, "add_email", true)]
+        );
+        let up = render(&migrator.migrations()[1].up, Dialect::Sqlite).unwrap();
+        assert_eq!(up, ["ALTER TABLE users ADD email TEXT"]);
+
+        fs::write(dir.join("third.sql"), "SELECT 1").unwrap();
+        assert!(matches!(
+            Migrator::from_dir(&dir),
+            Err(Error::Migration(message)) if message.contains("version number")
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            Migrator::from_dir(&dir),
+            Err(Error::Migration(message)) if message.contains("could not read")
+        ));
+    }
+
+    #[test]
+    fn checksum() {
+        let create = || Migration::new(1, "create").up_sql("CREATE TABLE a(id INTEGER)");
+        let checksum = create().checksum(Dialect::Sqlite).unwrap();
+        assert_eq!(checksum.len(), 64);
+        assert_eq!(checksum, create().checksum(Dialect::Sqlite).unwrap());
+        // only the up steps are hashed
+        assert_eq!(
+            checksum,
+            create()
+                .down_sql("DROP TABLE a")
+                .checksum(Dialect::Sqlite)
+                .unwrap()
+        );
+        assert_ne!(
+            checksum,
+            Migration::new(1, "create")
+                .up_sql("CREATE TABLE a(id BIGINT)")
+                .checksum(Dialect::Sqlite)
+                .unwrap()
+        );
+
+        let table = || {
+            Migration::new(1, "create")
+                .up(CreateTable::new("a").column(Column::new("id", Type::Auto).primary_key()))
+        };
+        assert_ne!(
+            table().checksum(Dialect::Sqlite).unwrap(),
+            table().checksum(Dialect::Pg).unwrap()
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let first = || Migration::new(1, "first").up_sql("CREATE TABLE a(id INTEGER)");
+        let second = || Migration::new(2, "second").up_sql("CREATE TABLE b(id INTEGER)");
+        let third = || Migration::new(3, "third").up_sql("CREATE TABLE c(id INTEGER)");
+
+        let migrator = Migrator::new().migration(first()).migration(second());
+        assert!(migrator.validate(Dialect::Sqlite, &[]).is_ok());
+        assert!(migrator
+            .validate(Dialect::Sqlite, &[applied(&first())])
+            .is_ok());
+
+        let duplicate = Migrator::new()
+            .migration(first())
+            .migration(Migration::new(1, "again").up_sql("SELECT 1"));
+        assert_eq!(
+            message(duplicate.validate(Dialect::Sqlite, &[])),
+            "duplicate migration version 1"
+        );
+
+        let mut edited = applied(&first());
+        edited.checksum = applied(&second()).checksum;
+        assert_eq!(
+            message(migrator.validate(Dialect::Sqlite, &[edited])),
+            "migration 1 first was edited after it was applied"
+        );
+
+        assert_eq!(
+            message(migrator.validate(Dialect::Sqlite, &[applied(&third())])),
+            "applied migration 3 third is unknown"
+        );
+
+        let out_of_order = Migrator::new()
+            .migration(first())
+            .migration(second())
+            .migration(third());
+        assert_eq!(
+            message(
+                out_of_order.validate(Dialect::Sqlite, &[applied(&first()), applied(&third())])
+            ),
+            "migration 2 second is older than the latest applied migration 3"
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn sqlite_rebuild_keeps_references() {
+        use nibard_dsl::create::AlterTable;
+
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        let folders = || {
+            CreateTable::new("folders")
+                .column(Column::new("id", Type::Int).primary_key())
+                .column(Column::new("name", Type::Text))
+        };
+        let migrator = Migrator::new()
+            .migration(
+                Migration::new(1, "create")
+                    .up(folders())
+                    .up_sql(
+                        "CREATE TABLE files(id INTEGER PRIMARY KEY, \
+                         folder_id INTEGER REFERENCES folders(id) ON DELETE CASCADE)",
+                    )
+                    .up_sql(
+                        "INSERT INTO folders VALUES (1, 'docs'); INSERT INTO files VALUES (1, 1)",
+                    ),
+            )
+            .migration(
+                Migration::new(2, "name")
+                    .up(AlterTable::set_not_null("folders", "name").schema(folders())),
+            );
+        assert_eq!(migrator.run(&db).await.unwrap().migrations.len(), 2);
+
+        let files = Query::new("SELECT id FROM files".to_owned(), Vec::new())
+            .fetch(&db)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(migrator.applied(&db).await.unwrap().len(), 2);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn sqlite_foreign_key_check() {
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        let migrator = Migrator::new()
+            .migration(
+                Migration::new(1, "create")
+                    .up_sql("CREATE TABLE folders(id INTEGER PRIMARY KEY)")
+                    .up_sql(
+                        "CREATE TABLE files(id INTEGER PRIMARY KEY, \
+                         folder_id INTEGER REFERENCES folders(id))",
+                    ),
+            )
+            .migration(Migration::new(2, "orphan").up_sql("INSERT INTO files VALUES (1, 1)"));
+        match migrator.run(&db).await {
+            Err(Error::Migration(message)) => assert_eq!(
+                message,
+                "migration 2 orphan leaves rows in files violating their foreign keys"
+            ),
+            other => panic!("unexpected result {:?}", other.map(|plan| plan.to_string())),
+        }
+        assert_eq!(migrator.applied(&db).await.unwrap().len(), 1);
+    }
+}
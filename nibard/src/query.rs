@@ -1,7 +1,7 @@
 use async_stream::stream;
 use futures::Stream;
 use nibard_connection::{DatabaseRow, Error, Execute, Executor, QueryResult};
-use nibard_dsl::{build, DefaultContext, Statement};
+use nibard_dsl::{build, prepare, DefaultContext, Error as DslError, Statement, Template};
 use nibard_shared::{Dialect, Value};
 
 #[derive(Clone, Debug, PartialEq)]
@@ -14,6 +14,21 @@ impl Query {
     pub fn new(sql: String, values: Vec<Value>) -> Query {
         Query { sql, values }
     }
+
+    /// Binds the named parameters of `template`. The SQL is the same for
+    /// every binding, so the driver's statement cache is reused.
+    pub fn from_template<K, V, I>(template: &Template, params: I) -> Result<Query, DslError>
+    where
+        K: AsRef<str>,
+        V: Into<Value>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let values = template.bind(params)?;
+        Ok(Query {
+            sql: template.sql().to_owned(),
+            values,
+        })
+    }
 }
 
 impl Query {
@@ -59,9 +74,13 @@ impl<'q> Execute<'q> for &'q Query {
 }
 
 pub trait StatementQuery: Statement<DefaultContext> + Sized {
-    fn to_query(self, dialect: Dialect) -> Query {
-        let (sql, values) = build(dialect, self).unwrap();
-        Query { sql, values }
+    fn to_query(self, dialect: Dialect) -> Result<Query, DslError> {
+        let (sql, values) = build(dialect, self)?;
+        Ok(Query { sql, values })
+    }
+
+    fn prepare(self, dialect: Dialect) -> Result<Template, DslError> {
+        prepare(dialect, self)
     }
 }
 
@@ -72,3 +91,42 @@ impl<S> StatementQuery for S where S: Statement<DefaultContext> {}
 
 //     Query { sql, values }
 // }
+
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use super::*;
+    use futures::TryStreamExt;
+    use nibard_connection::{Database, Row};
+    use nibard_dsl::prelude::*;
+    use nibard_dsl::query::Param;
+
+    #[tokio::test]
+    async fn null_params() {
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        db.execute("CREATE TABLE notes(id INTEGER PRIMARY KEY, label TEXT, score REAL)")
+            .await
+            .unwrap();
+        db.execute("INSERT INTO notes (id, label, score) VALUES (1, NULL, 0.5)")
+            .await
+            .unwrap();
+
+        let select = "notes".select("id").filter(
+            "label"
+                .eql(Param::named("label"))
+                .or("score".eql(Param::named("score"))),
+        );
+        let template = select.prepare(Dialect::Sqlite).unwrap();
+
+        let query =
+            Query::from_template(&template, [("label", None), ("score", Some(0.5))]).unwrap();
+        let rows = query.fetch(&db).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].try_get("id", None).unwrap(), Value::BigInt(1));
+
+        let query =
+            Query::from_template(&template, [("label", Value::Null), ("score", Value::Null)])
+                .unwrap();
+        let rows = query.fetch(&db).try_collect::<Vec<_>>().await.unwrap();
+        assert!(rows.is_empty());
+    }
+}
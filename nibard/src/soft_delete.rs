@@ -0,0 +1,152 @@
+use super::query::{Query, StatementQuery};
+use nibard_dsl::ast::SoftDelete;
+use nibard_dsl::{Context, DefaultContext, Error as DslError, Statement};
+use nibard_shared::Dialect;
+
+/// Builds `stmt`, rewriting it with the soft-delete `policy` of the
+/// executor, see `Database::with_soft_delete`. Statements built with
+/// `nibard_dsl::ast` are rewritten as a tree, the typed builders apply the
+/// policy while building, see `DefaultContext::with_soft_delete`.
+pub(crate) fn to_query<S: Statement<DefaultContext>>(
+    stmt: S,
+    dialect: Dialect,
+    policy: &SoftDelete,
+) -> Result<Query, DslError> {
+    if policy.is_empty() {
+        return stmt.to_query(dialect);
+    }
+    match stmt.to_ast() {
+        Some(ast) => ast.rewrite(&mut policy.clone()).to_query(dialect),
+        None => {
+            let mut ctx = DefaultContext::new(dialect).with_soft_delete(policy.clone());
+            stmt.build(&mut ctx)?;
+            let (sql, values) = ctx.build()?;
+            Ok(Query::new(sql, values))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use crate::prelude::*;
+    use futures::TryStreamExt;
+    use nibard_connection::Database;
+    use nibard_dsl::ast::{DeleteStmt, Expr, SelectStmt, SoftDelete};
+    use nibard_dsl::insert::Insert;
+    use nibard_dsl::prelude::*;
+    use nibard_dsl::query::Join;
+
+    async fn open() -> Database {
+        let db = Database::open("sqlite::memory:").await.unwrap();
+        db.execute("CREATE TABLE posts(id INTEGER PRIMARY KEY, deleted_at TEXT)")
+            .await
+            .unwrap();
+        db.with_soft_delete(SoftDelete::new().table("posts", "deleted_at"))
+    }
+
+    #[tokio::test]
+    async fn per_database() {
+        let db = open().await;
+        for id in 1..=2 {
+            db.exec(Insert::new("posts").set("id", id)).await.unwrap();
+        }
+        db.exec(DeleteStmt::new("posts").filter(Expr::col("id").eql(Expr::value(1))))
+            .await
+            .unwrap();
+
+        let posts = SelectStmt::new("posts").column("id");
+        let rows = db
+            .query(posts.clone())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        let rows = db
+            .query(posts.clone().with_deleted())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+
+        // transactions keep the policy of their database
+        let mut tx = db.begin().await.unwrap();
+        let rows = (&mut tx)
+            .query(posts.clone())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        tx.commit().await.unwrap();
+
+        // other databases are not affected
+        let plain = Database::open("sqlite::memory:").await.unwrap();
+        assert!(plain.soft_delete().is_empty());
+    }
+
+    #[tokio::test]
+    async fn typed_statements() {
+        let db = open().await;
+        db.execute("CREATE TABLE tags(id INTEGER PRIMARY KEY)")
+            .await
+            .unwrap();
+        for id in 1..=3 {
+            db.exec(Insert::new("posts").set("id", id)).await.unwrap();
+        }
+        db.exec(Insert::new("tags").set("id", 1)).await.unwrap();
+
+        db.exec(nibard_dsl::delete::delete("posts").filter(Expr::col("id").eql(Expr::value(1))))
+            .await
+            .unwrap();
+        let rows = db
+            .query("posts".select("id").filter("id".neq(3)))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        let rows = db
+            .query(
+                "tags"
+                    .select("p.id")
+                    .join(Join::inner("posts".table_alias("p"))),
+            )
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+
+        // the typed delete marked the row
+        let rows = db
+            .query(SelectStmt::new("posts").column("id").with_deleted())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 3);
+
+        // tables without a soft-delete column are untouched
+        let rows = db
+            .query("tags".select("id"))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        db.exec(nibard_dsl::delete::delete("tags")).await.unwrap();
+        let rows = db
+            .query("tags".select("id"))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(rows.is_empty());
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn explain() {
+        let db = open().await;
+        let plan = db
+            .explain(SelectStmt::new("posts").column("id"))
+            .await
+            .unwrap();
+        assert!(plan.has_full_scan());
+        assert!(db.explain("posts".select("id")).await.is_ok());
+    }
+}